@@ -25,9 +25,10 @@ async function main () {
     // connect to local node
     const wsProvider = new WsProvider('ws://127.0.0.1:9944');  
     const api = await ApiPromise.create({ provider: wsProvider,"types" :{
+        "CrmId": "u32",
         "Voting": {
             "changeid": "u32",
-            "crmid": "u32",
+            "crmid": "CrmId",
             "quorum": "u32",
             "nrvotesyes": "u32",
             "nrvotesno": "u32",
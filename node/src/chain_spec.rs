@@ -1,6 +1,6 @@
 use sp_core::{Pair, Public, sr25519};
 use polkamusic_node_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
+	AccountId, AuraConfig, BalancesConfig, CrmConfig, CrmId, GenesisConfig, GrandpaConfig,
 	SudoConfig, SystemConfig, WASM_BINARY, Signature
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -124,6 +124,12 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	))
 }
 
+// Contract stored at the genesis as example, the system contract 1 owned by the sudo account
+fn example_contract(owner: AccountId) -> (AccountId, CrmId, Vec<u8>) {
+	let crmdata = br#"{"ipfshash":"QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u","ipfshashprivate":"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51}"#;
+	(owner, 1, crmdata.to_vec())
+}
+
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
@@ -150,7 +156,11 @@ fn testnet_genesis(
 		}),
 		pallet_sudo: Some(SudoConfig {
 			// Assign network admin rights.
-			key: root_key,
+			key: root_key.clone(),
+		}),
+		pallet_crm: Some(CrmConfig {
+			// Contracts stored at the genesis, validated as the new contracts.
+			contracts: vec![example_contract(root_key)],
 		}),
 	}
 }
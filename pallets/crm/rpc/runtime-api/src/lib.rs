@@ -0,0 +1,15 @@
+//! Runtime API of the crm pallet, called by the crm RPC.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_crm::CrmContract;
+
+sp_api::decl_runtime_apis! {
+    pub trait CrmApi<AccountId, CrmId> where AccountId: Codec, CrmId: Codec {
+        /// The main crm data of a contract decoded, None when the contract is not owned by the account.
+        fn get_crm_contract(account: AccountId, crmid: CrmId) -> Option<CrmContract>;
+        /// The blake2-256 hash of the main crm data of a contract, None when the contract is not owned by the account.
+        fn crm_digest(account: AccountId, crmid: CrmId) -> Option<[u8; 32]>;
+    }
+}
@@ -0,0 +1,77 @@
+//! RPC of the crm pallet: the contracts decoded, for the front-ends that don't parse the json of the crm data.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+pub use pallet_crm_rpc_runtime_api::CrmApi as CrmRuntimeApi;
+use pallet_crm_rpc_runtime_api::CrmContract;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+#[rpc]
+pub trait CrmApi<BlockHash, AccountId, CrmId> {
+    /// The main crm data of a contract decoded, null when the contract is not owned by the account.
+    #[rpc(name = "crm_getCrmContract")]
+    fn get_crm_contract(&self, account: AccountId, crmid: CrmId, at: Option<BlockHash>) -> Result<Option<CrmContract>>;
+    /// The blake2-256 hash of the main crm data of a contract, null when the contract is not owned by the account.
+    #[rpc(name = "crm_getCrmDigest")]
+    fn crm_digest(&self, account: AccountId, crmid: CrmId, at: Option<BlockHash>) -> Result<Option<[u8; 32]>>;
+}
+
+/// Implementation of the crm RPC, calling the runtime API.
+pub struct Crm<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Crm<C, B> {
+    /// Create the crm RPC for the client given.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client, _marker: Default::default() }
+    }
+}
+
+// error code of the failures of the runtime API
+const RUNTIME_ERROR: i64 = 1;
+
+impl<C, Block, AccountId, CrmId> CrmApi<<Block as BlockT>::Hash, AccountId, CrmId> for Crm<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: CrmRuntimeApi<Block, AccountId, CrmId>,
+    AccountId: Codec,
+    CrmId: Codec,
+{
+    fn get_crm_contract(
+        &self,
+        account: AccountId,
+        crmid: CrmId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<CrmContract>> {
+        let api = self.client.runtime_api();
+        // the best block when the block is not given
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.get_crm_contract(&at, account, crmid).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(RUNTIME_ERROR),
+            message: "Unable to query the contract.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+    fn crm_digest(
+        &self,
+        account: AccountId,
+        crmid: CrmId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<[u8; 32]>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.crm_digest(&at, account, crmid).map_err(|e| RpcError {
+            code: ErrorCode::ServerError(RUNTIME_ERROR),
+            message: "Unable to query the digest of the contract.".into(),
+            data: Some(format!("{:?}", e).into()),
+        })
+    }
+}
@@ -0,0 +1,429 @@
+//! Benchmarks of the crm pallet: the calls validating the json of a contract, over the length of the crm data. The
+//! master, composition and other contracts data are at their maximum length, the worst case of their validation.
+//! The votes are benchmarked on the approving vote, which applies the change proposed.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+
+// minimum length of the crm data benchmarked, the main fields without padding
+const MIN_CRMDATA_LENGTH: u32 = 320;
+
+// main fields of the crm data, the last ones of the json
+const CRMDATA_FIELDS: &[u8] = br#""ipfshash":"QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u","ipfshashprivate":"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51}"#;
+
+// main fields of the crm data with a crowdfunding share and no campaign
+const CROWDFUNDING_FIELDS: &[u8] = br#""ipfshash":"QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u","ipfshashprivate":"QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o","globalquorum":100,"mastershare":40,"masterquorum":51,"compositionshare":40,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"crodwfundingshares":20}"#;
+
+// private hash appended by the benchmark of add_private_hash
+const PRIVATE_HASH: &[u8] = b"QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+// maximum length of the change proposals of the master, composition and other contracts data
+const MAX_PROPOSAL_LENGTH: usize = 1023;
+
+// crm data of the given length, padded by a "note" field placed before the main fields: every field read
+// scans the whole json, the worst case of json_get_value
+fn crmdata_of_length(l: u32) -> Vec<u8> {
+    crmdata_with_fields(l, CRMDATA_FIELDS)
+}
+
+fn crmdata_with_fields(l: u32, fields: &[u8]) -> Vec<u8> {
+    let mut crmdata = br#"{"note":""#.to_vec();
+    let padding = (l as usize).saturating_sub(crmdata.len() + 2 + fields.len());
+    crmdata.resize(crmdata.len() + padding, b'x');
+    crmdata.extend_from_slice(br#"","#);
+    crmdata.extend_from_slice(fields);
+    crmdata
+}
+
+// funded account creating the contracts, the deposit and the creation fee are paid
+fn funded_caller<T: Config>() -> T::AccountId {
+    let caller: T::AccountId = whitelisted_caller();
+    T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 2u32.into());
+    caller
+}
+
+// crmid of the contracts created by the benchmarks, above the reserved range
+fn crmid<T: Config>() -> T::CrmId {
+    T::ReservedCrmIdMax::get().saturating_add(One::one())
+}
+
+// account of "Bob", the holder of the master and composition data of the benchmarks, decoded as in the votes
+fn holder<T: Config>() -> T::AccountId {
+    let buffer: [u8; 32] =
+        hex::FromHex::from_hex("8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48").unwrap_or_default();
+    T::AccountId::decode(&mut &buffer[..]).unwrap_or_default()
+}
+
+// contract of the holder for the change proposals, with the master, composition and other contracts data at their
+// maximum length: the other contracts data references a second contract of the holder. The lock period after the
+// creation is over.
+fn contract_of_holder<T: Config>(caller: &T::AccountId, crmdata: Vec<u8>) -> Result<T::CrmId, &'static str> {
+    let crmid = crmid::<T>();
+    let other = crmid.saturating_add(One::one());
+    Pallet::<T>::new_contract(
+        RawOrigin::Signed(caller.clone()).into(),
+        other,
+        crmdata_of_length(MIN_CRMDATA_LENGTH),
+        holders_of_length(b"master", MAX_HOLDERS_LENGTH),
+        composition(),
+        Vec::new(),
+    )?;
+    Pallet::<T>::new_contract(
+        RawOrigin::Signed(caller.clone()).into(),
+        crmid,
+        crmdata,
+        holders_of_length(b"master", MAX_HOLDERS_LENGTH),
+        holders_of_length(b"composition", MAX_HOLDERS_LENGTH),
+        othercontracts_of_length(other, MAX_HOLDERS_LENGTH),
+    )?;
+    let now = frame_system::Module::<T>::block_number();
+    frame_system::Module::<T>::set_block_number(now.saturating_add(T::UpdateLockPeriod::get()));
+    Ok(crmid)
+}
+
+// maximum length of the master, composition and other contracts data
+const MAX_HOLDERS_LENGTH: usize = 1024;
+
+fn master() -> Vec<u8> {
+    br#"{"master":[{"nickname":"Bob","account":"0x8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48","percentage":100}]}"#.to_vec()
+}
+
+fn composition() -> Vec<u8> {
+    br#"{"composition":[{"nickname":"Charlie","account":"0x90b5ab205c6974c9ea841be688864633dc9ca8a357843eeacf2314649965fe22","percentage":100}]}"#.to_vec()
+}
+
+// json of the records of a group of the given length: every record is read from the start of the json, the worst case
+// is many records, the padding is in the first record
+fn records_of_length(group: &[u8], records: &[Vec<u8>], len: usize) -> Vec<u8> {
+    let build = |padding: usize| {
+        let mut json = br#"{""#.to_vec();
+        json.extend_from_slice(group);
+        json.extend_from_slice(br#"":["#);
+        for (i, record) in records.iter().enumerate() {
+            if i > 0 {
+                json.push(b',');
+            }
+            json.extend_from_slice(br#"{"note":""#);
+            if i == 0 {
+                json.resize(json.len() + padding, b'x');
+            }
+            json.extend_from_slice(br#"","#);
+            json.extend_from_slice(record);
+        }
+        json.extend_from_slice(b"]}");
+        json
+    };
+    build(len.saturating_sub(build(0).len()))
+}
+
+// holders of a group (master or composition) with the total percentage of 100, at the given length
+fn holders_of_length(group: &[u8], len: usize) -> Vec<u8> {
+    let records: Vec<Vec<u8>> = [&b"20"[..], b"20", b"20", b"20", b"10", b"10"]
+        .iter()
+        .map(|percentage| {
+            let mut record = br#""nickname":"Bob","account":"0x8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48","percentage":"#.to_vec();
+            record.extend_from_slice(percentage);
+            record.push(b'}');
+            record
+        })
+        .collect();
+    records_of_length(group, &records, len)
+}
+
+// decimal digits of a crmid
+fn decimal<CrmId: UniqueSaturatedInto<u64>>(crmid: CrmId) -> Vec<u8> {
+    let mut id = Vec::new();
+    let mut n: u64 = crmid.unique_saturated_into();
+    loop {
+        id.insert(0, b'0' + (n % 10) as u8);
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    id
+}
+
+// other contracts data referencing the contract given, with the total percentage of 100, at the given length
+fn othercontracts_of_length<CrmId: UniqueSaturatedInto<u64>>(crmid: CrmId, len: usize) -> Vec<u8> {
+    let id = decimal(crmid);
+    let records: Vec<Vec<u8>> = (0..10)
+        .map(|_| {
+            let mut record = br#""id":"#.to_vec();
+            record.extend_from_slice(&id);
+            record.extend_from_slice(br#","percentage":10}"#);
+            record
+        })
+        .collect();
+    records_of_length(b"othercontracts", &records, len)
+}
+
+// change proposal of the given length for the contract given: the json built by the closure with the "crmid" field
+// inserted as first field
+fn proposal_of_length<CrmId: UniqueSaturatedInto<u64>>(crmid: CrmId, len: usize, json_of_length: impl Fn(usize) -> Vec<u8>) -> Vec<u8> {
+    let mut field = br#""crmid":"#.to_vec();
+    field.extend_from_slice(&decimal(crmid));
+    field.push(b',');
+    let json = json_of_length(len.saturating_sub(field.len()));
+    let mut proposal = b"{".to_vec();
+    proposal.extend_from_slice(&field);
+    proposal.extend_from_slice(&json[1..]);
+    proposal
+}
+
+benchmarks! {
+    new_contract {
+        let l in MIN_CRMDATA_LENGTH .. T::MaxCrmDataLength::get();
+        let caller = funded_caller::<T>();
+        let crmid = crmid::<T>();
+        // the contract referenced by the other contracts data
+        let other = crmid.saturating_add(One::one());
+        Pallet::<T>::new_contract(
+            RawOrigin::Signed(caller.clone()).into(),
+            other,
+            crmdata_of_length(MIN_CRMDATA_LENGTH),
+            master(),
+            composition(),
+            Vec::new(),
+        )?;
+        let crmdata = crmdata_of_length(l);
+        let master = holders_of_length(b"master", MAX_HOLDERS_LENGTH);
+        let composition = holders_of_length(b"composition", MAX_HOLDERS_LENGTH);
+        let othercontracts = othercontracts_of_length(other, MAX_HOLDERS_LENGTH);
+    }: _(RawOrigin::Signed(caller.clone()), crmid, crmdata, master, composition, othercontracts)
+    verify {
+        assert!(CrmData::<T>::contains_key(crmid));
+        assert_eq!(CrmOwner::<T>::get(crmid), Some(caller));
+    }
+
+    patch_crmdata {
+        let l in MIN_CRMDATA_LENGTH .. T::MaxCrmDataLength::get();
+        let caller = funded_caller::<T>();
+        let crmid = crmid::<T>();
+        Pallet::<T>::new_contract(
+            RawOrigin::Signed(caller.clone()).into(),
+            crmid,
+            crmdata_of_length(l),
+            master(),
+            composition(),
+            Vec::new(),
+        )?;
+        // the end of the lock period after the creation
+        let now = frame_system::Module::<T>::block_number();
+        frame_system::Module::<T>::set_block_number(now.saturating_add(T::UpdateLockPeriod::get()));
+        let patch = br#"{"mastershare":40,"compositionshare":60}"#.to_vec();
+    }: _(RawOrigin::Signed(caller), crmid, patch)
+    verify {
+        assert_eq!(CrmVersion::<T>::get(crmid), 2);
+    }
+
+    change_proposal_crmdata {
+        let l in MIN_CRMDATA_LENGTH .. T::MaxCrmDataLength::get();
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_of_length(MIN_CRMDATA_LENGTH))?;
+        let crmdata = proposal_of_length(crmid, l as usize, |len| crmdata_of_length(len as u32));
+    }: _(RawOrigin::Signed(caller), 1, crmdata)
+    verify {
+        assert!(CrmDataChangeProposal::<T>::contains_key(1));
+    }
+
+    vote_proposal_crmdata {
+        let l in MIN_CRMDATA_LENGTH .. T::MaxCrmDataLength::get();
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_of_length(MIN_CRMDATA_LENGTH))?;
+        let crmdata = proposal_of_length(crmid, l as usize, |len| crmdata_of_length(len as u32));
+        Pallet::<T>::change_proposal_crmdata(RawOrigin::Signed(caller).into(), 1, crmdata)?;
+    }: _(RawOrigin::Signed(holder::<T>()), 1, true)
+    verify {
+        // the holder of the whole master and composition shares approves the change
+        assert_eq!(CrmVersion::<T>::get(crmid), 2);
+    }
+
+    change_proposal_crm_masterdata {
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_of_length(MIN_CRMDATA_LENGTH))?;
+        let masterdata = proposal_of_length(crmid, MAX_PROPOSAL_LENGTH, |len| holders_of_length(b"master", len));
+    }: _(RawOrigin::Signed(caller), 1, masterdata)
+    verify {
+        assert!(CrmMasterDataChangeProposal::<T>::contains_key(1));
+    }
+
+    vote_proposal_crm_masterdata {
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_of_length(MIN_CRMDATA_LENGTH))?;
+        let masterdata = proposal_of_length(crmid, MAX_PROPOSAL_LENGTH, |len| holders_of_length(b"master", len));
+        Pallet::<T>::change_proposal_crm_masterdata(RawOrigin::Signed(caller).into(), 1, masterdata.clone())?;
+    }: _(RawOrigin::Signed(holder::<T>()), 1, true)
+    verify {
+        assert_eq!(CrmMasterData::<T>::get(crmid), Some(masterdata));
+    }
+
+    change_proposal_crm_compositiondata {
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_of_length(MIN_CRMDATA_LENGTH))?;
+        let compositiondata = proposal_of_length(crmid, MAX_PROPOSAL_LENGTH, |len| holders_of_length(b"composition", len));
+    }: _(RawOrigin::Signed(caller), 1, compositiondata)
+    verify {
+        assert!(CrmCompositionDataChangeProposal::<T>::contains_key(1));
+    }
+
+    vote_proposal_crm_compositiondata {
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_of_length(MIN_CRMDATA_LENGTH))?;
+        let compositiondata = proposal_of_length(crmid, MAX_PROPOSAL_LENGTH, |len| holders_of_length(b"composition", len));
+        Pallet::<T>::change_proposal_crm_compositiondata(RawOrigin::Signed(caller).into(), 1, compositiondata.clone())?;
+    }: _(RawOrigin::Signed(holder::<T>()), 1, true)
+    verify {
+        assert_eq!(CrmCompositionData::<T>::get(crmid), Some(compositiondata));
+    }
+
+    change_proposal_crm_othercontractsdata {
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_of_length(MIN_CRMDATA_LENGTH))?;
+        let other = crmid.saturating_add(One::one());
+        let othercontractsdata = proposal_of_length(crmid, MAX_PROPOSAL_LENGTH, |len| othercontracts_of_length(other, len));
+    }: _(RawOrigin::Signed(caller), 1, othercontractsdata)
+    verify {
+        assert!(CrmOtherContractsDataChangeProposal::<T>::contains_key(1));
+    }
+
+    vote_proposal_crm_othercontractsdata {
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_of_length(MIN_CRMDATA_LENGTH))?;
+        let other = crmid.saturating_add(One::one());
+        let othercontractsdata = proposal_of_length(crmid, MAX_PROPOSAL_LENGTH, |len| othercontracts_of_length(other, len));
+        Pallet::<T>::change_proposal_crm_othercontractsdata(RawOrigin::Signed(caller).into(), 1, othercontractsdata.clone())?;
+    }: _(RawOrigin::Signed(holder::<T>()), 1, true)
+    verify {
+        assert_eq!(CrmOtherContractsData::<T>::get(crmid), Some(othercontractsdata));
+    }
+
+    add_private_hash {
+        // room for the hash appended and the array of the private hashes
+        let l in MIN_CRMDATA_LENGTH .. T::MaxCrmDataLength::get().saturating_sub(PRIVATE_HASH.len() as u32 + 8);
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_of_length(l))?;
+    }: _(RawOrigin::Signed(caller), crmid, PRIVATE_HASH.to_vec(), Some(1))
+    verify {
+        assert_eq!(CrmVersion::<T>::get(crmid), 2);
+    }
+
+    set_crowdfounders {
+        // room for the "crowdfounders" field added
+        let l in MIN_CRMDATA_LENGTH .. T::MaxCrmDataLength::get().saturating_sub(32);
+        let caller = funded_caller::<T>();
+        let crmid = contract_of_holder::<T>(&caller, crmdata_with_fields(l, CROWDFUNDING_FIELDS))?;
+        let deadline = frame_system::Module::<T>::block_number().saturating_add(100u32.into());
+        Pallet::<T>::create_campaign(RawOrigin::Signed(caller.clone()).into(), 1, 100u32.into(), 200u32.into(), deadline)?;
+    }: _(RawOrigin::Signed(caller), crmid, 1)
+    verify {
+        assert_eq!(CampaignToCrm::<T>::get(1), Some(crmid));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{new_test_ext, Test};
+    use frame_support::assert_ok;
+
+    #[test]
+    fn new_contract() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_new_contract::<Test>());
+        });
+    }
+
+    #[test]
+    fn patch_crmdata() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_patch_crmdata::<Test>());
+        });
+    }
+
+    #[test]
+    fn change_proposal_crmdata() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_change_proposal_crmdata::<Test>());
+        });
+    }
+
+    #[test]
+    fn vote_proposal_crmdata() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_vote_proposal_crmdata::<Test>());
+        });
+    }
+
+    #[test]
+    fn change_proposal_crm_masterdata() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_change_proposal_crm_masterdata::<Test>());
+        });
+    }
+
+    #[test]
+    fn vote_proposal_crm_masterdata() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_vote_proposal_crm_masterdata::<Test>());
+        });
+    }
+
+    #[test]
+    fn change_proposal_crm_compositiondata() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_change_proposal_crm_compositiondata::<Test>());
+        });
+    }
+
+    #[test]
+    fn vote_proposal_crm_compositiondata() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_vote_proposal_crm_compositiondata::<Test>());
+        });
+    }
+
+    #[test]
+    fn change_proposal_crm_othercontractsdata() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_change_proposal_crm_othercontractsdata::<Test>());
+        });
+    }
+
+    #[test]
+    fn vote_proposal_crm_othercontractsdata() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_vote_proposal_crm_othercontractsdata::<Test>());
+        });
+    }
+
+    #[test]
+    fn add_private_hash() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_add_private_hash::<Test>());
+        });
+    }
+
+    #[test]
+    fn set_crowdfounders() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(test_benchmark_set_crowdfounders::<Test>());
+        });
+    }
+
+    #[test]
+    fn crmdata_has_the_length_benchmarked() {
+        assert_eq!(crmdata_of_length(MIN_CRMDATA_LENGTH).len(), MIN_CRMDATA_LENGTH as usize);
+        assert_eq!(crmdata_of_length(1024).len(), 1024);
+        assert_eq!(holders_of_length(b"master", MAX_HOLDERS_LENGTH).len(), MAX_HOLDERS_LENGTH);
+        assert_eq!(holders_of_length(b"composition", MAX_HOLDERS_LENGTH).len(), MAX_HOLDERS_LENGTH);
+        assert_eq!(othercontracts_of_length(1001u32, MAX_HOLDERS_LENGTH).len(), MAX_HOLDERS_LENGTH);
+        let proposal = proposal_of_length(1001u32, MAX_PROPOSAL_LENGTH, |len| holders_of_length(b"master", len));
+        assert_eq!(proposal.len(), MAX_PROPOSAL_LENGTH);
+        assert!(proposal.starts_with(br#"{"crmid":1001,"master":["#));
+    }
+}
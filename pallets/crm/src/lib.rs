@@ -5,16 +5,39 @@ use core::str::FromStr;
 /// CRM - Module to setup the contracts for rights management
 use frame_support::{
     codec::{Decode, Encode},
-    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    debug, dispatch, ensure,
+    storage::with_transaction,
+    transactional,
+    traits::{
+        schedule::{DispatchTime, Named as ScheduleNamed, Period, Priority, HARD_DEADLINE},
+        Currency, EnsureOrigin, ExistenceRequirement, Get, OnUnbalanced, PalletInfo, ReservableCurrency, WithdrawReasons,
+    },
+    weights::{Weight, WithPostDispatchInfo},
+    Parameter,
 };
-use frame_system::ensure_signed;
-use sp_std::prelude::*;
+use frame_system::{ensure_root, ensure_signed};
+use sp_runtime::{
+    helpers_128bit::multiply_by_rational,
+    traits::{
+        AccountIdConversion, AtLeast32BitUnsigned, Bounded, CheckedAdd, Dispatchable, Hash as HashT, IdentifyAccount,
+        MaybeSerializeDeserialize, Member, One, Saturating, UniqueSaturatedInto, Verify, Zero,
+    },
+    DispatchError, ModuleId, PerThing, Perbill, Percent, RuntimeDebug, TransactionOutcome,
+};
+use sp_std::{
+    convert::{TryFrom, TryInto},
+    prelude::*,
+};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+pub use pallet::*;
 
 // structure to keep the voting progresses/results of the change proposals
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
-pub struct Voting {
+pub struct Voting<CrmId> {
     changeid: u32,
-    crmid: u32,
+    crmid: CrmId,
     quorum: u32,
     nrvotesyes: u32,
     nrvotesno: u32,
@@ -22,85 +45,916 @@ pub struct Voting {
     percvotesno: u32,
 }
 
+// main crm data of a contract decoded once at the storing, the shares are in basis points
+#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct CrmContract {
+    pub ipfshash: Vec<u8>,
+    pub ipfshashprivate: Vec<Vec<u8>>,
+    pub globalquorum: u8,
+    pub mastershare: u32,
+    pub masterquorum: u8,
+    pub compositionshare: u32,
+    pub compositionquorum: u8,
+    pub othercontractsshare: u32,
+    pub othercontractsquorum: u8,
+    pub crowdfundingshare: u32,
+    pub campaignid: Option<u32>,
+}
+
+// shares and quorums of a contract decoded from the main crm data
+#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+pub struct CrmShares {
+    pub globalquorum: Percent,
+    pub mastershare: Percent,
+    pub masterquorum: Percent,
+    pub compositionshare: Percent,
+    pub compositionquorum: Percent,
+    pub othercontractsshare: Percent,
+    pub othercontractsquorum: Percent,
+    pub crowdfundingshare: Percent,
+    pub donationshare: Percent,
+}
+
+// contract stored by hash: the cid of the json on IPFS, its blake2-256 hash and the shares submitted
+#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+pub struct CrmCommitment {
+    pub ipfscid: Vec<u8>,
+    pub datahash: [u8; 32],
+    pub shares: CrmShares,
+}
+
+// where the main crm data of a contract is stored
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum CrmStorageMode {
+    // the json is in CrmData
+    Inline,
+    // the json is on IPFS, its hash is in CrmCommitments
+    ByHash,
+}
+
+// status of a crowdfunding campaign
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum CampaignStatus {
+    // accepting contributions until the deadline or the hard cap is reached
+    Active,
+    // soft cap reached, the funds raised have been moved to the owner
+    Succeeded,
+    // deadline passed below the soft cap, the contributions can be refunded
+    Failed,
+}
+
+impl Default for CampaignStatus {
+    fn default() -> Self {
+        CampaignStatus::Active
+    }
+}
+
+// structure to keep the state of a crowdfunding campaign
+#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+pub struct Campaign<AccountId, Balance, BlockNumber> {
+    owner: AccountId,
+    soft_cap: Balance,
+    hard_cap: Balance,
+    raised: Balance,
+    deadline: BlockNumber,
+    status: CampaignStatus,
+    // set when the soft cap is reached the first time, to emit CampaignFunded once
+    funded: bool,
+}
+
+// crowdfunding share certificate of a backer of a successful campaign, the basis points are the part of the crowdfunding share (10000 = 100%)
+#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+pub struct Certificate<Balance, BlockNumber> {
+    basis_points: u32,
+    contributed: Balance,
+    issued_at: BlockNumber,
+}
+
+// distribution of the subscription pool of a closed period, the contracts are paid over several calls
+#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+pub struct PoolDistribution<Balance> {
+    amount: Balance,
+    total_plays: u64,
+    paid: Balance,
+}
+
+// owners of a co-owned contract and the number of approvals required by the sensitive actions
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct OwnerSet<AccountId> {
+    pub owners: Vec<AccountId>,
+    pub threshold: u32,
+}
+
+// sensitive action on a co-owned contract, executed when approved by the threshold of the owners
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum OwnerAction<AccountId, BlockNumber> {
+    // transfer the contract to the account, the owner set is removed
+    Transfer(AccountId),
+    // revoke the contract
+    Revoke,
+    // replace the owners and the threshold, an empty list removes the owner set
+    SetOwners(Vec<AccountId>, u32),
+    // set or remove the withholding account of the contract
+    SetWithholdingAccount(Option<AccountId>),
+    // grant the license request with the expiry, if any
+    GrantLicense(u32, Option<BlockNumber>),
+}
+
+// group of the rights of a contract with holders
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum HolderGroup {
+    Master,
+    Composition,
+}
+
+// group of the shares of a contract in the split of an amount
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ShareGroup {
+    Master,
+    Composition,
+    OtherContracts,
+    Crowdfunding,
+    Donation,
+}
+
+// kind of change of a contract in the CrmDataChanged event, for the indexers filtering the changes
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ChangeKind {
+    // the main crm data replaced as a whole
+    FullReplace,
+    // the public or private ipfs hashes
+    IpfsHash,
+    // the shares, the quorums or the crowdfunding campaign
+    Shares,
+    // the other fields of the main crm data
+    Meta,
+    // the editors of the contract, the version is not changed
+    Editors,
+    // the creation of the contract, in the changelog only
+    Created,
+    // the master, composition or other contracts data approved by the votes, in the changelog only
+    GroupData,
+    // the holders of the master or composition group, in the changelog only
+    Holders,
+}
+
+// entry of the changelog of a contract: the block, the account, the kind of change and the blake2-256 hash of the main
+// crm data after the change (the hash of the commitment for the contracts stored by hash)
+#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+pub struct ChangelogEntry<AccountId, BlockNumber> {
+    pub block: BlockNumber,
+    pub who: AccountId,
+    pub action: ChangeKind,
+    pub datahash: [u8; 32],
+}
+
+// kind of license of a contract
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum LicenseKind {
+    // mechanical license granted at the "mechanicallicensefee"
+    Mechanical,
+    // sync or custom license granted by the owner on the terms requested
+    Custom,
+}
+
+// license of a contract granted to a licensee, the terms hash is empty for the mechanical licenses.
+// The license is active until the expiry block (excluded), if any, or until it's revoked by the owner
+#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+pub struct License<BlockNumber> {
+    id: u32,
+    kind: LicenseKind,
+    terms_hash: Vec<u8>,
+    granted_at: BlockNumber,
+    expires_at: Option<BlockNumber>,
+    revoked_at: Option<BlockNumber>,
+}
+
+// license request pending the decision of the owner, the offer is held in the escrow account of the contract
+#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+pub struct LicenseRequest<AccountId, Balance, CrmId> {
+    licensee: AccountId,
+    crmid: CrmId,
+    terms_hash: Vec<u8>,
+    offer: Balance,
+}
+
+type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+type CampaignOf<T> = Campaign<
+    <T as frame_system::Config>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Config>::BlockNumber,
+>;
+type CertificateOf<T> = Certificate<BalanceOf<T>, <T as frame_system::Config>::BlockNumber>;
+type PoolDistributionOf<T> = PoolDistribution<BalanceOf<T>>;
+type LicenseOf<T> = License<<T as frame_system::Config>::BlockNumber>;
+type LicenseRequestOf<T> = LicenseRequest<<T as frame_system::Config>::AccountId, BalanceOf<T>, <T as Config>::CrmId>;
+type VotingOf<T> = Voting<<T as Config>::CrmId>;
+type ChangelogEntryOf<T> = ChangelogEntry<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
+type OwnerActionOf<T> = OwnerAction<<T as frame_system::Config>::AccountId, <T as frame_system::Config>::BlockNumber>;
+type NegativeImbalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::NegativeImbalance;
+type AssetIdOf<T> = <<T as Config>::Assets as RoyaltyAssets<
+    <T as frame_system::Config>::AccountId,
+    BalanceOf<T>,
+>>::AssetId;
+
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
 #[cfg(test)]
 mod mock;
 
 #[cfg(test)]
 mod tests;
 
-/// Module Configuration
-pub trait Config: frame_system::Config {
-    /// Because this pallet emits events, it depends on the runtime's definition of an event.
-    type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
+// maximum number of editors of a contract
+const MAX_EDITORS: usize = 16;
+
+// maximum number of holders of a group of a contract
+const MAX_HOLDERS: usize = 64;
+
+// maximum number of owners of a co-owned contract
+const MAX_OWNERS: usize = 16;
+
+// maximum number of actions waiting for the approvals of the owners of a co-owned contract
+const MAX_PENDING_OWNER_ACTIONS: usize = 8;
+
+// maximum number of crmids used skipped by new_contract_auto in a call, the reads are in its weight
+pub const MAX_CRMID_PROBES: u32 = 32;
+
+// weight of the whole royalties: share (bps) x member percentage (%) x 100, or share (bps) x certificate basis points
+const ROYALTY_WEIGHT_TOTAL: u128 = 100 * 100 * 10_000;
+
+// maximum length of the hash of the terms of a license request
+const MAX_TERMS_HASH_LENGTH: usize = 128;
+
+// maximum number of parents above a derivative work, it bounds the passthrough of the royalties: a chain made deeper
+// later (a parent set on an ancestor) is paid up to this depth
+const MAX_PARENT_DEPTH: u32 = 8;
+
+// version of the storage layout, on_runtime_upgrade migrates the storage of the earlier versions up to it
+pub const STORAGE_VERSION: u32 = 8;
+
+// prefix of the storage of the pallet declared with decl_storage, the storage is moved under the name of the pallet
+pub const OLD_STORAGE_PREFIX: &[u8] = b"PolkaMusic";
+
+// maximum length of a CID decoded, the bound of the base32 decoding
+const MAX_IPFS_CID_LENGTH: usize = 128;
+
+// types of contract accepted in the "type" field of the crm data
+pub const CONTRACT_TYPES: [&str; 4] = ["single", "album", "ep", "compilation"];
+
+// the top level fields of the main crm data, the other fields are rejected with StrictSchema
+pub const CRMDATA_FIELDS: [&str; 23] = [
+    "ipfshash", "ipfshashprivate", "globalquorum", "mastershare", "masterquorum", "compositionshare", "compositionquorum",
+    "othercontractsshare", "othercontractsquorum", "crodwfundingshares", "crowdfounders", "type", "rateperplay", "sharescale",
+    "mechanicallicensefee", "territories", "withholdingpercent", "donationshare", "donationaccount", "parentcontract", "isrc",
+    "iswc", "crmid",
+];
+
+// weight charged when a call fails on the checks done before the json validation, the reads done are added
+pub const EARLY_EXIT_WEIGHT: Weight = 10_000;
+
+// prefix of the keys of the crm data written in the off-chain indexing database
+pub const OFFCHAIN_PREFIX: &[u8] = b"crm::crmdata::";
+
+/// Key of the off-chain indexing database keeping the main crm data of a contract at a version: the prefix followed by
+/// the SCALE encoding of (owner, crmid, version). The value is the SCALE encoding of (owner, crmid, crmdata).
+pub fn offchain_key<AccountId: Encode, CrmId: Encode>(owner: &AccountId, crmid: CrmId, version: u32) -> Vec<u8> {
+    let mut key = OFFCHAIN_PREFIX.to_vec();
+    (owner, crmid, version).encode_to(&mut key);
+    key
+}
+
+// prefix of the topic of the events of a contract, hashed with its crmid
+pub const EVENT_TOPIC_PREFIX: &[u8] = b"crm::contract::";
+
+/// Number of approvals required to reach a quorum (percentage, capped at 100) among the holders of a group, rounded up:
+/// 51% of 3 holders requires 2 approvals.
+pub fn required_approvals(quorum_percent: u8, holder_count: u32) -> u32 {
+    let quorum = u64::from(quorum_percent.min(100));
+    let required = (quorum * u64::from(holder_count) + 99) / 100;
+    // not above holder_count, the conversion cannot fail
+    required.try_into().unwrap_or(holder_count)
+}
+
+// prefix of the scheduler task ids for the campaigns closing and of the escrow sub accounts of the campaigns
+const CAMPAIGN_TASK_ID: [u8; 8] = *b"crmcampg";
+// priority of the closing of the campaigns, dispatched at the deadline block even above the maximum weight of the scheduler
+const CAMPAIGN_CLOSE_PRIORITY: Priority = HARD_DEADLINE;
+
+/// Scheduler for runtimes without `pallet_scheduler`, nothing is scheduled and the campaigns
+/// must be closed with `close_campaign` once the deadline is reached.
+pub struct NoScheduler;
+impl<BlockNumber, Call, Origin> ScheduleNamed<BlockNumber, Call, Origin> for NoScheduler {
+    type Address = ();
+    fn schedule_named(
+        _id: Vec<u8>,
+        _when: DispatchTime<BlockNumber>,
+        _maybe_periodic: Option<Period<BlockNumber>>,
+        _priority: Priority,
+        _origin: Origin,
+        _call: Call,
+    ) -> Result<Self::Address, ()> {
+        Err(())
+    }
+    fn cancel_named(_id: Vec<u8>) -> Result<(), ()> {
+        Err(())
+    }
+    fn reschedule_named(
+        _id: Vec<u8>,
+        _when: DispatchTime<BlockNumber>,
+    ) -> Result<Self::Address, DispatchError> {
+        Err(DispatchError::Other("no scheduler"))
+    }
+    fn next_dispatch_time(_id: Vec<u8>) -> Result<BlockNumber, ()> {
+        Err(())
+    }
 }
 
-// The runtime storage items
-
-decl_storage! {
-    trait Store for Module<T: Config> as PolkaMusic {
-        // the Contract main data in json format, the key is the uniqueid received
-        CrmData get(fn get_crmdata): map hasher(blake2_128_concat) u32 => Option<Vec<u8>>;
-        // the Contract Master data in json format, the key is the uniqueid received
-        CrmMasterData get(fn get_master): map hasher(blake2_128_concat) u32 => Option<Vec<u8>>;
-        // the Contract composition data in json format, the key is the uniqueid received
-        CrmCompositionData get(fn get_composition): map hasher(blake2_128_concat) u32 => Option<Vec<u8>>;
-        // the Contract, Other Contracts data in json format, the key is the uniqueid received
-        CrmOtherContractsData get(fn get_othercontracts): map hasher(blake2_128_concat) u32 => Option<Vec<u8>>;
-        // Change proposal queue for Crm Data
-        CrmDataChangeProposal get(fn get_crmdata_change_proposal): map hasher(blake2_128_concat) u32 => Option<Vec<u8>>;
-        // Voting counters for the change proposals
-        CrmDataChangeVotingResult get(fn get_crmdata_change_voting_result): map hasher(blake2_128_concat) u32  => Option<Voting>;
-        // Votes casted for the contract main data change proposals
-        CrmDataChangeVoteCasted get(fn get_crmdata_change_vote_casted): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) u32 => Option<bool>;
-        // Change proposal queue for Crm Master Data
-        CrmMasterDataChangeProposal get(fn get_crm_masterdata_change_proposal): map hasher(blake2_128_concat) u32 => Option<Vec<u8>>;
-        // Voting counters for the change proposals
-        CrmMasterDataChangeVotingResult get(fn get_crm_masterdata_change_voting_result): map hasher(blake2_128_concat) u32  => Option<Voting>;
-        // Votes casted for the change proposals
-        CrmMasterDataChangeVoteCasted get(fn get_crm_masterdata_change_vote_casted): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) u32 => Option<bool>;
-        // Change proposal queue for Crm composition Data
-        CrmCompositionDataChangeProposal get(fn get_crm_compositiondata_change_proposal): map hasher(blake2_128_concat) u32 => Option<Vec<u8>>;
-        // Voting counters for the change proposals of composition data
-        CrmCompositionDataChangeVotingResult get(fn get_crm_compositiondata_change_voting_result): map hasher(blake2_128_concat) u32  => Option<Voting>;
-        // Votes casted for the change proposals of composition data
-        CrmCompositionDataChangeVoteCasted get(fn get_crm_compositiondata_change_vote_casted): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) u32 => Option<bool>;
-        // Change proposal queue for Crm Other Contracts Data
-        CrmOtherContractsDataChangeProposal get(fn get_crm_othercontractsdata_change_proposal): map hasher(blake2_128_concat) u32 => Option<Vec<u8>>;
-        // Voting counters for the change proposals of Other Contracts data
-        CrmOtherContractsDataChangeVotingResult get(fn get_crm_othercontractsdata_change_voting_result): map hasher(blake2_128_concat) u32  => Option<Voting>;
-        // Votes casted for the change proposals of Other Contracts data
-        CrmOtherContractsDataChangeVoteCasted get(fn get_crm_othercontractsdata_change_vote_casted): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) u32 => Option<bool>;
+/// Assets accepted for the royalties beside the native currency, implemented by the runtime on top of
+/// its assets pallet. The balance of the assets is expressed in the balance type of the native currency.
+pub trait RoyaltyAssets<AccountId, Balance> {
+    /// Identifier of an asset.
+    type AssetId: Parameter + Copy;
+    /// Whether the asset exists.
+    fn exists(asset: Self::AssetId) -> bool;
+    /// Transfer an amount of the asset between two accounts.
+    fn transfer(asset: Self::AssetId, source: &AccountId, dest: &AccountId, amount: Balance) -> dispatch::DispatchResult;
+}
+
+/// No assets, the royalties are paid in the native currency only.
+impl<AccountId, Balance> RoyaltyAssets<AccountId, Balance> for () {
+    type AssetId = u32;
+    fn exists(_asset: u32) -> bool {
+        false
+    }
+    fn transfer(_asset: u32, _source: &AccountId, _dest: &AccountId, _amount: Balance) -> dispatch::DispatchResult {
+        Err(DispatchError::Other("no assets"))
     }
 }
 
-// Events used to inform users when important changes are made.
-decl_event!(
-    pub enum Event<T>
-    where
-        AccountId = <T as frame_system::Config>::AccountId,
-    {
-        CrmAdded(AccountId, u32),                      // New contract has been added
-        CrmDataNewChangeProposal(AccountId, u32, u32), // A proposal change has been submitted
-        CrmDataChangeVote(AccountId, u32, u32), // A vote for a crm data change proposal has been received
-        CrmDataChanged(AccountId, u32),         // Crm data has been changed
-        CrmMasterChanged(AccountId, u32),       // Crm master data has been changed
-        CrmCompositionChanged(AccountId, u32),  // Crm composition data has been changed
-        CrmOtherContractsChanged(AccountId, Vec<u8>), // Crm other contracts data has been changed
-        CrmMasterDataNewChangeProposal(AccountId, u32, u32), // A proposal change for master data has been submitted
-        CrmMasterDataChangeVote(AccountId, u32, u32), // A vote for a crm master data change proposal has been received
-        CrmMasterDataChanged(AccountId, u32),         // Crm master data has been changed
-        CrmCompositionDataNewChangeProposal(AccountId, u32, u32), // A proposal change for composition data has been submitted
-        CrmCompositionDataChangeVote(AccountId, u32, u32), // A vote for a crm composition data change proposal has been received
-        CrmCompositionDataChanged(AccountId, u32),         // Crm composition data has been changed
-        CrmOtherContractsDataNewChangeProposal(AccountId, u32, u32), // A proposal change for Other Contracts data has been submitted
-        CrmOtherContractsDataChangeVote(AccountId, u32, u32), // A vote for a crm Other Contracts data change proposal has been received
-        CrmOtherContractsDataChanged(AccountId, u32), // Crm Other Contracts data has been changed
-    }
-);
-
-// Errors inform users that something went wrong.
-decl_error! {
-    pub enum Error for Module<T: Config> {
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+    use frame_support::pallet_prelude::*;
+    use frame_support::traits::GenesisBuild;
+    use frame_system::pallet_prelude::*;
+
+    /// Module Configuration
+    #[pallet::config]
+    pub trait Config: frame_system::Config {
+        /// Because this pallet emits events, it depends on the runtime's definition of an event.
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// The currency used for the crowdfunding contributions.
+        type Currency: ReservableCurrency<Self::AccountId>;
+        /// Deposit reserved from the owner for every contract.
+        type DepositBase: Get<BalanceOf<Self>>;
+        /// Deposit reserved from the owner for every byte of the crm data of a contract.
+        type DepositPerByte: Get<BalanceOf<Self>>;
+        /// Fee charged for the creation of a contract, it's not refunded.
+        type CreationFee: Get<BalanceOf<Self>>;
+        /// Handler of the creation fees, for example the treasury.
+        type CreationFeeHandler: OnUnbalanced<NegativeImbalanceOf<Self>>;
+        /// Module id used to derive the escrow account holding the crowdfunding contributions.
+        type ModuleId: Get<ModuleId>;
+        /// Maximum length in bytes of the crm data of a contract.
+        #[pallet::constant]
+        type MaxCrmDataLength: Get<u32>;
+        /// Minimum length in bytes of the crm data of a contract.
+        #[pallet::constant]
+        type MinCrmDataLength: Get<u32>;
+        /// Maximum number of hashes accepted in the "ipfshashprivate" array.
+        #[pallet::constant]
+        type MaxPrivateHashes: Get<u32>;
+        /// Minimum length of the public and private ipfs hashes (a CIDv0 has 46 characters), lower on the testnets.
+        #[pallet::constant]
+        type MinIpfsHashLength: Get<u32>;
+        /// Maximum length of the public and private ipfs hashes.
+        #[pallet::constant]
+        type MaxIpfsHashLength: Get<u32>;
+        /// Maximum number of contracts owned by a single account.
+        #[pallet::constant]
+        type MaxContractsPerAccount: Get<u32>;
+        /// Maximum number of contracts created in a single block, by all the accounts.
+        #[pallet::constant]
+        type MaxContractsPerBlock: Get<u32>;
+        /// Maximum number of contracts stored by all the accounts, root can change it with `set_contract_limit`.
+        #[pallet::constant]
+        type MaxTotalContracts: Get<u32>;
+        /// Number of blocks after the creation of a contract while the same account cannot create another one, 0 for no
+        /// cooldown. The contracts created in the same block count as one creation.
+        #[pallet::constant]
+        type CreationCooldown: Get<Self::BlockNumber>;
+        /// Type of the contract ids (crmid), u32 in the runtime to keep the keys of the storage. The crmids are numbers:
+        /// they are parsed from the json ("crmid" of the change proposals, "id" of the other and parent contracts), the
+        /// maximum value is reserved and `new_contract_auto` counts them, so the hash-based ids are not supported.
+        type CrmId: Parameter + Member + AtLeast32BitUnsigned + Copy + Default + MaybeSerializeDeserialize;
+        /// The crmids from 1 to this value are reserved to the system contracts created by root, 0 for no reserved range.
+        type ReservedCrmIdMax: Get<Self::CrmId>;
+        /// Maximum number of contracts paid by a single call of `distribute_pool`.
+        #[pallet::constant]
+        type MaxPoolPayouts: Get<u32>;
+        /// Maximum number of territories in the "territories" array.
+        #[pallet::constant]
+        type MaxTerritories: Get<u32>;
+        /// Keep a tombstone of the contracts removed, so that their crmid is never reused and the licenses granted are kept.
+        type SoftDelete: Get<bool>;
+        /// Number of blocks after the creation of a contract while its crm data cannot be changed, 0 for no lock.
+        type UpdateLockPeriod: Get<Self::BlockNumber>;
+        /// Reject the json with trailing commas (`{"a":1,}` or `[1,]`), that are accepted by the lenient validation.
+        type StrictJson: Get<bool>;
+        /// Reject a contract with an "ipfshash" already registered by another contract, otherwise the index keeps the first one.
+        type UniqueIpfsHash: Get<bool>;
+        /// Require a quorum of 100 for the master and composition groups owned by a single holder (100% of the group).
+        type SingleHolderQuorum: Get<bool>;
+        /// Reject the main crm data with a top level field not in CRMDATA_FIELDS, for example a misspelled share.
+        type StrictSchema: Get<bool>;
+        /// Accept only the CIDs for the public and the private ipfs hashes: CIDv0 ("Qm..." base58btc sha2-256 multihash)
+        /// or CIDv1 in base32 ("b...").
+        type StrictCid: Get<bool>;
+        /// Maximum nesting of the objects and arrays in the json data.
+        #[pallet::constant]
+        type MaxJsonDepth: Get<u32>;
+        /// Maximum number of entries returned by the list getters, the callers paginate beyond it.
+        #[pallet::constant]
+        type MaxListReturn: Get<u32>;
+        /// Maximum number of entries of the changelog of a contract, the oldest entry is removed when it's full.
+        #[pallet::constant]
+        type MaxChangelogEntries: Get<u32>;
+        /// The call dispatched by the scheduler to close the campaigns at the deadline.
+        type SchedulerCall: Parameter + Dispatchable<Origin = Self::Origin> + From<Call<Self>>;
+        /// The caller origin, overarching type of all pallets origins.
+        type PalletsOrigin: From<frame_system::RawOrigin<Self::AccountId>>;
+        /// The scheduler closing the campaigns at the deadline, use `NoScheduler` to close them manually only.
+        type Scheduler: ScheduleNamed<Self::BlockNumber, Self::SchedulerCall, Self::PalletsOrigin>;
+        /// Handler of the royalties not credited to any member (the groups of the contract do not cover the whole amount),
+        /// for example the treasury.
+        type DustHandler: OnUnbalanced<NegativeImbalanceOf<Self>>;
+        /// The signature of the artists authorizing the creation of a contract on their behalf.
+        type Signature: Verify<Signer = Self::Public> + Parameter;
+        /// The public key of the signature, identifying an account.
+        type Public: IdentifyAccount<AccountId = Self::AccountId>;
+        /// The origin allowed to authorize the reporters of the plays.
+        type ReporterAdminOrigin: EnsureOrigin<Self::Origin>;
+        /// The assets (for example stablecoins) accepted for the royalties beside the native currency.
+        type Assets: RoyaltyAssets<Self::AccountId, BalanceOf<Self>>;
+        /// Weights of the calls validating the json of a contract, from the benchmarks.
+        type WeightInfo: WeightInfo;
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(PhantomData<T>);
+
+    // The runtime storage items
+
+    // the Contract main data in json format, the key is the uniqueid received
+    #[pallet::storage]
+    #[pallet::getter(fn get_crmdata)]
+    pub(super) type CrmData<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<u8>>;
+
+    // the main crm data decoded, stored with CrmData that keeps the json submitted
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_contract)]
+    pub(super) type CrmContracts<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, CrmContract>;
+
+    // the contracts stored by hash, the json is on IPFS and it's not in CrmData
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_commitment)]
+    pub(super) type CrmCommitments<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, CrmCommitment>;
+
+    // the contract registering an "ipfshash" (or the cid of a contract stored by hash)
+    #[pallet::storage]
+    #[pallet::getter(fn get_ipfs_index)]
+    pub(super) type IpfsIndex<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, T::CrmId>;
+
+    // every contract with an "ipfshash" (or a cid), the index moves to another contract of the hash when it's removed
+    #[pallet::storage]
+    pub(super) type IpfsHashContracts<T: Config> = StorageDoubleMap<_, Blake2_128Concat, Vec<u8>, Blake2_128Concat, T::CrmId, bool>;
+
+    // version of the storage layout, 1 when CrmContracts has been populated from CrmData
+    #[pallet::storage]
+    #[pallet::getter(fn crm_storage_version)]
+    pub(super) type CrmStorageVersion<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    // the contracts stored before the bounds of the ipfs hashes with hashes out of them, set by the migration to the
+    // version 5: their stored hashes are kept on the updates, the new hashes must be in the bounds
+    #[pallet::storage]
+    #[pallet::getter(fn has_legacy_ipfshash)]
+    pub(super) type LegacyIpfsHashes<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, bool, ValueQuery>;
+
+    // the account that created the Contract, the key is the uniqueid received
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_owner)]
+    pub(super) type CrmOwner<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, T::AccountId>;
+
+    // the number of Contracts owned by an account
+    #[pallet::storage]
+    #[pallet::getter(fn get_contract_count)]
+    pub(super) type ContractCount<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    // the number of Contracts created in the current block, reset at the beginning of every block
+    #[pallet::storage]
+    #[pallet::getter(fn contracts_in_block)]
+    pub(super) type ContractsInBlock<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    // the Contracts owned by an account in order of creation/transfer, at the most MaxContractsPerAccount
+    #[pallet::storage]
+    #[pallet::getter(fn contracts_of_owner)]
+    pub(super) type OwnedContracts<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, Vec<T::CrmId>, ValueQuery>;
+
+    // the number of Contracts stored
+    #[pallet::storage]
+    #[pallet::getter(fn total_crms)]
+    pub(super) type TotalCrms<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    // the deposit reserved for the Contract with the account that paid it
+    #[pallet::storage]
+    #[pallet::getter(fn get_deposit)]
+    pub(super) type Deposits<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, (T::AccountId, BalanceOf<T>)>;
+
+    // the limit of the contracts stored set by root, replacing MaxTotalContracts
+    #[pallet::storage]
+    #[pallet::getter(fn contract_limit_override)]
+    pub(super) type ContractLimitOverride<T: Config> = StorageValue<_, u32>;
+
+    // the block of the last contract created by an account, for the creation cooldown
+    #[pallet::storage]
+    #[pallet::getter(fn last_creation)]
+    pub(super) type LastCreation<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::BlockNumber>;
+
+    // the accounts exempted from the creation cooldown by root
+    #[pallet::storage]
+    #[pallet::getter(fn cooldown_exempt)]
+    pub(super) type CooldownExempt<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
+    // the number of Contracts ever created, the removals are not counted
+    #[pallet::storage]
+    #[pallet::getter(fn total_contracts)]
+    pub(super) type TotalContracts<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    // the next crmid assigned by new_contract_auto, the ids already used are skipped
+    #[pallet::storage]
+    #[pallet::getter(fn next_crmid)]
+    pub(super) type NextCrmId<T: Config> = StorageValue<_, T::CrmId, ValueQuery>;
+
+    // the nonce of the next creation signed by an artist for new_contract_delegated, a signature is used once
+    #[pallet::storage]
+    #[pallet::getter(fn delegation_nonce)]
+    pub(super) type DelegationNonce<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    // the territories of the Contract (ISO 3166-1 alpha-2 codes), no entry for worldwide rights
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_territories)]
+    pub(super) type CrmTerritories<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<[u8; 2]>, ValueQuery>;
+
+    // the isrc code of the recording of a contract, without hyphens
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_isrc)]
+    pub(super) type CrmIsrc<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<u8>>;
+
+    // the iswc code of the work of a contract, without hyphens
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_iswc)]
+    pub(super) type CrmIswc<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<u8>>;
+
+    // the parent of a derivative work (remix, cover..) with the passthrough percentage of its royalties
+    #[pallet::storage]
+    #[pallet::getter(fn get_derivative_of)]
+    pub(super) type DerivativeOf<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, (T::CrmId, Percent)>;
+
+    // the version of the Contract, 1 at the creation and incremented on every change
+    #[pallet::storage]
+    #[pallet::getter(fn crm_version)]
+    pub(super) type CrmVersion<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, u32, ValueQuery>;
+
+    // the account receiving the part of the claims withheld by the "withholdingpercent" of the Contract
+    #[pallet::storage]
+    #[pallet::getter(fn get_withholding_account)]
+    pub(super) type WithholdingAccounts<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, T::AccountId>;
+
+    // the tombstones of the Contracts removed with the block of the removal, when SoftDelete is set
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_deleted)]
+    pub(super) type CrmDeleted<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, T::BlockNumber>;
+
+    // the block of the creation of a contract, the start of the update lock period
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_created_at)]
+    pub(super) type CrmCreatedAt<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, T::BlockNumber>;
+
+    // the block of the last change of a contract, the creation or the last version
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_updated_at)]
+    pub(super) type CrmUpdatedAt<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, T::BlockNumber>;
+
+    // the Contracts revoked by the owner with the block of the revocation, waiting to be pruned
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_revoked)]
+    pub(super) type CrmRevoked<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, T::BlockNumber>;
+
+    // the Contract Master data in json format, the key is the uniqueid received
+    #[pallet::storage]
+    #[pallet::getter(fn get_master)]
+    pub(super) type CrmMasterData<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<u8>>;
+
+    // the Contract composition data in json format, the key is the uniqueid received
+    #[pallet::storage]
+    #[pallet::getter(fn get_composition)]
+    pub(super) type CrmCompositionData<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<u8>>;
+
+    // the Contract, Other Contracts data in json format, the key is the uniqueid received
+    #[pallet::storage]
+    #[pallet::getter(fn get_othercontracts)]
+    pub(super) type CrmOtherContractsData<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<u8>>;
+
+    // Change proposal queue for Crm Data
+    #[pallet::storage]
+    #[pallet::getter(fn get_crmdata_change_proposal)]
+    pub(super) type CrmDataChangeProposal<T: Config> = StorageMap<_, Blake2_128Concat, u32, Vec<u8>>;
+
+    // Voting counters for the change proposals
+    #[pallet::storage]
+    #[pallet::getter(fn get_crmdata_change_voting_result)]
+    pub(super) type CrmDataChangeVotingResult<T: Config> = StorageMap<_, Blake2_128Concat, u32 , VotingOf<T>>;
+
+    // Votes casted for the contract main data change proposals
+    #[pallet::storage]
+    #[pallet::getter(fn get_crmdata_change_vote_casted)]
+    pub(super) type CrmDataChangeVoteCasted<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, u32, bool>;
+
+    // Change proposal queue for Crm Master Data
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_masterdata_change_proposal)]
+    pub(super) type CrmMasterDataChangeProposal<T: Config> = StorageMap<_, Blake2_128Concat, u32, Vec<u8>>;
+
+    // Voting counters for the change proposals
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_masterdata_change_voting_result)]
+    pub(super) type CrmMasterDataChangeVotingResult<T: Config> = StorageMap<_, Blake2_128Concat, u32 , VotingOf<T>>;
+
+    // Votes casted for the change proposals
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_masterdata_change_vote_casted)]
+    pub(super) type CrmMasterDataChangeVoteCasted<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, u32, bool>;
+
+    // Change proposal queue for Crm composition Data
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_compositiondata_change_proposal)]
+    pub(super) type CrmCompositionDataChangeProposal<T: Config> = StorageMap<_, Blake2_128Concat, u32, Vec<u8>>;
+
+    // Voting counters for the change proposals of composition data
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_compositiondata_change_voting_result)]
+    pub(super) type CrmCompositionDataChangeVotingResult<T: Config> = StorageMap<_, Blake2_128Concat, u32 , VotingOf<T>>;
+
+    // Votes casted for the change proposals of composition data
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_compositiondata_change_vote_casted)]
+    pub(super) type CrmCompositionDataChangeVoteCasted<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, u32, bool>;
+
+    // Change proposal queue for Crm Other Contracts Data
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_othercontractsdata_change_proposal)]
+    pub(super) type CrmOtherContractsDataChangeProposal<T: Config> = StorageMap<_, Blake2_128Concat, u32, Vec<u8>>;
+
+    // Voting counters for the change proposals of Other Contracts data
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_othercontractsdata_change_voting_result)]
+    pub(super) type CrmOtherContractsDataChangeVotingResult<T: Config> = StorageMap<_, Blake2_128Concat, u32 , VotingOf<T>>;
+
+    // Votes casted for the change proposals of Other Contracts data
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_othercontractsdata_change_vote_casted)]
+    pub(super) type CrmOtherContractsDataChangeVoteCasted<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, u32, bool>;
+
+    // Crowdfunding campaigns, the key is the campaign id referenced by "crowdfounders" in crm data
+    #[pallet::storage]
+    #[pallet::getter(fn get_campaign)]
+    pub(super) type Campaigns<T: Config> = StorageMap<_, Blake2_128Concat, u32, CampaignOf<T>>;
+
+    // Amount contributed to a crowdfunding campaign by each account
+    #[pallet::storage]
+    #[pallet::getter(fn get_campaign_contribution)]
+    pub(super) type CampaignContributions<T: Config> = StorageDoubleMap<_, Blake2_128Concat, u32, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+    // Contract linked to a crowdfunding campaign through the "crowdfounders" field
+    #[pallet::storage]
+    #[pallet::getter(fn get_campaign_crm)]
+    pub(super) type CampaignToCrm<T: Config> = StorageMap<_, Blake2_128Concat, u32, T::CrmId>;
+
+    // Crowdfunding share certificates of the backers, issued when a campaign succeeds
+    #[pallet::storage]
+    #[pallet::getter(fn get_crowdfunding_certificate)]
+    pub(super) type CrowdfundingCertificates<T: Config> = StorageDoubleMap<_, Blake2_128Concat, u32, Blake2_128Concat, T::AccountId, CertificateOf<T>>;
+
+    // Royalties credited to the members of a contract (crmid), held in the royalties escrow account until claimed
+    #[pallet::storage]
+    #[pallet::getter(fn get_royalty_balance)]
+    pub(super) type RoyaltyBalances<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::CrmId, Blake2_128Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+    // the royalties paid in assets credited to the members of a contract, the keys are the crmid and (asset id, member)
+    #[pallet::storage]
+    #[pallet::getter(fn get_asset_royalty_balance)]
+    pub(super) type AssetRoyaltyBalances<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::CrmId, Blake2_128Concat, (AssetIdOf<T>, T::AccountId), BalanceOf<T>, ValueQuery>;
+
+    // Accounts authorized to report the plays of the contracts
+    #[pallet::storage]
+    #[pallet::getter(fn is_reporter)]
+    pub(super) type Reporters<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
+    // Cumulative plays reported for a contract
+    #[pallet::storage]
+    #[pallet::getter(fn get_play_count)]
+    pub(super) type PlayCounts<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, u64, ValueQuery>;
+
+    // Editors of a contract, allowed to update the contract as the owner
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_editors)]
+    pub(super) type CrmEditors<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<T::AccountId>, ValueQuery>;
+
+    // Reporters allowed by the owner of a contract, when set only these reporters can report the plays of the contract
+    #[pallet::storage]
+    #[pallet::getter(fn is_contract_reporter)]
+    pub(super) type ContractReporters<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::CrmId, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
+    // the period of the subscription pool receiving the plays reported
+    #[pallet::storage]
+    #[pallet::getter(fn current_period)]
+    pub(super) type CurrentPeriod<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    // the plays reported in a period for each contract, the keys are the period and the crmid
+    #[pallet::storage]
+    #[pallet::getter(fn get_period_plays)]
+    pub(super) type PeriodPlays<T: Config> = StorageDoubleMap<_, Blake2_128Concat, u32, Blake2_128Concat, T::CrmId, u64, ValueQuery>;
+
+    // the plays reported in a period for all the contracts
+    #[pallet::storage]
+    #[pallet::getter(fn get_period_total_plays)]
+    pub(super) type PeriodTotalPlays<T: Config> = StorageMap<_, Blake2_128Concat, u32, u64, ValueQuery>;
+
+    // the funds of the subscription pool of the current period
+    #[pallet::storage]
+    #[pallet::getter(fn pool_balance)]
+    pub(super) type PoolBalance<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+    // the distributions of the closed periods still in progress
+    #[pallet::storage]
+    #[pallet::getter(fn get_pool_distribution)]
+    pub(super) type PoolDistributions<T: Config> = StorageMap<_, Blake2_128Concat, u32, PoolDistributionOf<T>>;
+
+    // the licenses of a contract granted to a licensee, the keys are the crmid and the licensee
+    #[pallet::storage]
+    #[pallet::getter(fn get_licenses)]
+    pub(super) type Licenses<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::CrmId, Blake2_128Concat, T::AccountId, Vec<LicenseOf<T>>, ValueQuery>;
+
+    // the crmid and licensee of a license, the key is the license id
+    #[pallet::storage]
+    #[pallet::getter(fn get_license_index)]
+    pub(super) type LicenseIndex<T: Config> = StorageMap<_, Blake2_128Concat, u32, (T::CrmId, T::AccountId)>;
+
+    // the id of the next license granted
+    #[pallet::storage]
+    #[pallet::getter(fn next_license_id)]
+    pub(super) type NextLicenseId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    // the license requests pending the decision of the owner, the key is the request id
+    #[pallet::storage]
+    #[pallet::getter(fn get_license_request)]
+    pub(super) type LicenseRequests<T: Config> = StorageMap<_, Blake2_128Concat, u32, LicenseRequestOf<T>>;
+
+    // the id of the next license request
+    #[pallet::storage]
+    #[pallet::getter(fn next_license_request_id)]
+    pub(super) type NextLicenseRequestId<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    // holders of the master or composition group of a contract with their percentage of the group, when set they replace
+    // the accounts of the master or composition data in the payment of the royalties
+    #[pallet::storage]
+    #[pallet::getter(fn get_crm_holders)]
+    pub(super) type CrmHolders<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::CrmId, Blake2_128Concat, HolderGroup, Vec<(T::AccountId, u8)>, ValueQuery>;
+
+    // the owners of a co-owned contract, the owner in CrmOwner is one of them
+    #[pallet::storage]
+    #[pallet::getter(fn get_owner_set)]
+    pub(super) type CrmOwnerSets<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, OwnerSet<T::AccountId>>;
+
+    // the sensitive actions waiting for the approvals of the owners of a co-owned contract, with the owners that approved them
+    #[pallet::storage]
+    #[pallet::getter(fn get_pending_owner_actions)]
+    pub(super) type PendingOwnerActions<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<(OwnerActionOf<T>, Vec<T::AccountId>)>, ValueQuery>;
+
+    // the last MaxChangelogEntries changes of a contract, the oldest first
+    #[pallet::storage]
+    #[pallet::getter(fn get_changelog)]
+    pub(super) type Changelog<T: Config> = StorageMap<_, Blake2_128Concat, T::CrmId, Vec<ChangelogEntryOf<T>>, ValueQuery>;
+
+    /// Contracts stored at the genesis as (owner, crmid, crmdata), for the testnets and the migrations from the legacy chains.
+    /// The main crm data are validated as in new_contract, without master, composition and other contracts data.
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        pub contracts: Vec<(T::AccountId, T::CrmId, Vec<u8>)>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self { contracts: Vec::new() }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        // an invalid contract stops the build of the chain spec
+        fn build(&self) {
+            for (owner, crmid, crmdata) in self.contracts.iter() {
+                if let Err(e) = Pallet::<T>::store_genesis_contract(owner, *crmid, crmdata.clone()) {
+                    panic!("invalid genesis contract {:?}: {:?}", crmid, e);
+                }
+            }
+        }
+    }
+
+    // Events used to inform users when important changes are made.
+    #[pallet::event]
+    #[pallet::metadata(T::AccountId = "AccountId", T::CrmId = "CrmId", BalanceOf<T> = "Balance", AssetIdOf<T> = "AssetId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        CrmAdded(T::AccountId, T::CrmId),                      // New contract has been added
+        CrmDataNewChangeProposal(T::AccountId, T::CrmId, u32), // A proposal change has been submitted
+        CrmDataChangeVote(T::AccountId, T::CrmId, u32), // A vote for a crm data change proposal has been received
+        CrmDataChanged(T::AccountId, T::CrmId, u32, ChangeKind), // Crm data has been changed, the crmid, the new version and the kind of change
+        CrmMasterChanged(T::AccountId, T::CrmId),       // Crm master data has been changed
+        CrmCompositionChanged(T::AccountId, T::CrmId),  // Crm composition data has been changed
+        CrmOtherContractsChanged(T::AccountId, Vec<u8>), // Crm other contracts data has been changed
+        CrmMasterDataNewChangeProposal(T::AccountId, T::CrmId, u32), // A proposal change for master data has been submitted
+        CrmMasterDataChangeVote(T::AccountId, T::CrmId, u32), // A vote for a crm master data change proposal has been received
+        CrmMasterDataChanged(T::AccountId, T::CrmId),         // Crm master data has been changed
+        CrmCompositionDataNewChangeProposal(T::AccountId, T::CrmId, u32), // A proposal change for composition data has been submitted
+        CrmCompositionDataChangeVote(T::AccountId, T::CrmId, u32), // A vote for a crm composition data change proposal has been received
+        CrmCompositionDataChanged(T::AccountId, T::CrmId),         // Crm composition data has been changed
+        CrmOtherContractsDataNewChangeProposal(T::AccountId, T::CrmId, u32), // A proposal change for Other Contracts data has been submitted
+        CrmOtherContractsDataChangeVote(T::AccountId, T::CrmId, u32), // A vote for a crm Other Contracts data change proposal has been received
+        CrmOtherContractsDataChanged(T::AccountId, T::CrmId), // Crm Other Contracts data has been changed
+        CampaignCreated(T::AccountId, u32), // A new crowdfunding campaign has been created
+        CampaignContribution(T::AccountId, u32, BalanceOf<T>), // A contribution to a crowdfunding campaign has been received
+        CampaignSoftCapReached(u32, BalanceOf<T>), // The funds raised by the campaign reached the soft cap
+        CampaignFunded(T::AccountId, u32, BalanceOf<T>), // The campaign of the owner is funded, emitted once per campaign
+        CampaignHardCapReached(u32, BalanceOf<T>), // The funds raised by the campaign reached the hard cap, the campaign is closed
+        CampaignSucceeded(u32, BalanceOf<T>), // The campaign has been closed successfully, the funds raised are moved to the owner
+        CampaignFailed(u32, BalanceOf<T>), // The campaign has been closed below the soft cap, the contributions are refundable
+        CampaignContributionRefunded(T::AccountId, u32, BalanceOf<T>), // A contribution to a failed campaign has been refunded
+        CertificateIssued(T::AccountId, u32, u32), // A crowdfunding share certificate (basis points) has been issued to a backer
+        CrmForceRemoved(T::CrmId), // A contract has been removed by governance
+        CrowdfundingShareTransferred(T::AccountId, T::AccountId, T::CrmId, u32), // Basis points of a crowdfunding certificate have been transferred for a contract
+        CrmRevoked(T::AccountId, T::CrmId), // A contract has been revoked by the owner
+        CrmRevokedPruned(u32), // Number of revoked contracts removed from the storage
+        RoyaltiesPaid(T::AccountId, T::CrmId, BalanceOf<T>, BalanceOf<T>), // Royalties paid for a contract, total credited and dust sent to the dust handler
+        RoyaltyCredited(T::AccountId, T::CrmId, BalanceOf<T>), // Part of the royalties of a contract credited to a recipient
+        RoyaltiesClaimed(T::AccountId, T::CrmId, BalanceOf<T>), // Royalties of a contract claimed by a recipient
+        AssetRoyaltiesPaid(T::AccountId, T::CrmId, AssetIdOf<T>, BalanceOf<T>), // Royalties paid for a contract in an asset, total credited
+        AssetRoyaltyCredited(T::AccountId, T::CrmId, AssetIdOf<T>, BalanceOf<T>), // Part of the royalties of a contract paid in an asset credited to a recipient
+        AssetRoyaltiesClaimed(T::AccountId, T::CrmId, AssetIdOf<T>, BalanceOf<T>), // Royalties of a contract paid in an asset claimed by a recipient
+        ReporterAdded(T::AccountId), // Account authorized to report the plays
+        ReporterRemoved(T::AccountId), // Account not authorized to report the plays anymore
+        ContractReporterSet(T::CrmId, T::AccountId, bool), // Reporter allowed (true) or not (false) by the owner of a contract
+        EditorAdded(T::CrmId, T::AccountId), // Editor added to a contract
+        EditorRemoved(T::CrmId, T::AccountId), // Editor removed from a contract
+        PlaysReported(T::AccountId, T::CrmId, u64, u64), // Plays reported for a contract and the cumulative plays
+        PoolFunded(T::AccountId, BalanceOf<T>), // Subscription pool funded
+        PoolSlicePaid(u32, T::CrmId, BalanceOf<T>), // Part of the pool of a period paid to a contract by its plays
+        PoolDistributed(u32, BalanceOf<T>, BalanceOf<T>), // Pool of a period distributed, the amount paid and the amount carried to the current period
+        MechanicalLicenseGranted(T::AccountId, T::CrmId, BalanceOf<T>), // Mechanical license of a contract granted to the licensee at the fee
+        LicenseRequested(T::AccountId, u32, T::CrmId, BalanceOf<T>), // License of a contract requested, the request id, crmid and offer
+        LicenseGranted(T::AccountId, u32, T::CrmId), // License request granted to the licensee, the request id and crmid
+        LicenseRejected(T::AccountId, u32, T::CrmId, BalanceOf<T>), // License request rejected, the offer is refunded to the licensee
+        LicenseRevoked(T::AccountId, u32, T::CrmId, Vec<u8>), // License revoked by the owner, the license id, crmid and hash of the reason
+        WithholdingAccountSet(T::CrmId, Option<T::AccountId>), // Withholding account of a contract set (Some) or removed (None)
+        RoyaltiesWithheld(T::AccountId, T::CrmId, T::AccountId, BalanceOf<T>), // Part of a claim of a member moved to the withholding account
+        DonationPaid(T::AccountId, T::CrmId, T::AccountId, BalanceOf<T>), // Donation share of a payment of royalties transferred to the donation account
+        RoyaltiesPassedThrough(T::CrmId, T::CrmId, BalanceOf<T>), // Passthrough of a derivative work (crmid) paid through the parent contract
+        ContractTransferred(T::AccountId, T::CrmId, T::AccountId), // Ownership of a contract transferred, the old owner, crmid and new owner
+        CrmAddedByHash(T::AccountId, T::CrmId),                // New contract stored by hash has been added
+        CrmRemoved(T::AccountId, T::CrmId), // A contract has been removed by the owner
+        HoldersUpdated(T::AccountId, T::CrmId, HolderGroup), // Holders of the master or composition group of a contract set by the owner
+        ContractLimitSet(u32), // Limit of the contracts stored set by root
+        CooldownExemptSet(T::AccountId, bool), // Account exempted (true) from the creation cooldown or not (false) by root
+        OwnersUpdated(T::AccountId, T::CrmId), // Owners of a co-owned contract set, the crmid
+        OwnerActionApproved(T::AccountId, T::CrmId, u32), // Sensitive action of a co-owned contract approved by an owner, the crmid and the approvals
+        OwnerActionExecuted(T::CrmId), // Sensitive action of a co-owned contract executed after reaching the threshold
+        CampaignCloseNotScheduled(u32, T::BlockNumber), // Closing of a campaign not scheduled at the deadline, it must be closed with close_campaign
+    }
+
+    // Errors inform users that something went wrong.
+    #[pallet::error]
+    pub enum Error<T> {
         /// Missing value
         NoneValue,
         /// CrmData is too short to be valid
@@ -189,19 +1043,257 @@ decl_error! {
         VoteCastedAlready,
         /// Changed id field is empty
         ChangeIdTooShort,
+        /// Campaign id is already present on chain
+        DuplicatedCampaignId,
+        /// Campaign id not found
+        CampaignNotFound,
+        /// Soft cap and hard cap must be > 0 and soft cap <= hard cap
+        InvalidCampaignCap,
+        /// Campaign deadline must be in the future
+        InvalidCampaignDeadline,
+        /// Contribution amount must be > 0
+        InvalidContribution,
+        /// The contribution would raise more than the hard cap of the campaign
+        HardCapExceeded,
+        /// Campaign is not accepting contributions anymore
+        CampaignClosed,
+        /// Campaign deadline has been reached
+        CampaignExpired,
+        /// Campaign deadline has not been reached yet
+        CampaignStillActive,
+        /// Campaign has not failed, the contributions cannot be refunded
+        CampaignNotFailed,
+        /// No contribution to refund for the signer
+        NothingToRefund,
+        /// Too many hashes in the Ipfs Hash Private array
+        TooManyPrivateHashes,
+        /// Signer is not the owner of the contract (or an editor, for the updates)
+        SignerIsNotOwner,
+        /// Contract has no crowdfunding campaign linked
+        MissingCrowdfundingCampaign,
+        /// Crowdfunding campaign has not been finalized successfully
+        CampaignNotSucceeded,
+        /// Basis points must be > 0
+        InvalidBasisPoints,
+        /// Crowdfunding share cannot be transferred to the same account
+        ShareTransferToSelf,
+        /// Crowdfunding certificate has not enough basis points
+        InsufficientCrowdfundingShare,
+        /// Contract has been revoked already
+        ContractRevoked,
+        /// Crowdfunding campaign is owned by a different account
+        CampaignNotOwned,
+        /// Crowdfunding campaign is already linked to a different contract
+        CampaignAlreadyLinked,
+        /// The account owns the maximum number of contracts allowed
+        TooManyContracts,
+        /// Royalties amount must be greater than zero
+        InvalidRoyaltyAmount,
+        /// The members of a group with a share greater than zero are not set
+        GroupMembersNotSet,
+        /// The crmid is reserved
+        ReservedCrmId,
+        /// No crmid left to assign automatically
+        NoAvailableCrmId,
+        /// Royalties balance overflow
+        RoyaltyOverflow,
+        /// Type of contract is not single, album, ep or compilation
+        InvalidContractType,
+        /// No royalties to claim
+        NothingToClaim,
+        /// The asset does not exist
+        UnknownAsset,
+        /// The amount claimed is greater than the royalties credited
+        InsufficientRoyaltyBalance,
+        /// Crowdfunding share and campaign of a contract cannot be changed
+        CrowdfundingShareImmutable,
+        /// A private hash is equal to the public ipfs hash
+        DuplicatePublicPrivateHash,
+        /// The private hashes contain duplicated entries
+        DuplicatePrivateHash,
+        /// Rate per play must be a number greater than zero
+        InvalidRatePerPlay,
+        /// The contract has no rate per play
+        MissingRatePerPlay,
+        /// Number of plays must be greater than zero
+        InvalidPlays,
+        /// The signer is not authorized to report the plays
+        NotAuthorizedReporter,
+        /// The signature of the artist is not valid
+        BadSignature,
+        /// The account is already an editor of the contract
+        EditorAlreadyAdded,
+        /// The account is not an editor of the contract
+        EditorNotFound,
+        /// The contract has the maximum number of editors
+        TooManyEditors,
+        /// The period is not the current one and it has no distribution in progress
+        InvalidPeriod,
+        /// The "sharescale" must be "percent" or "bps"
+        InvalidShareScale,
+        /// The "mechanicallicensefee" must be a number > 0
+        InvalidMechanicalLicenseFee,
+        /// The contract does not grant mechanical licenses
+        MechanicalLicenseNotAvailable,
+        /// The license has been granted already
+        LicenseAlreadyGranted,
+        /// The hash of the terms is empty or too long
+        InvalidTermsHash,
+        /// The offer of the license request must be > 0
+        InvalidLicenseOffer,
+        /// The license request is not on chain
+        LicenseRequestNotFound,
+        /// The license is not on chain
+        LicenseNotFound,
+        /// The license has expired
+        LicenseExpired,
+        /// The license has been revoked already
+        LicenseAlreadyRevoked,
+        /// The expiry of the license must be a future block
+        InvalidLicenseExpiry,
+        /// No license id is available
+        NoAvailableLicenseId,
+        /// The hash of the reason is empty or too long
+        InvalidReasonHash,
+        /// A key is duplicated at the top level of the json
+        DuplicateJsonKey,
+        /// The territories must be distinct ISO 3166-1 alpha-2 codes (two uppercase letters)
+        InvalidTerritory,
+        /// Too many territories
+        TooManyTerritories,
+        /// The version of the contract is not the one expected
+        VersionMismatch,
+        /// The "withholdingpercent" must be a number between 0 and 50
+        InvalidWithholdingPercent,
+        /// The contract has a "withholdingpercent" and no withholding account
+        WithholdingAccountNotSet,
+        /// The contract has no crowdfunding share
+        NoCrowdFundingShare,
+        /// The "donationshare" must be <= the share scale and > 0 when the "donationaccount" is set
+        InvalidDonationShare,
+        /// The "donationaccount" is not a valid SS58 address
+        InvalidDonationAccount,
+        /// The "donationshare" is set without the "donationaccount"
+        MissingDonationAccount,
+        /// The passthrough of the parent contract must be a number between 1 and 100
+        InvalidPassthrough,
+        /// The parent contract of a derivative work is not on chain
+        ParentContractNotFound,
+        /// The parent contract would create a cycle of derivative works
+        CyclicParentContract,
+        /// The chain of the parent contracts is too long
+        ParentChainTooDeep,
+        /// The maximum number of contracts created in the block has been reached
+        BlockCapacityReached,
+        /// The contract is already owned by the account
+        SameContractOwner,
+        /// The json is nested deeper than the maximum depth
+        JsonTooDeep,
+        /// The crmid is in the range reserved to the system contracts
+        ReservedCrmIdRange,
+        /// The ipfs hash is registered by another contract
+        IpfsHashAlreadyRegistered,
+        /// The isrc code is not in the format CC-XXX-YY-NNNNN
+        InvalidIsrc,
+        /// The iswc code is not in the format T-NNNNNNNNN-C or the check digit is wrong
+        InvalidIswc,
+        /// The contract cannot be changed until the end of the lock period after its creation
+        LockPeriodActive,
+        /// The "ipfshash" must be a string
+        IpfsHashNotString,
+        /// The "ipfshashprivate" must be a string or an array of strings
+        IpfsHashPrivateNotStringOrArray,
+        /// The "globalquorum" must be a number
+        GlobalQuorumNotNumber,
+        /// The "mastershare" must be a number
+        MasterShareNotNumber,
+        /// The "masterquorum" must be a number
+        MasterQuorumNotNumber,
+        /// The "compositionshare" must be a number
+        CompositionShareNotNumber,
+        /// The "compositionquorum" must be a number
+        CompositionQuorumNotNumber,
+        /// The "othercontractsshare" must be a number
+        OtherContractsShareNotNumber,
+        /// The "othercontractsquorum" must be a number
+        OtherContractsQuorumNotNumber,
+        /// The "crodwfundingshares" must be a number
+        CrowdFundingSharesNotNumber,
+        /// The quorum of a group owned by a single holder must be 100
+        QuorumHolderMismatch,
+        /// The percentages of the holders of a group must be > 0 and total 100
+        WrongTotalPercentageHolders,
+        /// Too many holders for a group
+        TooManyHolders,
+        /// An account is listed twice in the holders of a group
+        DuplicatedHolder,
+        /// The maximum number of contracts stored has been reached
+        GlobalContractLimitReached,
+        /// The crm data has a field not in the schema
+        UnknownField,
+        /// The account has created a contract less than CreationCooldown blocks ago
+        CreationRateLimited,
+        /// The owners must include the owner of the contract without duplicates and the threshold must be between 1 and their number
+        InvalidOwnerSet,
+        /// The action on a co-owned contract must be approved by the owners with approve_owner_action
+        ApprovalRequired,
+        /// The owner has already approved the action
+        AlreadyApproved,
+        /// The co-owned contract has the maximum number of actions waiting for approvals
+        TooManyPendingActions,
     }
-}
 
-// Dispatchable functions allows users to interact with the pallet and invoke state changes.
-// These functions materialize as "extrinsics", which are often compared to transactions.
-// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
-decl_module! {
-    pub struct Module<T: Config> for enum Call where origin: T::Origin {
-        // Errors must be initialized
-        type Error = Error<T>;
-        // Events must be initialized if they are used by the pallet.
-        fn deposit_event() = default;
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        // reset the number of contracts created in the block
+        fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+            ContractsInBlock::<T>::kill();
+            T::DbWeight::get().writes(1)
+        }
+
+        // migrate the storage to the current version, one step for every version and every step is idempotent
+        fn on_runtime_upgrade() -> Weight {
+            // the storage of the decl_storage pallet is moved first, the version is kept under the prefix
+            let mut weight = Self::migrate_storage_prefix();
+            weight = weight.saturating_add(T::DbWeight::get().reads(1));
+            let version = CrmStorageVersion::<T>::get();
+            if version >= STORAGE_VERSION {
+                return weight;
+            }
+            debug::info!("Crm storage migration from version {} to {}", version, STORAGE_VERSION);
+            if CrmStorageVersion::<T>::get() < 1 {
+                weight = weight.saturating_add(Self::migrate_to_v1());
+            }
+            if CrmStorageVersion::<T>::get() < 2 {
+                weight = weight.saturating_add(Self::migrate_to_v2());
+            }
+            if CrmStorageVersion::<T>::get() < 3 {
+                weight = weight.saturating_add(Self::migrate_to_v3());
+            }
+            if CrmStorageVersion::<T>::get() < 4 {
+                weight = weight.saturating_add(Self::migrate_to_v4());
+            }
+            if CrmStorageVersion::<T>::get() < 5 {
+                weight = weight.saturating_add(Self::migrate_to_v5());
+            }
+            if CrmStorageVersion::<T>::get() < 6 {
+                weight = weight.saturating_add(Self::migrate_to_v6());
+            }
+            if CrmStorageVersion::<T>::get() < 7 {
+                weight = weight.saturating_add(Self::migrate_to_v7());
+            }
+            if CrmStorageVersion::<T>::get() < 8 {
+                weight = weight.saturating_add(Self::migrate_to_v8());
+            }
+            weight
+        }
+    }
 
+    // Dispatchable functions allows users to interact with the pallet and invoke state changes.
+    // These functions materialize as "extrinsics", which are often compared to transactions.
+    // Dispatchable functions must be annotated with a weight and must return a DispatchResultWithPostInfo.
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
         // function to create a new Contract Rights Management (CRM), the crmid must be not already used json structures are expected. For crmdata:
         /*
         {
@@ -216,6 +1308,15 @@ decl_module! {
             "othercontratsquorum":75,  						// the quorum required to change the other countracts data
             "crowdfundingshare": 20,  						// crowd founders can get share
             "crowdfounders": "xxxxxx"					    // crowd funding campaign Id
+            "type": "album"                                 // type of contract: single, album, ep or compilation (optional)
+            "rateperplay": 10                               // royalties for every play reported, must be > 0 (optional)
+            "sharescale": "bps"                             // scale of the shares: "percent" total 100 (default) or "bps" total 10000 (optional)
+            "mechanicallicensefee": 500                     // fee of the mechanical license granted automatically, must be > 0 (optional)
+            "territories": ["DE","FR"]                      // territories of the rights, ISO 3166-1 alpha-2 codes, empty for worldwide (optional)
+            "withholdingpercent": 15                        // percentage of the claims withheld for the withholding account, 0..50 (optional)
+            "donationshare": 5,                             // share transferred to the donation account on every payment (optional)
+            "donationaccount": "5Grwva..."                  // SS58 address of the donation account, required with "donationshare"
+            "parentcontract": {"id": 1, "passthrough": 10}  // parent of a derivative work and percentage of the royalties passed through (optional)
         }
         for example:
         cmmrid can be: 3
@@ -230,282 +1331,161 @@ decl_module! {
         for Empty field you can use:
         {}
         */
-        #[weight = 50_000]
-        pub fn new_contract(origin, crmid: u32, crmdata: Vec<u8>,master: Vec<u8>,composition:Vec<u8>,othercontracts: Vec<u8>) -> dispatch::DispatchResult {
+        #[pallet::weight(T::WeightInfo::new_contract(crmdata.len() as u32))]
+        #[transactional]
+        pub fn new_contract(origin: OriginFor<T>, crmid: T::CrmId, crmdata: Vec<u8>,master: Vec<u8>,composition:Vec<u8>,othercontracts: Vec<u8>) -> DispatchResultWithPostInfo {
             // Check that the extrinsic was signed and get the signer.
             let sender = ensure_signed(origin)?;
-            // check crm data
-            ensure!(crmdata.len() >= 32, Error::<T>::CrmDataTooShort); //check minimum length
-            ensure!(crmdata.len() <= 1024, Error::<T>::CrmDataTooLong);  // check maximum length
-            // check master data
-            ensure!(master.len() >= 8, Error::<T>::MasterTooShort); //check minimum length
-            ensure!(master.len() <= 1024, Error::<T>::MasterTooLong);  // check maximum length
-            // check composition data
-            ensure!(composition.len() >= 8, Error::<T>::CompositionTooShort); //check minimum length
-            ensure!(composition.len() <= 1024, Error::<T>::CompositionTooLong);  // check maximum length
-            // check Other Contracts data
-            ensure!(othercontracts.len() <= 1024, Error::<T>::OtherContractsTooLong);  // check maximum length
-            // check oracleid
-            ensure!(crmid > 0, Error::<T>::InvalidValue); //check for crmid length >0
-            // check of the crmid is free
-            ensure!(!CrmData::contains_key(&crmid), Error::<T>::DuplicatedCrmId);
-            // check json validity
-            let js=crmdata.clone();
-            ensure!(json_check_validity(js),Error::<T>::InvalidJson);
-
-            // check ipfshash
-            let jsf=crmdata.clone();
-            let ipfshash=json_get_value(jsf,"ipfshash".as_bytes().to_vec());
-            ensure!(ipfshash.len() >= 46, Error::<T>::InvalidIpfsHash); //check minimum length for the Ipfs Hash
-            // check ipfshash private
-            let jsfp=crmdata.clone();
-            let ipfshashprivate=json_get_value(jsfp,"ipfshashprivate".as_bytes().to_vec());
-            ensure!(ipfshashprivate.len() >= 46, Error::<T>::InvalidIpfsHashPrivate);  //check minimum length for the Ipfs Hash Private
-            // check globalquorum
-            let jsgq=crmdata.clone();
-            let globalquorum=json_get_value(jsgq,"globalquorum".as_bytes().to_vec());
-            let globalquorumvalue=vecu8_to_u32(globalquorum);
-            ensure!(globalquorumvalue > 0, Error::<T>::InvalidGlobalQuorum); //check Global Quorum that must be > 0
-            ensure!(globalquorumvalue <= 100, Error::<T>::InvalidGlobalQuorum); //check Global Quorum that must be <=100
-            // check master shares
-            let jsms=crmdata.clone();
-            let mastershare=json_get_value(jsms,"mastershare".as_bytes().to_vec());
-            let mastersharevalue=vecu8_to_u32(mastershare);
-            ensure!(mastersharevalue > 0, Error::<T>::InvalidMasterShare); //check Master Shares  that must be > 0
-            ensure!(mastersharevalue <= 100, Error::<T>::InvalidMasterShare); //check Master Shares that must be <=100
-            // check master quorum
-            let jsmq=crmdata.clone();
-            let masterquorum=json_get_value(jsmq,"masterquorum".as_bytes().to_vec());
-            let masterquorumvalue=vecu8_to_u32(masterquorum);
-            ensure!(masterquorumvalue > 0, Error::<T>::InvalidMasterQuorum); //check Master Quorum that must be > 0
-            ensure!(masterquorumvalue <= 100, Error::<T>::InvalidMasterQuorum); //check Master Quorum that must be <=100
-            // check composition shares
-            let jscs=crmdata.clone();
-            let compositionshare=json_get_value(jscs,"compositionshare".as_bytes().to_vec());
-            let compositionsharevalue=vecu8_to_u32(compositionshare);
-            ensure!(compositionsharevalue > 0, Error::<T>::InvalidCompositionShare); //check Composition Shares  that must be > 0
-            ensure!(compositionsharevalue <= 100, Error::<T>::InvalidCompositionShare); //check Composition Shares that must be <=100
-            // check composition quorum
-            let jscq=crmdata.clone();
-            let compositionquorum=json_get_value(jscq,"compositionquorum".as_bytes().to_vec());
-            let compositionquorumvalue=vecu8_to_u32(compositionquorum);
-            ensure!(compositionquorumvalue > 0, Error::<T>::InvalidCompositionQuorum); //check Composition Quorum  that must be > 0
-            ensure!(compositionquorumvalue <= 100, Error::<T>::InvalidCompositionQuorum); //check Composition Quorum that must be <=100
-            // check othercontracts shares
-            let jsos=crmdata.clone();
-            let othercontractsshare=json_get_value(jsos,"othercontractsshare".as_bytes().to_vec());
-            let othercontractssharevalue=vecu8_to_u32(othercontractsshare);
-            ensure!(othercontractssharevalue <= 100, Error::<T>::InvalidOtherContractsShare); 	//check Composition Shares that must be <=100
-            // check other contracts quorum
-            let jsoq=crmdata.clone();
-            let othercontractsquorum=json_get_value(jsoq,"othercontractsquorum".as_bytes().to_vec());
-            let othercontractsquorumvalue=vecu8_to_u32(othercontractsquorum);
-            ensure!(othercontractsquorumvalue <= 100, Error::<T>::InvalidOtherContractsQuorum); //check other Contracts Quorum that must be <=100
-            // check crowdfundingshare
-            let jscf=crmdata.clone();
-            let crodwfundingshare=json_get_value(jscf,"crodwfundingshares".as_bytes().to_vec());
-            let crodwfundingsharevalue=vecu8_to_u32(crodwfundingshare);
-            ensure!(crodwfundingsharevalue <= 100, Error::<T>::InvalidCrowdFundingshares); //check Crowd Funding Shares that must be <=100
-            // check that the total shares are = 100
-            let totalshares=mastersharevalue+compositionsharevalue+othercontractssharevalue+crodwfundingsharevalue;
-            ensure!(totalshares == 100, Error::<T>::InvalidTotalShares); //check total shares that must be 100
-
-            // check validity of master data
-            let masterclone=master.clone();
-            // check for a valid json
-            ensure!(json_check_validity(masterclone),Error::<T>::InvalidJson);
-            let mut x=0;
-            let mut totpercentage:u32 = 0;
-            // check validity of records for Master Data
-            loop {
-                let jr=json_get_recordvalue(master.clone(),x);
-                if jr.is_empty() {
-                    break;
-                }
-                // check for nickname
-                let nickname=json_get_value(jr.clone(),"nickname".as_bytes().to_vec());
-                ensure!(!nickname.is_empty(), Error::<T>::MissingMasterNickname);
-                // check for account address
-                let account=json_get_value(jr.clone(),"account".as_bytes().to_vec());
-                ensure!(!account.is_empty(), Error::<T>::MissingMasterAccount);
-                // check for percentage
-                let percentage=json_get_value(jr.clone(),"percentage".as_bytes().to_vec());
-                ensure!(!percentage.is_empty(), Error::<T>::MissingMasterPercentage);
-                // convert percentage from vec to u32
-                let percentagevalue=vecu8_to_u32(percentage);
-                ensure!(percentagevalue >0, Error::<T>::MissingMasterPercentage);
-                // sum percentage to totpercentage
-                totpercentage += percentagevalue;
-                x += 1;
-            }
-            // check the total percentage is = 100 TODO
-            ensure!(totpercentage == 100, Error::<T>::WrongTotalPercentageMaster);
-
-            // check validity of composition data
-            let compositionclone=composition.clone();
-            // check for a valid json
-            ensure!(json_check_validity(compositionclone),Error::<T>::InvalidJson);
-            x=0;
-            totpercentage=0;
-            // check validity of records for Composition Data
-            loop {
-                let jr=json_get_recordvalue(composition.clone(),x);
-                if jr.is_empty() {
-                    break;
+            // the reserved range is for the system contracts created by root
+            ensure!(crmid > T::ReservedCrmIdMax::get(), Error::<T>::ReservedCrmIdRange.with_weight(EARLY_EXIT_WEIGHT));
+            Self::precheck_new_contract(&sender, crmid, &crmdata)?;
+            Self::create_contract(sender, crmid, crmdata, master, composition, othercontracts)?;
+            Ok(().into())
+        }
+        /// Create a new contract with the next free crmid, the id assigned is in the CrmAdded event: the post-info carries
+        /// the weight and the fee flag only, it cannot return the id. The counter is a single NextCrmId for all the accounts
+        /// rather than a counter per account, since the crmids are unique on chain, and the ids chosen manually are probed
+        /// and skipped. The data are validated as in new_contract. It fails with NoAvailableCrmId when the next
+        /// MAX_CRMID_PROBES ids are used, the next call continues the search after them.
+        #[pallet::weight(T::WeightInfo::new_contract(crmdata.len() as u32)
+            .saturating_add(T::DbWeight::get().reads(3 * MAX_CRMID_PROBES as Weight)))]
+        pub fn new_contract_auto(origin: OriginFor<T>, crmdata: Vec<u8>,master: Vec<u8>,composition:Vec<u8>,othercontracts: Vec<u8>) -> DispatchResultWithPostInfo {
+            // find the next crmid free, skipping the ids chosen manually and the reserved range
+            let mut crmid = NextCrmId::<T>::get().max(T::ReservedCrmIdMax::get().saturating_add(One::one())).max(One::one());
+            let mut probes = 0;
+            while CrmData::<T>::contains_key(&crmid) || CrmCommitments::<T>::contains_key(&crmid) || CrmDeleted::<T>::contains_key(&crmid) {
+                probes += 1;
+                if probes >= MAX_CRMID_PROBES {
+                    // the failed calls are not reverted, the search is resumed after the ids probed
+                    NextCrmId::<T>::put(crmid.saturating_add(One::one()));
+                    return Err(Error::<T>::NoAvailableCrmId.into());
                 }
-                // check for nickname
-                let nickname=json_get_value(jr.clone(),"nickname".as_bytes().to_vec());
-                ensure!(!nickname.is_empty(), Error::<T>::MissingCompositionNickname);
-                // check for account address
-                let account=json_get_value(jr.clone(),"account".as_bytes().to_vec());
-                ensure!(!account.is_empty(), Error::<T>::MissingCompositionAccount);
-                // check for percentage
-                let percentage=json_get_value(jr.clone(),"percentage".as_bytes().to_vec());
-                ensure!(!percentage.is_empty(), Error::<T>::MissingCompositionPercentage);
-                // convert percentage from vec to u32
-                let percentagevalue=vecu8_to_u32(percentage);
-                ensure!(percentagevalue >0, Error::<T>::MissingCompositionPercentage);
-                // sum percentage to totpercentage
-                totpercentage+=percentagevalue;
-                x+=1;
+                crmid = crmid.checked_add(&One::one()).ok_or(Error::<T>::NoAvailableCrmId)?;
             }
-            // check the total percentage is = 100
-            ensure!(totpercentage == 100, Error::<T>::WrongTotalPercentageComposition);
-
-
-            // Other contracts are optional we check the validity if there is a value only
-            if othercontracts.len()>10 {
-                // check validity of othercontracts data
-                let othercontractsclone=othercontracts.clone();
-                // check for a valid json
-                ensure!(json_check_validity(othercontractsclone),Error::<T>::InvalidJson);
-                x=0;
-                totpercentage= 0;
-                // check validity of records for other contracts data
-                loop {
-                    let jr=json_get_recordvalue(othercontracts.clone(),x);
-                    if jr.is_empty() {
-                        break;
-                    }
-                    // check for id
-                    let id=json_get_value(jr.clone(),"id".as_bytes().to_vec());
-                    ensure!(!id.is_empty(), Error::<T>::MissingOtherContractsId);
-                    let idvalue=vecu8_to_u32(id);
-                    // check that the id is on chain
-                    ensure!(CrmData::contains_key(&idvalue), Error::<T>::InvalidContractId);
-                    // check for percentage
-                    let percentage=json_get_value(jr.clone(),"percentage".as_bytes().to_vec());
-                    ensure!(!percentage.is_empty(), Error::<T>::MissingOtherContractsPercentage);
-                    // convert percentage from vec to u32
-                    let percentagevalue=vecu8_to_u32(percentage);
-                    ensure!(percentagevalue >0, Error::<T>::MissingOtherContractsPercentage);
-                    // sum percentage to totpercentage
-                    totpercentage+=percentagevalue;
-                    x+=1;
-                }
-                // check the total percentage is = 100
-                ensure!(totpercentage == 100, Error::<T>::WrongTotalPercentageOtherContracts);
-            }
-
-            //****************************************
-            // STORING DATA
-            //****************************************
-            // Write storage for crmdata
-            CrmData::insert(&crmid, crmdata);
-            // Write the storage for master data
-            CrmMasterData::insert(crmid, master);
-            // Write the storage for Composition data
-            CrmCompositionData::insert(crmid, composition);
-            // write the storage for Other Contracts data (optional)
-            if !othercontracts.is_empty() {
-                // Update storage for Other Contracts data
-                CrmOtherContractsData::insert(crmid, othercontracts);
+            ensure!(crmid != Self::reserved_crm_id(), Error::<T>::NoAvailableCrmId);
+            Self::new_contract(origin, crmid, crmdata, master, composition, othercontracts)?;
+            NextCrmId::<T>::put(crmid.saturating_add(One::one()));
+            Ok(().into())
+        }
+        /// Create a new contract on behalf of the artist, for example by a label. The artist authorizes the creation
+        /// signing the SCALE encoding of (genesis hash, nonce, crmid, crmdata, master, composition, othercontracts), with
+        /// the current delegation nonce of the artist. The contract is owned by the artist.
+        #[pallet::weight(T::WeightInfo::new_contract(crmdata.len() as u32))]
+        pub fn new_contract_delegated(origin: OriginFor<T>, artist: T::AccountId, crmid: T::CrmId, crmdata: Vec<u8>,master: Vec<u8>,composition:Vec<u8>,othercontracts: Vec<u8>, signature: T::Signature) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed, the fees are paid by the signer
+            ensure_signed(origin)?;
+            // check the signature of the artist, the nonce prevents a replay once the contract is removed and the
+            // genesis hash a replay on another chain
+            let nonce = DelegationNonce::<T>::get(&artist);
+            let genesis = <frame_system::Module<T>>::block_hash(T::BlockNumber::zero());
+            let payload = (genesis, nonce, crmid, &crmdata, &master, &composition, &othercontracts).encode();
+            ensure!(signature.verify(&payload[..], &artist), Error::<T>::BadSignature);
+            let post = Self::new_contract(frame_system::RawOrigin::Signed(artist.clone()).into(), crmid, crmdata, master, composition, othercontracts)?;
+            DelegationNonce::<T>::insert(&artist, nonce.wrapping_add(1));
+            Ok(post)
+        }
+        /// Create a system contract owned by the account, it can be submitted by root only and the crmid can be in the
+        /// reserved range. The data are validated as in new_contract, the deposit and the creation fee are paid by the owner.
+        #[pallet::weight(T::WeightInfo::new_contract(crmdata.len() as u32))]
+        #[transactional]
+        pub fn force_new_contract(origin: OriginFor<T>, owner: T::AccountId, crmid: T::CrmId, crmdata: Vec<u8>,master: Vec<u8>,composition:Vec<u8>,othercontracts: Vec<u8>) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            Self::precheck_new_contract(&owner, crmid, &crmdata)?;
+            Self::create_contract(owner, crmid, crmdata, master, composition, othercontracts)?;
+            Ok(().into())
+        }
+        /// Create a contract stored by hash: the json is kept on IPFS and only its cid, its blake2-256 hash and the shares
+        /// are stored on chain. The shares are validated on the struct, the contract has no master, composition or other
+        /// contracts data and it's listed in CrmCommitments instead of CrmData.
+        #[pallet::weight(50_000)]
+        #[transactional]
+        pub fn new_contract_by_hash(origin: OriginFor<T>, crmid: T::CrmId, ipfscid: Vec<u8>, datahash: [u8; 32], shares: CrmShares) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            ensure!(crmid > T::ReservedCrmIdMax::get(), Error::<T>::ReservedCrmIdRange);
+            ensure!(!crmid.is_zero(), Error::<T>::InvalidValue);
+            ensure!(crmid != Self::reserved_crm_id(), Error::<T>::ReservedCrmId);
+            ensure!(Self::ipfshash_in_bounds(&ipfscid), Error::<T>::InvalidIpfsHash);
+            Self::check_new_crmid(&sender, crmid)?;
+            Self::check_shares(&shares)?;
+            // the deposit covers the cid and the hash
+            Self::charge_creation(&sender, crmid, ipfscid.len() + datahash.len())?;
+            Self::index_ipfshash(crmid, &[], &ipfscid)?;
+            CrmCommitments::<T>::insert(crmid, CrmCommitment { ipfscid, datahash, shares });
+            Self::register_contract(&sender, crmid);
+            Self::deposit_crm_event(crmid, Event::CrmAddedByHash(sender, crmid));
+            Ok(().into())
+        }
+        /// Create a contract when the crmid is not on chain, otherwise replace its crm data. The master, composition and
+        /// other contracts data are used for the creation only. The update can be submitted by the owner of the contract or an editor
+        /// and the crowdfunding share and campaign cannot be changed.
+        /// When "expectedversion" is set, the call fails if the version of the contract is different (0 for a new contract).
+        #[pallet::weight(T::WeightInfo::new_contract(crmdata.len() as u32))]
+        #[transactional]
+        pub fn upsert_crmdata(origin: OriginFor<T>, crmid: T::CrmId, crmdata: Vec<u8>,master: Vec<u8>,composition:Vec<u8>,othercontracts: Vec<u8>, expectedversion: Option<u32>) -> DispatchResultWithPostInfo {
+            Self::check_version(crmid, expectedversion).map_err(|e| e.with_weight(Self::early_exit_weight(1)))?;
+            if !CrmData::<T>::contains_key(&crmid) {
+                return Self::new_contract(origin, crmid, crmdata, master, composition, othercontracts);
             }
-            // Emit an event
-            Self::deposit_event(RawEvent::CrmAdded(sender,crmid));
-            // Return a successful DispatchResult
-            Ok(())
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            Self::update_crmdata(sender, crmid, crmdata, ChangeKind::FullReplace)?;
+            Ok(().into())
+        }
+        /// Change some fields of the main crm data of a contract: the top level fields of the patch (a json object) replace
+        /// the fields with the same key and the others are added. The result is validated as in upsert_crmdata, the patch
+        /// can be submitted by the owner of the contract or an editor.
+        #[pallet::weight(T::WeightInfo::patch_crmdata(T::MaxCrmDataLength::get()))]
+        #[transactional]
+        pub fn patch_crmdata(origin: OriginFor<T>, crmid: T::CrmId, patch: Vec<u8>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            ensure!(
+                patch.len() as u32 <= T::MaxCrmDataLength::get(),
+                Error::<T>::CrmDataTooLong.with_weight(EARLY_EXIT_WEIGHT)
+            );
+            ensure!(
+                CrmData::<T>::contains_key(&crmid),
+                Error::<T>::InvalidContractId.with_weight(Self::early_exit_weight(1))
+            );
+            Self::check_json(patch.clone())?;
+            let current = CrmData::<T>::get(&crmid).unwrap_or_default();
+            let crmdata = json_merge(&current, &patch).ok_or(Error::<T>::InvalidJson)?;
+            Self::update_crmdata(sender, crmid, crmdata, json_change_kind(&patch))?;
+            Ok(().into())
         }
 
 
 
         /// Submit a change proposal for CRM main data that must be approved by voting
-        #[weight = 50_000]
-        pub fn change_proposal_crmdata(origin, changeid: u32, crmdata: Vec<u8>) -> dispatch::DispatchResult {
+        #[pallet::weight(T::WeightInfo::change_proposal_crmdata(crmdata.len() as u32))]
+        pub fn change_proposal_crmdata(origin: OriginFor<T>, changeid: u32, crmdata: Vec<u8>) -> DispatchResultWithPostInfo {
             // Check that the extrinsic is signed and get the signer.
             let sender = ensure_signed(origin)?;
             // check that at the least some data to change has been received and it's not too long
-            ensure!(!crmdata.is_empty(), Error::<T>::MissingContractData);
-            ensure!(crmdata.len()<1024, Error::<T>::CrmDataTooLong);
+            ensure!(!crmdata.is_empty(), Error::<T>::MissingContractData.with_weight(EARLY_EXIT_WEIGHT));
+            ensure!(crmdata.len() as u32 <= T::MaxCrmDataLength::get(), Error::<T>::CrmDataTooLong.with_weight(EARLY_EXIT_WEIGHT));
+            // check the changeid is NOT on chain, before the json validation
+            ensure!(!CrmDataChangeProposal::<T>::contains_key(changeid), Error::<T>::ChangeIdDuplicated.with_weight(Self::early_exit_weight(1)));
             // check the validity of the proposed CRM data
             let js=crmdata.clone();
-            ensure!(json_check_validity(js),Error::<T>::InvalidJson);
+            Self::check_json(js)?;
             // check crmid field in json
             let jscm=crmdata.clone();
             let crmidjs=json_get_value(jscm,"crmid".as_bytes().to_vec());
-            let crmid=vecu8_to_u32(crmidjs);
+            let crmid=vecu8_to_crmid::<T::CrmId>(crmidjs);
             // check the contract id (crmid field in json), IS on chain
-            ensure!(CrmData::contains_key(&crmid), Error::<T>::InvalidContractId);
-            // check the changeid is NOT on chain
-            ensure!(!CrmDataChangeProposal::contains_key(changeid), Error::<T>::ChangeIdDuplicated);
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            // the contract must be changeable as for the direct updates
+            ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+            Self::ensure_unlocked(crmid)?;
             // get the currentquorum for Global data from main contractid
-            let crmdataq=CrmData::get(&crmid).unwrap();
+            let crmdataq=CrmData::<T>::get(&crmid).unwrap();
             let currentquorumj=json_get_value(crmdataq,"globalquorum".as_bytes().to_vec());
             let currentquorum=vecu8_to_u32(currentquorumj);
             ensure!(currentquorum >0 && currentquorum <=100, Error::<T>::InvalidMasterQuorum);
-            // check ipfshash
-            let jsf=crmdata.clone();
-            let ipfshash=json_get_value(jsf,"ipfshash".as_bytes().to_vec());
-            ensure!(ipfshash.len() >= 46, Error::<T>::InvalidIpfsHash); //check minimum length for the Ipfs Hash
-            // check ipfshash private
-            let jsfp=crmdata.clone();
-            let ipfshashprivate=json_get_value(jsfp,"ipfshashprivate".as_bytes().to_vec());
-            ensure!(ipfshashprivate.len() >= 46, Error::<T>::InvalidIpfsHashPrivate); //check minimum length for the Ipfs Hash Private
-            // check globalquorum
-            let jsgq=crmdata.clone();
-            let globalquorum=json_get_value(jsgq,"globalquorum".as_bytes().to_vec());
-            let globalquorumvalue=vecu8_to_u32(globalquorum);
-            ensure!(globalquorumvalue > 0 && globalquorumvalue <= 100, Error::<T>::InvalidGlobalQuorum);
-            // check master shares
-            let jsms=crmdata.clone();
-            let mastershare=json_get_value(jsms,"mastershare".as_bytes().to_vec());
-            let mastersharevalue=vecu8_to_u32(mastershare);
-            ensure!(mastersharevalue > 0 && mastersharevalue <= 100, Error::<T>::InvalidMasterShare); //check Master Shares  that must be > 0
-            // check master quorum
-            let jsmq=crmdata.clone();
-            let masterquorum=json_get_value(jsmq,"masterquorum".as_bytes().to_vec());
-            let masterquorumvalue=vecu8_to_u32(masterquorum);
-            ensure!(masterquorumvalue > 0 && masterquorumvalue <= 100, Error::<T>::InvalidMasterQuorum); //check Master Quorum that must be > 0
-            // check composition shares
-            let jscs=crmdata.clone();
-            let compositionshare=json_get_value(jscs,"compositionshare".as_bytes().to_vec());
-            let compositionsharevalue=vecu8_to_u32(compositionshare);
-            ensure!(compositionsharevalue > 0 && compositionsharevalue <= 100, Error::<T>::InvalidCompositionShare); //check Composition Shares  that must be > 0
-            // check composition quorum
-            let jscq=crmdata.clone();
-            let compositionquorum=json_get_value(jscq,"compositionquorum".as_bytes().to_vec());
-            let compositionquorumvalue=vecu8_to_u32(compositionquorum);
-            ensure!(compositionquorumvalue > 0 && compositionquorumvalue <= 100, Error::<T>::InvalidCompositionQuorum); //check Composition Quorum  that must be > 0
-            // check othercontracts shares
-            let jsos=crmdata.clone();
-            let othercontractsshare=json_get_value(jsos,"othercontractsshare".as_bytes().to_vec());
-            let othercontractssharevalue=vecu8_to_u32(othercontractsshare);
-            ensure!(othercontractssharevalue <= 100, Error::<T>::InvalidOtherContractsShare); 	//check Composition Shares that must be <=100
-            // check other contracts quorum
-            let jsoq=crmdata.clone();
-            let othercontractsquorum=json_get_value(jsoq,"othercontractsquorum".as_bytes().to_vec());
-            let othercontractsquorumvalue=vecu8_to_u32(othercontractsquorum);
-            ensure!(othercontractsquorumvalue <= 100, Error::<T>::InvalidOtherContractsQuorum); //check other Contracts Quorum that must be <=100
-            // check crowdfundingshare
-            let jscf=crmdata.clone();
-            let crodwfundingshare=json_get_value(jscf,"crodwfundingshares".as_bytes().to_vec());
-            let crodwfundingsharevalue=vecu8_to_u32(crodwfundingshare);
-            ensure!(crodwfundingsharevalue <= 100, Error::<T>::InvalidCrowdFundingshares); //check Crowd Funding Shares that must be <=100
-            // check that the total shares are = 100
-            let totalshares=mastersharevalue+compositionsharevalue+othercontractssharevalue+crodwfundingsharevalue;
-            ensure!(totalshares == 100, Error::<T>::InvalidTotalShares); //check total shares that must be 100
+            // check the fields of the crm data
+            Self::check_crmdata(&crmdata, crmid)?;
             // store the proposal data in the queue.
-            CrmDataChangeProposal::insert(changeid, crmdata);
+            CrmDataChangeProposal::<T>::insert(changeid, crmdata);
             // store initial voting results with current quorum required to change the data
             let v= Voting {
                 changeid,
@@ -516,31 +1496,33 @@ decl_module! {
                 percvotesyes: 0,
                 percvotesno: 0,
             };
-            CrmDataChangeVotingResult::insert(changeid,v);
+            CrmDataChangeVotingResult::<T>::insert(changeid,v);
             // Emit an event
-            Self::deposit_event(RawEvent::CrmDataNewChangeProposal(sender,crmid,changeid));
-            Ok(())
+            Self::deposit_event(Event::CrmDataNewChangeProposal(sender,crmid,changeid));
+            Ok(().into())
         }
         /// Vote a change proposal for CRM data
-        #[weight = 10_000]
-        pub fn vote_proposal_crmdata(origin, changeid: u32, vote: bool) -> dispatch::DispatchResult {
+        #[pallet::weight(T::WeightInfo::vote_proposal_crmdata(T::MaxCrmDataLength::get()))]
+        #[transactional]
+        pub fn vote_proposal_crmdata(origin: OriginFor<T>, changeid: u32, vote: bool) -> DispatchResultWithPostInfo {
             // Check that the extrinsic is signed and get the signer.
             let sender = ensure_signed(origin)?;
             // check changeid
             ensure!(changeid > 0, Error::<T>::ChangeIdTooShort); //check minimum length
             // check the changeid change proposal is on chain
-            ensure!(CrmDataChangeProposal::contains_key(changeid), Error::<T>::ChangeIdNotFound);
+            ensure!(CrmDataChangeProposal::<T>::contains_key(changeid), Error::<T>::ChangeIdNotFound);
             // check for double voting
             ensure!(!CrmDataChangeVoteCasted::<T>::contains_key(&sender,changeid), Error::<T>::VoteCastedAlready);
             // get crmid from the change proposal
-            let jsc=CrmDataChangeProposal::get(&changeid).unwrap();
+            let jsc=CrmDataChangeProposal::<T>::get(&changeid).unwrap();
             let crmidj=json_get_value(jsc,"crmid".as_bytes().to_vec());
-            let crmid=vecu8_to_u32(crmidj);
+            let crmid=vecu8_to_crmid::<T::CrmId>(crmidj);
             // check the contract id is on chain
-            ensure!(CrmData::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
 
             // get the percentage of votes for "Masters"
-            let crmdata=CrmData::get(&crmid).unwrap_or_default();
+            let crmdata=CrmData::<T>::get(&crmid).unwrap_or_default();
+            let sharescale=json_get_share_scale(crmdata.clone()).unwrap_or(100);
             let js=crmdata.clone();
             let mastershare=json_get_value(js,"mastershare".as_bytes().to_vec());
             let mastersharevalue=vecu8_to_u32(mastershare);
@@ -553,7 +1535,7 @@ decl_module! {
             let othercontractsshare=json_get_value(jsc,"othercontractsshare".as_bytes().to_vec());
             let othercontractssharevalue=vecu8_to_u32(othercontractsshare);
             // check if the signer is one of the Master Accounts
-            let masterdata=CrmMasterData::get(crmid).unwrap_or_default();
+            let masterdata=CrmMasterData::<T>::get(crmid).unwrap_or_default();
             let mut x=0;
             let mut votepercentage=0;
             loop {
@@ -578,12 +1560,12 @@ decl_module! {
                 let accountid=T::AccountId::decode(&mut &buffer[..]).unwrap_or_default();
                 // verify account matching between AccountId types
                 if accountid==sender && mastersharevalue>0 {
-                        votepercentage += percentagevalue*mastersharevalue/100;
+                        votepercentage += percentagevalue*mastersharevalue/sharescale;
                 }
                 x+=1;
             }
             // check if the signer is one of the Composition Accounts
-            let compositiondata=CrmCompositionData::get(crmid).unwrap_or_default();
+            let compositiondata=CrmCompositionData::<T>::get(crmid).unwrap_or_default();
             x=0;
             loop {
                 let jr=json_get_recordvalue(compositiondata.clone(),x);
@@ -609,14 +1591,14 @@ decl_module! {
                 if accountid==sender{
                     //debug::info!("COMPOSITION IS MATCHING - compositionsharevalue:{} percentagevalue: {} percentage_str: {}",compositionsharevalue,percentagevalue,percentage_str);
                     if compositionsharevalue>0 {
-                        votepercentage += percentagevalue*compositionsharevalue/100
+                        votepercentage += percentagevalue*compositionsharevalue/sharescale
                         //debug::info!("COMPOSITION - votepercentage:{} ",votepercentage);
                     }
                 }
                 x+=1;
             }
             // check if the signer is part of any "other contract"
-            let othercontractsdata=CrmOtherContractsData::get(crmid).unwrap_or_default();
+            let othercontractsdata=CrmOtherContractsData::<T>::get(crmid).unwrap_or_default();
             //debug::info!("[DEBUG] othercontractsdata: {:?}",othercontractsdata);
             if othercontractsdata.len()>10{
                 x=0;
@@ -627,8 +1609,8 @@ decl_module! {
                     }
                     let id=json_get_value(jr.clone(),"id".as_bytes().to_vec());
                     ensure!(!id.is_empty(), Error::<T>::InvalidContractIdVoting);
-                    let idvalue=vecu8_to_u32(id);
-                    ensure!(idvalue >0, Error::<T>::InvalidContractIdVotingNumeric);
+                    let idvalue=vecu8_to_crmid::<T::CrmId>(id);
+                    ensure!(!idvalue.is_zero(), Error::<T>::InvalidContractIdVotingNumeric);
                     // check for percentage
                     let percentage=json_get_value(jr.clone(),"percentage".as_bytes().to_vec());
                     ensure!(!percentage.is_empty(), Error::<T>::MissingOtherContractsPercentage);
@@ -637,7 +1619,7 @@ decl_module! {
                     ensure!(percentagevalue>0, Error::<T>::MissingOtherContractsPercentage);
                     // check Master record of the other contract
                     let mut xx=0;
-                    let masterdata=CrmMasterData::get(idvalue).unwrap();
+                    let masterdata=CrmMasterData::<T>::get(idvalue).unwrap();
                     loop {
                         let jr=json_get_recordvalue(masterdata.clone(),xx);
                         if jr.is_empty() {
@@ -660,7 +1642,7 @@ decl_module! {
                         let accountid=T::AccountId::decode(&mut &buffer[..]).unwrap_or_default();
                         // verify account matching between AccountId types
                         if accountid == sender && othercontractssharevalue >0 {
-                                votepercentage+=percentagevalue*othercontractssharevalue/100;
+                                votepercentage+=percentagevalue*othercontractssharevalue/sharescale;
                         }
                         xx+=1;
                     }
@@ -670,7 +1652,7 @@ decl_module! {
             // check if the signer has rights to vote >0
             ensure!(votepercentage > 0, Error::<T>::SignerHasNoRightsForVoting);
             // store the vote
-            let mut v:Voting=CrmDataChangeVotingResult::get(changeid).unwrap_or_default();
+            let mut v:VotingOf<T>=CrmDataChangeVotingResult::<T>::get(changeid).unwrap_or_default();
             let currentpervotesyes=v.percvotesyes;
             // update the voting structure
             if vote {
@@ -681,54 +1663,65 @@ decl_module! {
                 v.percvotesno+=votepercentage;
             }
             //update the storage with voting results
-            CrmDataChangeVotingResult::remove(changeid);
-            CrmDataChangeVotingResult::insert(changeid,v.clone());
+            CrmDataChangeVotingResult::<T>::remove(changeid);
+            CrmDataChangeVotingResult::<T>::insert(changeid,v.clone());
             // store the vote for the account id
             CrmDataChangeVoteCasted::<T>::insert(sender.clone(),changeid,vote);
             // Emit an event to alert the user of the vote received
             //debug::info!("[DEBUG] Emit Event for Vote");
-            Self::deposit_event(RawEvent::CrmDataChangeVote(sender.clone(),crmid,changeid));
+            Self::deposit_event(Event::CrmDataChangeVote(sender.clone(),crmid,changeid));
             // if quorum has been reached, we replace the current CRM data with the one voted from the majority
             if v.percvotesyes>=v.quorum && v.quorum>=currentpervotesyes {
                 //debug::info!("[DEBUG] CHANGE APPROVED ON CRMDATA!");
-                let crmdata=CrmDataChangeProposal::get(changeid).unwrap();
-                CrmData::remove(crmid);
-                CrmData::insert(crmid, crmdata);
+                // the contract could have been revoked or locked since the proposal, the approving vote is reverted
+                ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+                Self::ensure_unlocked(crmid)?;
+                let crmdata=CrmDataChangeProposal::<T>::get(changeid).unwrap();
+                // the parent contract is checked again, a cycle could be created after the proposal
+                Self::store_parent_contract(crmid, Self::check_parent_contract(&crmdata, crmid)?);
+                // move the link of the crowdfunding campaign
+                Self::unlink_crowdfunding_campaign(crmid);
+                Self::link_crowdfunding_campaign(crmid, &crmdata);
+                Self::store_territories(crmid, json_get_territories(crmdata.clone()).unwrap_or_default());
+                // the entry is overwritten, the previous ipfs hash is read to move its index entry
+                Self::store_crmdata(crmid, crmdata)?;
+                let version = Self::bump_version(crmid);
                 // Emit an event to alert the user of the crm data change done
-                Self::deposit_event(RawEvent::CrmDataChanged(sender,crmid));
+                Self::log_change(crmid, &sender, ChangeKind::FullReplace);
+                Self::deposit_crm_event(crmid, Event::CrmDataChanged(sender, crmid, version, ChangeKind::FullReplace));
             }
             // returns back with no errors
-            Ok(())
+            Ok(().into())
         }
         /// Submit a change proposal for CRM master data that must be approved by voting from master members only
-        #[weight = 50_000]
-        pub fn change_proposal_crm_masterdata(origin, changeid: u32, masterdata: Vec<u8>) -> dispatch::DispatchResult {
+        #[pallet::weight(T::WeightInfo::change_proposal_crm_masterdata())]
+        pub fn change_proposal_crm_masterdata(origin: OriginFor<T>, changeid: u32, masterdata: Vec<u8>) -> DispatchResultWithPostInfo {
             // Check that the extrinsic is signed and get the signer.
             let sender = ensure_signed(origin)?;
             // check that at the least some data to change has been received and it's not too long
-            ensure!(!masterdata.is_empty(), Error::<T>::MissingContractData);
-            ensure!(masterdata.len()<1024, Error::<T>::CrmDataTooLong);
+            ensure!(!masterdata.is_empty(), Error::<T>::MissingContractData.with_weight(EARLY_EXIT_WEIGHT));
+            ensure!(masterdata.len()<1024, Error::<T>::CrmDataTooLong.with_weight(EARLY_EXIT_WEIGHT));
+            // check the changeid is NOT on chain, before the json validation
+            ensure!(!CrmMasterDataChangeProposal::<T>::contains_key(changeid), Error::<T>::ChangeIdDuplicated.with_weight(Self::early_exit_weight(1)));
             // check the json validity of the proposed CRM master data
             let js=masterdata.clone();
-            ensure!(json_check_validity(js),Error::<T>::InvalidJson);
+            Self::check_json(js)?;
             // check crmid field in json
             let jscm=masterdata.clone();
             let crmidjs=json_get_value(jscm,"crmid".as_bytes().to_vec());
-            let crmid=vecu8_to_u32(crmidjs);
+            let crmid=vecu8_to_crmid::<T::CrmId>(crmidjs);
             // check the contract id (crmid field in json), IS on chain on both storage, main and master data
-            ensure!(CrmMasterData::contains_key(&crmid), Error::<T>::InvalidContractId);
-            ensure!(CrmData::contains_key(&crmid), Error::<T>::InvalidContractId);
-            // check the changeid is NOT on chain
-            ensure!(!CrmMasterDataChangeProposal::contains_key(changeid), Error::<T>::ChangeIdDuplicated);
+            ensure!(CrmMasterData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
             // get the quorum for Master data from main contractid
-            let crmdata=CrmData::get(&crmid).unwrap();
+            let crmdata=CrmData::<T>::get(&crmid).unwrap();
             let currentquorumj=json_get_value(crmdata,"masterquorum".as_bytes().to_vec());
             let currentquorum=vecu8_to_u32(currentquorumj);
             ensure!(currentquorum >0 && currentquorum <=100, Error::<T>::InvalidMasterQuorum);
             // check validity of master data
             let masterclone=masterdata.clone();
             // check for a valid json
-            ensure!(json_check_validity(masterclone),Error::<T>::InvalidJson);
+            Self::check_json(masterclone)?;
             let mut x=0;
             let mut totpercentage:u32 = 0;
             // check validity of records for Master Data
@@ -757,7 +1750,7 @@ decl_module! {
             ensure!(totpercentage == 100, Error::<T>::WrongTotalPercentageMaster);
 
             // store the proposal data in the queue.
-            CrmMasterDataChangeProposal::insert(changeid, masterdata);
+            CrmMasterDataChangeProposal::<T>::insert(changeid, masterdata);
             // store initial voting results with current quorum required to change the data
             let v= Voting {
                 changeid,
@@ -768,30 +1761,30 @@ decl_module! {
                 percvotesyes: 0,
                 percvotesno: 0,
             };
-            CrmMasterDataChangeVotingResult::insert(changeid,v);
+            CrmMasterDataChangeVotingResult::<T>::insert(changeid,v);
             // Emit an event
-            Self::deposit_event(RawEvent::CrmMasterDataNewChangeProposal(sender,crmid,changeid));
-            Ok(())
+            Self::deposit_event(Event::CrmMasterDataNewChangeProposal(sender,crmid,changeid));
+            Ok(().into())
         }
         /// Vote a change proposal for CRM master data
-        #[weight = 10_000]
-        pub fn vote_proposal_crm_masterdata(origin, changeid: u32, vote: bool) -> dispatch::DispatchResult {
+        #[pallet::weight(T::WeightInfo::vote_proposal_crm_masterdata())]
+        pub fn vote_proposal_crm_masterdata(origin: OriginFor<T>, changeid: u32, vote: bool) -> DispatchResultWithPostInfo {
             // Check that the extrinsic is signed and get the signer.
             let sender = ensure_signed(origin)?;
             // check changeid
             ensure!(changeid > 0, Error::<T>::ChangeIdTooShort); //check minimum length
             // check the changeid change proposal is on chain
-            ensure!(CrmMasterDataChangeProposal::contains_key(changeid), Error::<T>::ChangeIdNotFound);
+            ensure!(CrmMasterDataChangeProposal::<T>::contains_key(changeid), Error::<T>::ChangeIdNotFound);
             // check for double voting
             ensure!(!CrmMasterDataChangeVoteCasted::<T>::contains_key(&sender,changeid), Error::<T>::VoteCastedAlready);
             // get crmid from the change proposal
-            let jsc=CrmMasterDataChangeProposal::get(&changeid).unwrap();
+            let jsc=CrmMasterDataChangeProposal::<T>::get(&changeid).unwrap();
             let crmidj=json_get_value(jsc,"crmid".as_bytes().to_vec());
-            let crmid=vecu8_to_u32(crmidj);
+            let crmid=vecu8_to_crmid::<T::CrmId>(crmidj);
             // check the contract id is on chain
-            ensure!(CrmMasterData::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(CrmMasterData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
             // check if the signer is one of the Master Accounts
-            let masterdata=CrmMasterData::get(crmid).unwrap_or_default();
+            let masterdata=CrmMasterData::<T>::get(crmid).unwrap_or_default();
             let mut x=0;
             let mut votepercentage=0;
             loop {
@@ -822,7 +1815,7 @@ decl_module! {
             // check if the signer has rights to vote >0
             ensure!(votepercentage > 0, Error::<T>::SignerHasNoRightsForVoting);
             // store the vote
-            let mut v:Voting=CrmMasterDataChangeVotingResult::get(changeid).unwrap_or_default();
+            let mut v:VotingOf<T>=CrmMasterDataChangeVotingResult::<T>::get(changeid).unwrap_or_default();
             let currentpervotesyes=v.percvotesyes;
             // update the voting structure
             if vote {
@@ -833,53 +1826,55 @@ decl_module! {
                 v.percvotesno+=votepercentage;
             }
             //update the storage with voting results
-            CrmMasterDataChangeVotingResult::remove(changeid);
-            CrmMasterDataChangeVotingResult::insert(changeid,v.clone());
+            CrmMasterDataChangeVotingResult::<T>::remove(changeid);
+            CrmMasterDataChangeVotingResult::<T>::insert(changeid,v.clone());
             // store the vote for the account id
             CrmMasterDataChangeVoteCasted::<T>::insert(sender.clone(),changeid,vote);
             // Emit an event to alert the user of the vote received
-            Self::deposit_event(RawEvent::CrmMasterDataChangeVote(sender.clone(),crmid,changeid));
+            Self::deposit_event(Event::CrmMasterDataChangeVote(sender.clone(),crmid,changeid));
             // if quorum has been reached, we replace the current CRM data with the one voted from the majority
             if v.percvotesyes>=v.quorum && v.quorum>currentpervotesyes {
                 //debug::info!("[DEBUG] CHANGE APPROVED ON CRMDATA!");
-                let crmdata=CrmMasterDataChangeProposal::get(changeid).unwrap();
-                CrmMasterData::remove(crmid);
-                CrmMasterData::insert(crmid, crmdata);
+                let crmdata=CrmMasterDataChangeProposal::<T>::get(changeid).unwrap();
+                CrmMasterData::<T>::remove(crmid);
+                CrmMasterData::<T>::insert(crmid, crmdata);
+                Self::bump_version(crmid);
+                Self::log_change(crmid, &sender, ChangeKind::GroupData);
                 // Emit an event to alert the user of the crm data change done
-                Self::deposit_event(RawEvent::CrmMasterDataChanged(sender,crmid));
+                Self::deposit_event(Event::CrmMasterDataChanged(sender,crmid));
             }
             // returns back with no errors
-            Ok(())
+            Ok(().into())
         }
         /// Submit a change proposal for CRM composition data that must be approved by voting from composition members only
-        #[weight = 50_000]
-        pub fn change_proposal_crm_compositiondata(origin, changeid: u32, compositiondata: Vec<u8>) -> dispatch::DispatchResult {
+        #[pallet::weight(T::WeightInfo::change_proposal_crm_compositiondata())]
+        pub fn change_proposal_crm_compositiondata(origin: OriginFor<T>, changeid: u32, compositiondata: Vec<u8>) -> DispatchResultWithPostInfo {
             // Check that the extrinsic is signed and get the signer.
             let sender = ensure_signed(origin)?;
             // check that at the least some data to change has been received and it's not too long
-            ensure!(!compositiondata.is_empty(), Error::<T>::MissingContractData);
-            ensure!(compositiondata.len()<1024, Error::<T>::CrmDataTooLong);
+            ensure!(!compositiondata.is_empty(), Error::<T>::MissingContractData.with_weight(EARLY_EXIT_WEIGHT));
+            ensure!(compositiondata.len()<1024, Error::<T>::CrmDataTooLong.with_weight(EARLY_EXIT_WEIGHT));
+            // check the changeid is NOT on chain, before the json validation
+            ensure!(!CrmCompositionDataChangeProposal::<T>::contains_key(changeid), Error::<T>::ChangeIdDuplicated.with_weight(Self::early_exit_weight(1)));
             // check the json validity of the proposed CRM composition data
             let js=compositiondata.clone();
-            ensure!(json_check_validity(js),Error::<T>::InvalidJson);
+            Self::check_json(js)?;
             // check crmid field in json
             let jscm=compositiondata.clone();
             let crmidjs=json_get_value(jscm,"crmid".as_bytes().to_vec());
-            let crmid=vecu8_to_u32(crmidjs);
+            let crmid=vecu8_to_crmid::<T::CrmId>(crmidjs);
             // check the contract id (crmid field in json), IS on chain on both storage, main and composition data
-            ensure!(CrmCompositionData::contains_key(&crmid), Error::<T>::InvalidContractId);
-            ensure!(CrmData::contains_key(&crmid), Error::<T>::InvalidContractId);
-            // check the changeid is NOT on chain
-            ensure!(!CrmCompositionDataChangeProposal::contains_key(changeid), Error::<T>::ChangeIdDuplicated);
+            ensure!(CrmCompositionData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
             // get the quorum for composition data from main contractid
-            let crmdata=CrmData::get(&crmid).unwrap();
+            let crmdata=CrmData::<T>::get(&crmid).unwrap();
             let currentquorumj=json_get_value(crmdata,"compositionquorum".as_bytes().to_vec());
             let currentquorum=vecu8_to_u32(currentquorumj);
             ensure!(currentquorum >0 && currentquorum <=100, Error::<T>::InvalidCompositionQuorum);
             // check validity of composition data
             let compositionclone=compositiondata.clone();
             // check for a valid json
-            ensure!(json_check_validity(compositionclone),Error::<T>::InvalidJson);
+            Self::check_json(compositionclone)?;
             let mut x=0;
             let mut totpercentage:u32 = 0;
             // check validity of records for Composition Data
@@ -908,7 +1903,7 @@ decl_module! {
             ensure!(totpercentage == 100, Error::<T>::WrongTotalPercentageComposition);
 
             // store the proposal data in the queue.
-            CrmCompositionDataChangeProposal::insert(changeid, compositiondata);
+            CrmCompositionDataChangeProposal::<T>::insert(changeid, compositiondata);
             // store initial voting results with current quorum required to change the data
             let v= Voting {
                 changeid,
@@ -919,30 +1914,30 @@ decl_module! {
                 percvotesyes: 0,
                 percvotesno: 0,
             };
-            CrmCompositionDataChangeVotingResult::insert(changeid,v);
+            CrmCompositionDataChangeVotingResult::<T>::insert(changeid,v);
             // Emit an event
-            Self::deposit_event(RawEvent::CrmCompositionDataNewChangeProposal(sender,crmid,changeid));
-            Ok(())
+            Self::deposit_event(Event::CrmCompositionDataNewChangeProposal(sender,crmid,changeid));
+            Ok(().into())
         }
         /// Vote a change proposal for CRM composition data
-        #[weight = 10_000]
-        pub fn vote_proposal_crm_compositiondata(origin, changeid: u32, vote: bool) -> dispatch::DispatchResult {
+        #[pallet::weight(T::WeightInfo::vote_proposal_crm_compositiondata())]
+        pub fn vote_proposal_crm_compositiondata(origin: OriginFor<T>, changeid: u32, vote: bool) -> DispatchResultWithPostInfo {
             // Check that the extrinsic is signed and get the signer.
             let sender = ensure_signed(origin)?;
             // check changeid
             ensure!(changeid > 0, Error::<T>::ChangeIdTooShort); //check minimum length
             // check the changeid change proposal is on chain
-            ensure!(CrmCompositionDataChangeProposal::contains_key(changeid), Error::<T>::ChangeIdNotFound);
+            ensure!(CrmCompositionDataChangeProposal::<T>::contains_key(changeid), Error::<T>::ChangeIdNotFound);
             // check for double voting
             ensure!(!CrmCompositionDataChangeVoteCasted::<T>::contains_key(&sender,changeid), Error::<T>::VoteCastedAlready);
             // get crmid from the change proposal
-            let jsc=CrmCompositionDataChangeProposal::get(&changeid).unwrap();
+            let jsc=CrmCompositionDataChangeProposal::<T>::get(&changeid).unwrap();
             let crmidj=json_get_value(jsc,"crmid".as_bytes().to_vec());
-            let crmid=vecu8_to_u32(crmidj);
+            let crmid=vecu8_to_crmid::<T::CrmId>(crmidj);
             // check the contract id is on chain
-            ensure!(CrmCompositionData::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(CrmCompositionData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
             // check if the signer is one of the composition Accounts
-            let compositiondata=CrmCompositionData::get(crmid).unwrap_or_default();
+            let compositiondata=CrmCompositionData::<T>::get(crmid).unwrap_or_default();
             let mut x=0;
             let mut votepercentage=0;
             loop {
@@ -973,7 +1968,7 @@ decl_module! {
             // check if the signer has rights to vote >0
             ensure!(votepercentage > 0, Error::<T>::SignerHasNoRightsForVoting);
             // store the vote
-            let mut v:Voting=CrmCompositionDataChangeVotingResult::get(changeid).unwrap_or_default();
+            let mut v:VotingOf<T>=CrmCompositionDataChangeVotingResult::<T>::get(changeid).unwrap_or_default();
             let currentpervotesyes=v.percvotesyes;
             // update the voting structure
             if vote {
@@ -984,52 +1979,54 @@ decl_module! {
                 v.percvotesno+=votepercentage;
             }
             //update the storage with voting results
-            CrmCompositionDataChangeVotingResult::remove(changeid);
-            CrmCompositionDataChangeVotingResult::insert(changeid,v.clone());
+            CrmCompositionDataChangeVotingResult::<T>::remove(changeid);
+            CrmCompositionDataChangeVotingResult::<T>::insert(changeid,v.clone());
             // store the vote for the account id
             CrmCompositionDataChangeVoteCasted::<T>::insert(sender.clone(),changeid,vote);
             // Emit an event to alert the user of the vote received
-            Self::deposit_event(RawEvent::CrmCompositionDataChangeVote(sender.clone(),crmid,changeid));
+            Self::deposit_event(Event::CrmCompositionDataChangeVote(sender.clone(),crmid,changeid));
             // if quorum has been reached, we replace the current CRM data with the one voted from the majority
             if v.percvotesyes>=v.quorum && v.quorum>currentpervotesyes {
-                let crmdata=CrmCompositionDataChangeProposal::get(changeid).unwrap();
-                CrmCompositionData::remove(crmid);
-                CrmCompositionData::insert(crmid, crmdata);
+                let crmdata=CrmCompositionDataChangeProposal::<T>::get(changeid).unwrap();
+                CrmCompositionData::<T>::remove(crmid);
+                CrmCompositionData::<T>::insert(crmid, crmdata);
+                Self::bump_version(crmid);
+                Self::log_change(crmid, &sender, ChangeKind::GroupData);
                 // Emit an event to alert the user of the crm data change done
-                Self::deposit_event(RawEvent::CrmCompositionDataChanged(sender,crmid));
+                Self::deposit_event(Event::CrmCompositionDataChanged(sender,crmid));
             }
             // returns back with no errors
-            Ok(())
+            Ok(().into())
         }
         /// Submit a change proposal for CRM Other Contracts data that must be approved by voting from master members only of the other contracts.
-        #[weight = 50_000]
-        pub fn change_proposal_crm_othercontractsdata(origin, changeid: u32, othercontractsdata: Vec<u8>) -> dispatch::DispatchResult {
+        #[pallet::weight(T::WeightInfo::change_proposal_crm_othercontractsdata())]
+        pub fn change_proposal_crm_othercontractsdata(origin: OriginFor<T>, changeid: u32, othercontractsdata: Vec<u8>) -> DispatchResultWithPostInfo {
             // Check that the extrinsic is signed and get the signer.
             let sender = ensure_signed(origin)?;
             // check that at the least some data to change has been received and it's not too long
-            ensure!(!othercontractsdata.is_empty(), Error::<T>::MissingContractData);
-            ensure!(othercontractsdata.len()<1024, Error::<T>::CrmDataTooLong);
+            ensure!(!othercontractsdata.is_empty(), Error::<T>::MissingContractData.with_weight(EARLY_EXIT_WEIGHT));
+            ensure!(othercontractsdata.len()<1024, Error::<T>::CrmDataTooLong.with_weight(EARLY_EXIT_WEIGHT));
+            // check the changeid is NOT on chain, before the json validation
+            ensure!(!CrmOtherContractsDataChangeProposal::<T>::contains_key(changeid), Error::<T>::ChangeIdDuplicated.with_weight(Self::early_exit_weight(1)));
             // check the json validity of the proposed CRM composition data
             let js=othercontractsdata.clone();
-            ensure!(json_check_validity(js),Error::<T>::InvalidJson);
+            Self::check_json(js)?;
             // check crmid field in json
             let jscm=othercontractsdata.clone();
             let crmidjs=json_get_value(jscm,"crmid".as_bytes().to_vec());
-            let crmid=vecu8_to_u32(crmidjs);
+            let crmid=vecu8_to_crmid::<T::CrmId>(crmidjs);
             // check the contract id (crmid field in json), IS on chain on both storage, main and composition data
-            ensure!(CrmOtherContractsData::contains_key(&crmid), Error::<T>::InvalidContractId);
-            ensure!(CrmData::contains_key(&crmid), Error::<T>::InvalidContractId);
-            // check the changeid is NOT on chain
-            ensure!(!CrmOtherContractsDataChangeProposal::contains_key(changeid), Error::<T>::ChangeIdDuplicated);
+            ensure!(CrmOtherContractsData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
             // get the quorum for other contracts data from main contractid
-            let crmdata=CrmData::get(&crmid).unwrap();
+            let crmdata=CrmData::<T>::get(&crmid).unwrap();
             let currentquorumj=json_get_value(crmdata,"othercontractsquorum".as_bytes().to_vec());
             let currentquorum=vecu8_to_u32(currentquorumj);
             ensure!(currentquorum >0 && currentquorum <=100, Error::<T>::InvalidOtherContractsQuorum);
             // check validity of othercontracts data
             let othercontractsclone=othercontractsdata.clone();
             // check for a valid json
-            ensure!(json_check_validity(othercontractsclone),Error::<T>::InvalidJson);
+            Self::check_json(othercontractsclone)?;
             let mut x=0;
             let mut totpercentage= 0;
             // check validity of records for other contracts data
@@ -1041,10 +2038,10 @@ decl_module! {
                 // check for id
                 let id=json_get_value(jr.clone(),"id".as_bytes().to_vec());
                 ensure!(!id.is_empty(), Error::<T>::MissingOtherContractsId);
-                // convert id from vec to u32
-                let idvalue=vecu8_to_u32(id);
+                // convert id from vec to a crmid
+                let idvalue=vecu8_to_crmid::<T::CrmId>(id);
                 // check that the id is on chain
-                ensure!(CrmData::contains_key(&idvalue), Error::<T>::InvalidContractId);
+                ensure!(CrmData::<T>::contains_key(&idvalue), Error::<T>::InvalidContractId);
                 // check for percentage
                 let percentage=json_get_value(jr.clone(),"percentage".as_bytes().to_vec());
                 ensure!(!percentage.is_empty(), Error::<T>::MissingOtherContractsPercentage);
@@ -1059,7 +2056,7 @@ decl_module! {
             ensure!(totpercentage == 100, Error::<T>::WrongTotalPercentageOtherContracts);
 
             // store the proposal data in the queue.
-            CrmOtherContractsDataChangeProposal::insert(changeid, othercontractsdata);
+            CrmOtherContractsDataChangeProposal::<T>::insert(changeid, othercontractsdata);
             // store initial voting results with current quorum required to change the data
             let v= Voting {
                 changeid,
@@ -1070,30 +2067,30 @@ decl_module! {
                 percvotesyes: 0,
                 percvotesno: 0,
             };
-            CrmOtherContractsDataChangeVotingResult::insert(changeid,v);
+            CrmOtherContractsDataChangeVotingResult::<T>::insert(changeid,v);
             // Emit an event
-            Self::deposit_event(RawEvent::CrmOtherContractsDataNewChangeProposal(sender,crmid,changeid));
-            Ok(())
+            Self::deposit_event(Event::CrmOtherContractsDataNewChangeProposal(sender,crmid,changeid));
+            Ok(().into())
         }
         /// Vote a change proposal for CRM data
-        #[weight = 10_000]
-        pub fn vote_proposal_crm_othercontractsdata(origin, changeid: u32, vote: bool) -> dispatch::DispatchResult {
+        #[pallet::weight(T::WeightInfo::vote_proposal_crm_othercontractsdata())]
+        pub fn vote_proposal_crm_othercontractsdata(origin: OriginFor<T>, changeid: u32, vote: bool) -> DispatchResultWithPostInfo {
             // Check that the extrinsic is signed and get the signer.
             let sender = ensure_signed(origin)?;
             // check changeid
             ensure!(changeid > 0, Error::<T>::ChangeIdTooShort); //check minimum length
             // check the changeid change proposal is on chain
-            ensure!(CrmOtherContractsDataChangeProposal::contains_key(changeid), Error::<T>::ChangeIdNotFound);
+            ensure!(CrmOtherContractsDataChangeProposal::<T>::contains_key(changeid), Error::<T>::ChangeIdNotFound);
             // check for double voting
             ensure!(!CrmOtherContractsDataChangeVoteCasted::<T>::contains_key(&sender,changeid), Error::<T>::VoteCastedAlready);
             // get crmid from the change proposal
-            let jsc=CrmOtherContractsDataChangeProposal::get(&changeid).unwrap();
+            let jsc=CrmOtherContractsDataChangeProposal::<T>::get(&changeid).unwrap();
             let crmidj=json_get_value(jsc,"crmid".as_bytes().to_vec());
-            let crmid=vecu8_to_u32(crmidj);
+            let crmid=vecu8_to_crmid::<T::CrmId>(crmidj);
             // check the contract id is on chain
-            ensure!(CrmData::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
             // check if the signer is part of any "other contract"
-            let othercontractsdata=CrmOtherContractsData::get(crmid).unwrap_or_default();
+            let othercontractsdata=CrmOtherContractsData::<T>::get(crmid).unwrap_or_default();
             let mut votepercentage=0;
             if othercontractsdata.len()>10{
                 let mut x=0;
@@ -1104,8 +2101,8 @@ decl_module! {
                     }
                     let id=json_get_value(jr.clone(),"id".as_bytes().to_vec());
                     ensure!(!id.is_empty(), Error::<T>::InvalidContractIdVoting);
-                    let idvalue=vecu8_to_u32(id);
-                    ensure!(idvalue >0, Error::<T>::InvalidContractIdVotingNumeric);
+                    let idvalue=vecu8_to_crmid::<T::CrmId>(id);
+                    ensure!(!idvalue.is_zero(), Error::<T>::InvalidContractIdVotingNumeric);
                     // check for percentage
                     let percentage=json_get_value(jr.clone(),"percentage".as_bytes().to_vec());
                     ensure!(!percentage.is_empty(), Error::<T>::MissingOtherContractsPercentage);
@@ -1114,7 +2111,7 @@ decl_module! {
                     ensure!(percentagevalue>0, Error::<T>::MissingOtherContractsPercentage);
                     // check Master record of the other contract
                     let mut xx=0;
-                    let masterdata=CrmMasterData::get(idvalue).unwrap();
+                    let masterdata=CrmMasterData::<T>::get(idvalue).unwrap();
                     loop {
                         let jr=json_get_recordvalue(masterdata.clone(),xx);
                         if jr.is_empty() {
@@ -1145,7 +2142,7 @@ decl_module! {
             // check if the signer has rights to vote >0
             ensure!(votepercentage > 0, Error::<T>::SignerHasNoRightsForVoting);
             // store the vote
-            let mut v:Voting=CrmOtherContractsDataChangeVotingResult::get(changeid).unwrap_or_default();
+            let mut v:VotingOf<T>=CrmOtherContractsDataChangeVotingResult::<T>::get(changeid).unwrap_or_default();
             let currentpervotesyes=v.percvotesyes;
             // update the voting structure
             if vote {
@@ -1156,24 +2153,2314 @@ decl_module! {
                 v.percvotesno+=votepercentage;
             }
             //update the storage with voting results
-            CrmOtherContractsDataChangeVotingResult::remove(changeid);
-            CrmOtherContractsDataChangeVotingResult::insert(changeid,v.clone());
+            CrmOtherContractsDataChangeVotingResult::<T>::remove(changeid);
+            CrmOtherContractsDataChangeVotingResult::<T>::insert(changeid,v.clone());
             // store the vote for the account id
             CrmOtherContractsDataChangeVoteCasted::<T>::insert(sender.clone(),changeid,vote);
             // Emit an event to alert the user of the vote received
-            Self::deposit_event(RawEvent::CrmOtherContractsDataChangeVote(sender.clone(),crmid,changeid));
+            Self::deposit_event(Event::CrmOtherContractsDataChangeVote(sender.clone(),crmid,changeid));
             // if quorum has been reached, we replace the current CRM Other Contracts data with the one voted from the majority
             if v.percvotesyes>=v.quorum && v.quorum>currentpervotesyes {
-                let crmdata=CrmOtherContractsDataChangeProposal::get(changeid).unwrap();
-                CrmOtherContractsData::remove(crmid);
-                CrmOtherContractsData::insert(crmid, crmdata);
+                let crmdata=CrmOtherContractsDataChangeProposal::<T>::get(changeid).unwrap();
+                CrmOtherContractsData::<T>::remove(crmid);
+                CrmOtherContractsData::<T>::insert(crmid, crmdata);
+                Self::bump_version(crmid);
+                Self::log_change(crmid, &sender, ChangeKind::GroupData);
                 // Emit an event to alert the user of the crm data change done
-                Self::deposit_event(RawEvent::CrmOtherContractsDataChanged(sender,crmid));
+                Self::deposit_event(Event::CrmOtherContractsDataChanged(sender,crmid));
             }
             // returns back with no errors
-            Ok(())
+            Ok(().into())
         }
-    }
+        /// Append a private hash to the "ipfshashprivate" field of a contract, it can be submitted by the owner of the contract or an editor.
+        /// When "expectedversion" is set, the call fails if the version of the contract is different.
+        #[pallet::weight(T::WeightInfo::add_private_hash(T::MaxCrmDataLength::get()))]
+        #[transactional]
+        pub fn add_private_hash(origin: OriginFor<T>, crmid: T::CrmId, hash: Vec<u8>, expectedversion: Option<u32>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            Self::check_version(crmid, expectedversion)?;
+            // check the private hash
+            ensure!(Self::ipfshash_in_bounds(&hash), Error::<T>::InvalidIpfsHashPrivate); //check the length of the Ipfs Hash Private
+            ensure!(hash.iter().all(|b| b.is_ascii_alphanumeric()), Error::<T>::InvalidIpfsHashPrivate);
+            // check the contract is on chain and the signer is the owner
+            let crmdata = CrmData::<T>::get(&crmid).ok_or(Error::<T>::InvalidContractId)?;
+            ensure!(Self::is_owner_or_editor(crmid, &sender), Error::<T>::SignerIsNotOwner);
+            Self::ensure_unlocked(crmid)?;
+            // append the hash to the private hashes, a single hash is converted to an array
+            let newcrmdata = json_array_append(crmdata, "ipfshashprivate".as_bytes().to_vec(), hash);
+            ensure!(!newcrmdata.is_empty(), Error::<T>::InvalidJson);
+            ensure!(newcrmdata.len() as u32 <= T::MaxCrmDataLength::get(), Error::<T>::CrmDataTooLong);
+            // check the number of private hashes
+            let privatehashes = json_get_array(newcrmdata.clone(), "ipfshashprivate".as_bytes().to_vec());
+            ensure!(privatehashes.len() as u32 <= T::MaxPrivateHashes::get(), Error::<T>::TooManyPrivateHashes);
+            // check the private hashes are distinct and different from the public hash
+            Self::check_private_hashes(&newcrmdata, &privatehashes, &Self::legacy_hashes(crmid))?;
+            // store the updated crm data
+            Self::store_crmdata(crmid, newcrmdata)?;
+            let version = Self::bump_version(crmid);
+            // Emit an event to alert the user of the crm data change done
+            Self::log_change(crmid, &sender, ChangeKind::IpfsHash);
+            Self::deposit_crm_event(crmid, Event::CrmDataChanged(sender, crmid, version, ChangeKind::IpfsHash));
+            Ok(().into())
+        }
+        /// Set or update the crowdfunding campaign of a contract ("crowdfounders" field), for the campaigns created after the contract.
+        /// It can be submitted by the owner of the contract only and the contract must have a "crodwfundingshares" > 0.
+        #[pallet::weight(T::WeightInfo::set_crowdfounders(T::MaxCrmDataLength::get()))]
+        #[transactional]
+        pub fn set_crowdfounders(origin: OriginFor<T>, crmid: T::CrmId, campaign: u32) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            // check the contract is on chain and the signer is the owner
+            let crmdata = CrmData::<T>::get(&crmid).ok_or(Error::<T>::InvalidContractId)?;
+            ensure!(Self::is_owner(crmid, &sender), Error::<T>::SignerIsNotOwner);
+            ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+            Self::ensure_unlocked(crmid)?;
+            // the contract must have a crowdfunding share
+            let share = vecu8_to_u32(json_get_value(crmdata.clone(), "crodwfundingshares".as_bytes().to_vec()));
+            ensure!(share > 0, Error::<T>::NoCrowdFundingShare);
+            // set the campaign id as string, like in the crm data submitted
+            let mut value = Vec::new();
+            value.push(b'"');
+            value.extend_from_slice(&u32_to_vecu8(campaign));
+            value.push(b'"');
+            let newcrmdata = json_set_value(crmdata, "crowdfounders".as_bytes().to_vec(), value);
+            ensure!(!newcrmdata.is_empty(), Error::<T>::InvalidJson);
+            ensure!(newcrmdata.len() as u32 <= T::MaxCrmDataLength::get(), Error::<T>::CrmDataTooLong);
+            // check the campaign is on chain, owned by the signer and not linked to another contract
+            Self::check_crowdfunding_campaign(&newcrmdata, Some(sender.clone()), crmid)?;
+            // link the new campaign
+            Self::unlink_crowdfunding_campaign(crmid);
+            Self::link_crowdfunding_campaign(crmid, &newcrmdata);
+            Self::store_crmdata(crmid, newcrmdata)?;
+            let version = Self::bump_version(crmid);
+            // Emit an event to alert the user of the crm data change done
+            Self::log_change(crmid, &sender, ChangeKind::Shares);
+            Self::deposit_crm_event(crmid, Event::CrmDataChanged(sender, crmid, version, ChangeKind::Shares));
+            Ok(().into())
+        }
+        /// Remove a contract and the crowdfunding certificates of the campaign linked, it can be submitted by governance only.
+        #[pallet::weight(50_000)]
+        pub fn force_remove_contract(origin: OriginFor<T>, crmid: T::CrmId) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            ensure!(
+                CrmData::<T>::contains_key(&crmid) || CrmCommitments::<T>::contains_key(&crmid),
+                Error::<T>::InvalidContractId.with_weight(Self::early_exit_weight(2))
+            );
+            Self::remove_contract(crmid);
+            Self::deposit_event(Event::CrmForceRemoved(crmid));
+            Ok(().into())
+        }
+        /// Transfer the ownership of a contract to another account, it can be submitted by the owner of the contract only.
+        /// The new owner must be under the maximum number of contracts per account. A co-owned contract is transferred
+        /// with the approval of the owners (`approve_owner_action`).
+        #[pallet::weight(50_000)]
+        pub fn transfer_contract(origin: OriginFor<T>, crmid: T::CrmId, to: T::AccountId) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(Self::is_owner(crmid, &sender), Error::<T>::SignerIsNotOwner);
+            ensure!(!CrmOwnerSets::<T>::contains_key(&crmid), Error::<T>::ApprovalRequired);
+            Self::transfer_ownership(crmid, sender, to)?;
+            Ok(().into())
+        }
+        /// Revoke a contract, it can be submitted by the owner of the contract only. The revoked contracts are removed by `prune_revoked`.
+        /// A co-owned contract is revoked with the approval of the owners (`approve_owner_action`).
+        #[pallet::weight(50_000)]
+        pub fn revoke_contract(origin: OriginFor<T>, crmid: T::CrmId) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId.with_weight(Self::early_exit_weight(1)));
+            ensure!(Self::is_owner(crmid, &sender), Error::<T>::SignerIsNotOwner.with_weight(Self::early_exit_weight(3)));
+            ensure!(!CrmOwnerSets::<T>::contains_key(&crmid), Error::<T>::ApprovalRequired.with_weight(Self::early_exit_weight(3)));
+            Self::revoke(crmid, sender)?;
+            Ok(().into())
+        }
+        /// Remove up to `limit` revoked contracts from the storage, it can be submitted by governance only.
+        #[pallet::weight(50_000 + 10_000 * (*limit as u64))]
+        pub fn prune_revoked(origin: OriginFor<T>, limit: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            let revoked: Vec<T::CrmId> = CrmRevoked::<T>::iter()
+                .map(|(crmid, _)| crmid)
+                .take(limit as usize)
+                .collect();
+            for crmid in revoked.iter() {
+                Self::remove_contract(*crmid);
+                CrmRevoked::<T>::remove(crmid);
+            }
+            Self::deposit_event(Event::CrmRevokedPruned(revoked.len() as u32));
+            Ok(().into())
+        }
+        /// Transfer part or all the crowdfunding share of a contract (basis points of the certificate) to another account.
+        #[pallet::weight(50_000)]
+        pub fn transfer_crowdfunding_share(origin: OriginFor<T>, crmid: T::CrmId, to: T::AccountId, basis_points: u32) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(basis_points > 0, Error::<T>::InvalidBasisPoints);
+            ensure!(sender != to, Error::<T>::ShareTransferToSelf);
+            // check the campaign linked to the contract has been finalized
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            let campaignid = Self::crm_campaign(crmid).ok_or(Error::<T>::MissingCrowdfundingCampaign)?;
+            let c = Campaigns::<T>::get(&campaignid).ok_or(Error::<T>::CampaignNotFound)?;
+            ensure!(c.status == CampaignStatus::Succeeded, Error::<T>::CampaignNotSucceeded);
+            // check the certificate of the signer
+            let mut from = CrowdfundingCertificates::<T>::get(campaignid, &sender).ok_or(Error::<T>::InsufficientCrowdfundingShare)?;
+            ensure!(from.basis_points >= basis_points, Error::<T>::InsufficientCrowdfundingShare);
+            // the contributed amount follows the basis points transferred
+            let contributed = Perbill::from_rational_approximation(basis_points, from.basis_points) * from.contributed;
+            from.basis_points -= basis_points;
+            from.contributed = from.contributed.saturating_sub(contributed);
+            // merge with the certificate of the recipient
+            let now = <frame_system::Module<T>>::block_number();
+            let mut dest = CrowdfundingCertificates::<T>::get(campaignid, &to).unwrap_or(Certificate {
+                basis_points: 0,
+                contributed: Zero::zero(),
+                issued_at: now,
+            });
+            dest.basis_points = dest.basis_points.saturating_add(basis_points);
+            dest.contributed = dest.contributed.saturating_add(contributed);
+            // update the storage, a certificate fully transferred is removed
+            if from.basis_points == 0 {
+                CrowdfundingCertificates::<T>::remove(campaignid, &sender);
+            } else {
+                CrowdfundingCertificates::<T>::insert(campaignid, &sender, from);
+            }
+            CrowdfundingCertificates::<T>::insert(campaignid, &to, dest);
+            Self::deposit_event(Event::CrowdfundingShareTransferred(sender, to, crmid, basis_points));
+            Ok(().into())
+        }
+        /// Create a new crowdfunding campaign accepting contributions up to the hard cap until the deadline block.
+        /// The campaign is successful when at the deadline the funds raised are >= soft cap, or as soon as the hard cap is reached.
+        #[pallet::weight(50_000)]
+        pub fn create_campaign(origin: OriginFor<T>, campaignid: u32, soft_cap: BalanceOf<T>, hard_cap: BalanceOf<T>, deadline: T::BlockNumber) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            // check the campaign id is valid and free
+            ensure!(campaignid > 0, Error::<T>::InvalidValue);
+            ensure!(!Campaigns::<T>::contains_key(&campaignid), Error::<T>::DuplicatedCampaignId);
+            // check the caps, both > 0 and soft cap <= hard cap
+            ensure!(!soft_cap.is_zero() && !hard_cap.is_zero(), Error::<T>::InvalidCampaignCap);
+            ensure!(soft_cap <= hard_cap, Error::<T>::InvalidCampaignCap);
+            // check the deadline is in the future
+            ensure!(deadline > <frame_system::Module<T>>::block_number(), Error::<T>::InvalidCampaignDeadline);
+            // store the campaign
+            let c = Campaign {
+                owner: sender.clone(),
+                soft_cap,
+                hard_cap,
+                raised: Zero::zero(),
+                deadline,
+                status: CampaignStatus::Active,
+                funded: false,
+            };
+            Campaigns::<T>::insert(campaignid, c);
+            // Emit an event
+            Self::deposit_event(Event::CampaignCreated(sender, campaignid));
+            // schedule the closing at the deadline, when it fails (for example with `NoScheduler`) the campaign is closed manually
+            if T::Scheduler::schedule_named(
+                Self::campaign_task_id(campaignid),
+                DispatchTime::At(deadline),
+                None,
+                CAMPAIGN_CLOSE_PRIORITY,
+                frame_system::RawOrigin::Root.into(),
+                Call::<T>::close_campaign(campaignid).into(),
+            ).is_err() {
+                Self::deposit_event(Event::CampaignCloseNotScheduled(campaignid, deadline));
+            }
+            Ok(().into())
+        }
+        /// Contribute to an active crowdfunding campaign, the contribution is rejected when it would exceed the hard cap.
+        #[pallet::weight(50_000)]
+        #[transactional]
+        pub fn contribute(origin: OriginFor<T>, campaignid: u32, amount: BalanceOf<T>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::InvalidContribution);
+            // check the campaign is on chain and still accepting contributions
+            let mut c = Campaigns::<T>::get(&campaignid).ok_or(Error::<T>::CampaignNotFound)?;
+            ensure!(c.status == CampaignStatus::Active, Error::<T>::CampaignClosed);
+            ensure!(<frame_system::Module<T>>::block_number() < c.deadline, Error::<T>::CampaignExpired);
+            // check the hard cap
+            let raised = c.raised.checked_add(&amount).ok_or(Error::<T>::HardCapExceeded)?;
+            ensure!(raised <= c.hard_cap, Error::<T>::HardCapExceeded);
+            // move the contribution to the escrow account
+            T::Currency::transfer(&sender, &Self::campaign_account_id(campaignid), amount, ExistenceRequirement::AllowDeath)?;
+            CampaignContributions::<T>::mutate(campaignid, &sender, |v| *v = v.saturating_add(amount));
+            let softcapreached = c.raised < c.soft_cap && raised >= c.soft_cap;
+            c.raised = raised;
+            Self::deposit_event(Event::CampaignContribution(sender, campaignid, amount));
+            if softcapreached {
+                Self::deposit_event(Event::CampaignSoftCapReached(campaignid, raised));
+            }
+            // the funded milestone is emitted once, guarded by the flag in the campaign
+            if !c.funded && raised >= c.soft_cap {
+                c.funded = true;
+                Self::deposit_event(Event::CampaignFunded(c.owner.clone(), campaignid, raised));
+            }
+            // the campaign is closed as soon as the hard cap is hit
+            if raised == c.hard_cap {
+                Self::deposit_event(Event::CampaignHardCapReached(campaignid, raised));
+                Self::finalize_campaign(campaignid, &mut c)?;
+                // the closing at the deadline is not needed anymore, when it's left scheduled it fails with CampaignClosed
+                if T::Scheduler::cancel_named(Self::campaign_task_id(campaignid)).is_err() {
+                    debug::info!("Campaign {}: closing at the deadline not cancelled, not scheduled", campaignid);
+                }
+            }
+            Campaigns::<T>::insert(campaignid, c);
+            Ok(().into())
+        }
+        /// Close a crowdfunding campaign once the deadline is reached, it can be called from any account
+        /// and it's dispatched by the scheduler at the deadline block when available.
+        #[pallet::weight(50_000)]
+        pub fn close_campaign(origin: OriginFor<T>, campaignid: u32) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed or dispatched by the scheduler
+            ensure_root(origin.clone()).or_else(|_| ensure_signed(origin).map(|_| ()))?;
+            let mut c = Campaigns::<T>::get(&campaignid).ok_or(Error::<T>::CampaignNotFound)?;
+            ensure!(c.status == CampaignStatus::Active, Error::<T>::CampaignClosed);
+            ensure!(<frame_system::Module<T>>::block_number() >= c.deadline, Error::<T>::CampaignStillActive);
+            Self::finalize_campaign(campaignid, &mut c)?;
+            Campaigns::<T>::insert(campaignid, c);
+            Ok(().into())
+        }
+        /// Refund the contribution of the signer to a failed crowdfunding campaign.
+        #[pallet::weight(50_000)]
+        pub fn refund_contribution(origin: OriginFor<T>, campaignid: u32) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            let c = Campaigns::<T>::get(&campaignid).ok_or(Error::<T>::CampaignNotFound)?;
+            ensure!(c.status == CampaignStatus::Failed, Error::<T>::CampaignNotFailed);
+            let amount = CampaignContributions::<T>::get(campaignid, &sender);
+            ensure!(!amount.is_zero(), Error::<T>::NothingToRefund);
+            // move back the contribution from the escrow account
+            T::Currency::transfer(&Self::campaign_account_id(campaignid), &sender, amount, ExistenceRequirement::AllowDeath)?;
+            CampaignContributions::<T>::remove(campaignid, &sender);
+            Self::deposit_event(Event::CampaignContributionRefunded(sender, campaignid, amount));
+            Ok(().into())
+        }
+        /// Pay the royalties of a contract, the amount is split by the shares of master, composition, other contracts
+        /// and crowdfunding and credited to the members of each group, the funds are held in the escrow account of the contract.
+        /// The royalties are paid in the native currency or, when given, in an asset with its own ledger.
+        #[pallet::weight(50_000)]
+        #[transactional]
+        pub fn pay_royalties(origin: OriginFor<T>, crmid: T::CrmId, amount: BalanceOf<T>, asset: Option<AssetIdOf<T>>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::InvalidRoyaltyAmount.with_weight(EARLY_EXIT_WEIGHT));
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId.with_weight(Self::early_exit_weight(1)));
+            ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked.with_weight(Self::early_exit_weight(2)));
+            Self::do_pay_royalties(sender, crmid, amount, asset, ExistenceRequirement::KeepAlive)?;
+            Ok(().into())
+        }
+        /// Report the plays of a contract, the royalties (plays x "rateperplay") are paid by the reporter and credited
+        /// to the members of the contract as in pay_royalties. It can be submitted by the reporters authorized only.
+        #[pallet::weight(50_000)]
+        #[transactional]
+        pub fn report_plays(origin: OriginFor<T>, crmid: T::CrmId, plays: u64) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(Reporters::<T>::get(&sender), Error::<T>::NotAuthorizedReporter.with_weight(Self::early_exit_weight(1)));
+            ensure!(plays > 0, Error::<T>::InvalidPlays.with_weight(Self::early_exit_weight(1)));
+            let crmdata = CrmData::<T>::get(&crmid).ok_or(Error::<T>::InvalidContractId.with_weight(Self::early_exit_weight(2)))?;
+            // check the allowlist of the contract, if any
+            ensure!(
+                ContractReporters::<T>::get(crmid, &sender) || ContractReporters::<T>::iter_prefix(crmid).next().is_none(),
+                Error::<T>::NotAuthorizedReporter
+            );
+            ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+            let rate = vecu8_to_u32(json_get_value(crmdata, "rateperplay".as_bytes().to_vec()));
+            ensure!(rate > 0, Error::<T>::MissingRatePerPlay);
+            // plays x rate fits in u128, the conversion to the balance type fails on overflow
+            let amount: BalanceOf<T> = (plays as u128)
+                .checked_mul(rate as u128)
+                .and_then(|a| a.try_into().ok())
+                .ok_or(Error::<T>::RoyaltyOverflow)?;
+            Self::do_pay_royalties(sender.clone(), crmid, amount, None, ExistenceRequirement::KeepAlive)?;
+            // update the play counter, saturating at u64::MAX
+            let cumulative = PlayCounts::<T>::mutate(crmid, |n| {
+                *n = n.saturating_add(plays);
+                *n
+            });
+            // plays of the current period of the subscription pool
+            let period = CurrentPeriod::<T>::get();
+            PeriodPlays::<T>::mutate(period, crmid, |n| *n = n.saturating_add(plays));
+            PeriodTotalPlays::<T>::mutate(period, |n| *n = n.saturating_add(plays));
+            Self::deposit_event(Event::PlaysReported(sender, crmid, plays, cumulative));
+            Ok(().into())
+        }
+        /// Authorize an account to report the plays of the contracts, it can be submitted by the reporters admin only.
+        #[pallet::weight(10_000)]
+        pub fn add_reporter(origin: OriginFor<T>, reporter: T::AccountId) -> DispatchResultWithPostInfo {
+            T::ReporterAdminOrigin::ensure_origin(origin)?;
+            Reporters::<T>::insert(&reporter, true);
+            Self::deposit_event(Event::ReporterAdded(reporter));
+            Ok(().into())
+        }
+        /// Remove the authorization to report the plays, it can be submitted by the reporters admin only.
+        #[pallet::weight(10_000)]
+        pub fn remove_reporter(origin: OriginFor<T>, reporter: T::AccountId) -> DispatchResultWithPostInfo {
+            T::ReporterAdminOrigin::ensure_origin(origin)?;
+            ensure!(Reporters::<T>::contains_key(&reporter), Error::<T>::NotAuthorizedReporter);
+            Reporters::<T>::remove(&reporter);
+            Self::deposit_event(Event::ReporterRemoved(reporter));
+            Ok(().into())
+        }
+        /// Add an editor to a contract, the editors can update the contract as the owner. It can be submitted by the owner only.
+        #[pallet::weight(10_000)]
+        pub fn add_editor(origin: OriginFor<T>, crmid: T::CrmId, editor: T::AccountId) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(Self::is_owner(crmid, &sender), Error::<T>::SignerIsNotOwner);
+            CrmEditors::<T>::try_mutate(crmid, |editors| -> dispatch::DispatchResult {
+                ensure!(!editors.contains(&editor), Error::<T>::EditorAlreadyAdded);
+                ensure!(editors.len() < MAX_EDITORS, Error::<T>::TooManyEditors);
+                editors.push(editor.clone());
+                Ok(())
+            })?;
+            Self::deposit_event(Event::EditorAdded(crmid, editor));
+            Self::log_change(crmid, &sender, ChangeKind::Editors);
+            Self::deposit_crm_event(crmid, Event::CrmDataChanged(sender, crmid, CrmVersion::<T>::get(crmid), ChangeKind::Editors));
+            Ok(().into())
+        }
+        /// Remove an editor from a contract, it can be submitted by the owner only.
+        #[pallet::weight(10_000)]
+        pub fn remove_editor(origin: OriginFor<T>, crmid: T::CrmId, editor: T::AccountId) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(Self::is_owner(crmid, &sender), Error::<T>::SignerIsNotOwner);
+            CrmEditors::<T>::try_mutate_exists(crmid, |editors| -> dispatch::DispatchResult {
+                let list = editors.as_mut().ok_or(Error::<T>::EditorNotFound)?;
+                let p = list.iter().position(|e| *e == editor).ok_or(Error::<T>::EditorNotFound)?;
+                list.remove(p);
+                if list.is_empty() {
+                    *editors = None;
+                }
+                Ok(())
+            })?;
+            Self::deposit_event(Event::EditorRemoved(crmid, editor));
+            Self::log_change(crmid, &sender, ChangeKind::Editors);
+            Self::deposit_crm_event(crmid, Event::CrmDataChanged(sender, crmid, CrmVersion::<T>::get(crmid), ChangeKind::Editors));
+            Ok(().into())
+        }
+        /// Allow or not a reporter for a contract, it can be submitted by the owner of the contract only.
+        /// When at the least one reporter is allowed, the other reporters cannot report the plays of the contract.
+        #[pallet::weight(10_000)]
+        pub fn set_contract_reporter(origin: OriginFor<T>, crmid: T::CrmId, reporter: T::AccountId, allowed: bool) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(Self::is_owner(crmid, &sender), Error::<T>::SignerIsNotOwner);
+            if allowed {
+                ContractReporters::<T>::insert(crmid, &reporter, true);
+            } else {
+                ContractReporters::<T>::remove(crmid, &reporter);
+            }
+            Self::deposit_event(Event::ContractReporterSet(crmid, reporter, allowed));
+            Ok(().into())
+        }
+        /// Claim the royalties of a contract credited to the signer, all the balance or the amount given,
+        /// from the ledger of the native currency or of the asset given.
+        #[pallet::weight(50_000)]
+        #[transactional]
+        pub fn claim_royalties(origin: OriginFor<T>, crmid: T::CrmId, amount: Option<BalanceOf<T>>, asset: Option<AssetIdOf<T>>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            let balance = Self::royalty_balance(crmid, asset, &sender);
+            ensure!(!balance.is_zero(), Error::<T>::NothingToClaim.with_weight(Self::early_exit_weight(1)));
+            let amount = amount.unwrap_or(balance);
+            ensure!(!amount.is_zero(), Error::<T>::NothingToClaim.with_weight(Self::early_exit_weight(1)));
+            ensure!(amount <= balance, Error::<T>::InsufficientRoyaltyBalance.with_weight(Self::early_exit_weight(1)));
+            // update the ledger, the transfer failure rolls it back
+            Self::set_royalty_balance(crmid, asset, &sender, balance.saturating_sub(amount));
+            // the part withheld is moved to the withholding account of the contract
+            let withheld = match Self::withholding(crmid, amount)? {
+                Some((account, withheld)) => {
+                    Self::transfer_from_escrow(crmid, asset, &account, withheld)?;
+                    Self::deposit_event(Event::RoyaltiesWithheld(sender.clone(), crmid, account, withheld));
+                    withheld
+                }
+                None => Zero::zero(),
+            };
+            Self::transfer_from_escrow(crmid, asset, &sender, amount.saturating_sub(withheld))?;
+            match asset {
+                None => Self::deposit_event(Event::RoyaltiesClaimed(sender, crmid, amount)),
+                Some(asset) => Self::deposit_event(Event::AssetRoyaltiesClaimed(sender, crmid, asset, amount)),
+            }
+            Ok(().into())
+        }
+        /// Set the account receiving the part of the claims withheld by the "withholdingpercent" of a contract (for example
+        /// a tax agent), it can be submitted by the owner of the contract only. A co-owned contract sets it with the
+        /// approval of the owners (`approve_owner_action`).
+        #[pallet::weight(10_000)]
+        pub fn set_withholding_account(origin: OriginFor<T>, crmid: T::CrmId, account: Option<T::AccountId>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(Self::is_owner(crmid, &sender), Error::<T>::SignerIsNotOwner);
+            ensure!(!CrmOwnerSets::<T>::contains_key(&crmid), Error::<T>::ApprovalRequired);
+            Self::store_withholding_account(crmid, account);
+            Ok(().into())
+        }
+        /// Fund the subscription pool of the current period, the pool is split between the contracts by the plays
+        /// reported in the period.
+        #[pallet::weight(10_000)]
+        pub fn fund_pool(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(!amount.is_zero(), Error::<T>::InvalidRoyaltyAmount);
+            T::Currency::transfer(&sender, &Self::pool_account_id(), amount, ExistenceRequirement::KeepAlive)?;
+            PoolBalance::<T>::mutate(|b| *b = b.saturating_add(amount));
+            Self::deposit_event(Event::PoolFunded(sender, amount));
+            Ok(().into())
+        }
+        /// Distribute the subscription pool of a period, it can be submitted by the reporters admin only.
+        /// The first call closes the current period, the following plays are reported in the next one. Each call pays
+        /// up to MaxPoolPayouts contracts by their plays as in pay_royalties, the distribution is resumed by calling
+        /// it again with the same period. The amount not paid (rounding, contracts removed) is carried to the current period.
+        #[pallet::weight(10_000 + 50_000 * T::MaxPoolPayouts::get() as u64)]
+        pub fn distribute_pool(origin: OriginFor<T>, period: u32) -> DispatchResultWithPostInfo {
+            T::ReporterAdminOrigin::ensure_origin(origin)?;
+            let mut distribution = match PoolDistributions::<T>::get(period) {
+                Some(d) => d,
+                None => {
+                    ensure!(period == CurrentPeriod::<T>::get(), Error::<T>::InvalidPeriod);
+                    CurrentPeriod::<T>::put(period.checked_add(1).ok_or(Error::<T>::InvalidPeriod)?);
+                    PoolDistribution {
+                        amount: PoolBalance::<T>::take(),
+                        total_plays: PeriodTotalPlays::<T>::take(period),
+                        paid: Zero::zero(),
+                    }
+                }
+            };
+            let batch: Vec<(T::CrmId, u64)> = PeriodPlays::<T>::iter_prefix(period).take(T::MaxPoolPayouts::get() as usize).collect();
+            for (crmid, plays) in batch {
+                PeriodPlays::<T>::remove(period, crmid);
+                let slice: BalanceOf<T> = multiply_by_rational(
+                    distribution.amount.unique_saturated_into(),
+                    plays as u128,
+                    distribution.total_plays as u128,
+                )
+                .unwrap_or(0)
+                .unique_saturated_into();
+                // the slices of the contracts revoked or that cannot be paid are carried over
+                if slice.is_zero() || CrmRevoked::<T>::contains_key(&crmid) {
+                    continue;
+                }
+                let paid = with_transaction(|| {
+                    let r = Self::do_pay_royalties(
+                        Self::pool_account_id(),
+                        crmid,
+                        slice,
+                        None,
+                        ExistenceRequirement::AllowDeath,
+                    );
+                    if r.is_ok() {
+                        TransactionOutcome::Commit(r)
+                    } else {
+                        TransactionOutcome::Rollback(r)
+                    }
+                });
+                if paid.is_ok() {
+                    distribution.paid = distribution.paid.saturating_add(slice);
+                    Self::deposit_event(Event::PoolSlicePaid(period, crmid, slice));
+                }
+            }
+            if PeriodPlays::<T>::iter_prefix(period).next().is_none() {
+                let left = distribution.amount.saturating_sub(distribution.paid);
+                PoolBalance::<T>::mutate(|b| *b = b.saturating_add(left));
+                PoolDistributions::<T>::remove(period);
+                Self::deposit_event(Event::PoolDistributed(period, distribution.paid, left));
+            } else {
+                PoolDistributions::<T>::insert(period, distribution);
+            }
+            Ok(().into())
+        }
+        /// Acquire the mechanical license of a contract at the "mechanicallicensefee" of the crm data, the fee is credited
+        /// to the members of the contract as in pay_royalties. The contracts without the fee do not grant mechanical licenses.
+        #[pallet::weight(50_000)]
+        #[transactional]
+        pub fn acquire_mechanical_license(origin: OriginFor<T>, crmid: T::CrmId) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            let crmdata = CrmData::<T>::get(&crmid).ok_or(Error::<T>::InvalidContractId)?;
+            ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+            let fee = vecu8_to_u32(json_get_value(crmdata, "mechanicallicensefee".as_bytes().to_vec()));
+            ensure!(fee > 0, Error::<T>::MechanicalLicenseNotAvailable);
+            ensure!(
+                !Licenses::<T>::get(crmid, &sender).iter().any(|l| l.kind == LicenseKind::Mechanical),
+                Error::<T>::LicenseAlreadyGranted
+            );
+            let fee: BalanceOf<T> = fee.into();
+            Self::do_pay_royalties(sender.clone(), crmid, fee, None, ExistenceRequirement::KeepAlive)?;
+            Self::add_license(crmid, &sender, LicenseKind::Mechanical, Vec::new(), None)?;
+            Self::deposit_event(Event::MechanicalLicenseGranted(sender, crmid, fee));
+            Ok(().into())
+        }
+        /// Request a sync or custom license of a contract on the terms given (for example the ipfs hash of the terms),
+        /// the offer is held in the escrow account of the contract until the owner grants or rejects the request.
+        #[pallet::weight(50_000)]
+        pub fn request_license(origin: OriginFor<T>, crmid: T::CrmId, terms_hash: Vec<u8>, offer: BalanceOf<T>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+            ensure!(!terms_hash.is_empty() && terms_hash.len() <= MAX_TERMS_HASH_LENGTH, Error::<T>::InvalidTermsHash);
+            ensure!(!offer.is_zero(), Error::<T>::InvalidLicenseOffer);
+            let requestid = NextLicenseRequestId::<T>::get();
+            let next = requestid.checked_add(1).ok_or(Error::<T>::LicenseRequestNotFound)?;
+            T::Currency::transfer(&sender, &Self::contract_account_id(crmid), offer, ExistenceRequirement::KeepAlive)?;
+            NextLicenseRequestId::<T>::put(next);
+            LicenseRequests::<T>::insert(requestid, LicenseRequest { licensee: sender.clone(), crmid, terms_hash, offer });
+            Self::deposit_event(Event::LicenseRequested(sender, requestid, crmid, offer));
+            Ok(().into())
+        }
+        /// Grant a license request, it can be submitted by the owner of the contract only. The offer held in the escrow
+        /// account is credited to the members of the contract as in pay_royalties. The license expires at the block given, if any.
+        /// A co-owned contract grants it with the approval of the owners (`approve_owner_action`).
+        #[pallet::weight(50_000)]
+        #[transactional]
+        pub fn grant_license(origin: OriginFor<T>, requestid: u32, expires_at: Option<T::BlockNumber>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            let request = LicenseRequests::<T>::get(requestid).ok_or(Error::<T>::LicenseRequestNotFound)?;
+            ensure!(Self::is_owner(request.crmid, &sender), Error::<T>::SignerIsNotOwner);
+            ensure!(!CrmOwnerSets::<T>::contains_key(&request.crmid), Error::<T>::ApprovalRequired);
+            Self::do_grant_license(request.crmid, requestid, expires_at)?;
+            Ok(().into())
+        }
+        /// Reject a license request refunding the offer, it can be submitted by the owner of the contract or by the licensee
+        /// withdrawing the request.
+        #[pallet::weight(50_000)]
+        #[transactional]
+        pub fn reject_license(origin: OriginFor<T>, requestid: u32) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            let request = LicenseRequests::<T>::get(requestid).ok_or(Error::<T>::LicenseRequestNotFound)?;
+            ensure!(
+                sender == request.licensee || Self::is_owner(request.crmid, &sender),
+                Error::<T>::SignerIsNotOwner
+            );
+            LicenseRequests::<T>::remove(requestid);
+            T::Currency::transfer(
+                &Self::contract_account_id(request.crmid),
+                &request.licensee,
+                request.offer,
+                ExistenceRequirement::AllowDeath,
+            )?;
+            Self::deposit_event(Event::LicenseRejected(request.licensee, requestid, request.crmid, request.offer));
+            Ok(().into())
+        }
+        /// Revoke a license for breach, it can be submitted by the owner of the contract only.
+        /// The "reasonhash" is the hash of the document with the reason (for example the ipfs hash).
+        #[pallet::weight(10_000)]
+        pub fn revoke_license(origin: OriginFor<T>, licenseid: u32, reasonhash: Vec<u8>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(!reasonhash.is_empty() && reasonhash.len() <= MAX_TERMS_HASH_LENGTH, Error::<T>::InvalidReasonHash);
+            let (crmid, licensee) = LicenseIndex::<T>::get(licenseid).ok_or(Error::<T>::LicenseNotFound)?;
+            ensure!(Self::is_owner(crmid, &sender), Error::<T>::SignerIsNotOwner);
+            let now = <frame_system::Module<T>>::block_number();
+            Licenses::<T>::try_mutate(crmid, &licensee, |licenses| -> dispatch::DispatchResult {
+                let license = licenses.iter_mut().find(|l| l.id == licenseid).ok_or(Error::<T>::LicenseNotFound)?;
+                ensure!(license.revoked_at.is_none(), Error::<T>::LicenseAlreadyRevoked);
+                ensure!(license.expires_at.map_or(true, |e| now < e), Error::<T>::LicenseExpired);
+                license.revoked_at = Some(now);
+                Ok(())
+            })?;
+            Self::deposit_event(Event::LicenseRevoked(licensee, licenseid, crmid, reasonhash));
+            Ok(().into())
+        }
+        /// Remove up to `limit` contracts of the signer, a catalog is deleted by calling it until no contract is left.
+        /// The co-owned contracts and the contracts with a crowdfunding campaign linked are skipped, so that the certificates
+        /// of the backers are not removed with them. The weight of the contracts not found is refunded.
+        #[pallet::weight(50_000 + 50_000 * (*limit as u64))]
+        pub fn delete_all_crm(origin: OriginFor<T>, limit: u32) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            // the co-owned contracts are removed with the approval of the owners
+            let crmids: Vec<T::CrmId> = OwnedContracts::<T>::get(&sender)
+                .into_iter()
+                .filter(|crmid| !CrmOwnerSets::<T>::contains_key(crmid) && Self::crm_campaign(*crmid).is_none())
+                .take(limit as usize)
+                .collect();
+            for crmid in crmids.iter() {
+                Self::remove_contract(*crmid);
+                CrmRevoked::<T>::remove(crmid);
+                Self::deposit_event(Event::CrmRemoved(sender.clone(), *crmid));
+            }
+            Ok(Some(50_000 + 50_000 * crmids.len() as Weight).into())
+        }
+        /// Set the holders of the master group of a contract with their percentage of the group (total 100), it can be
+        /// submitted by the owner of the contract only. An empty list removes the holders.
+        #[pallet::weight(50_000)]
+        pub fn set_master_holders(origin: OriginFor<T>, crmid: T::CrmId, holders: Vec<(T::AccountId, u8)>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            Self::set_holders(sender, crmid, HolderGroup::Master, holders)?;
+            Ok(().into())
+        }
+        /// Set the holders of the composition group of a contract with their percentage of the group (total 100), it can
+        /// be submitted by the owner of the contract only. An empty list removes the holders.
+        #[pallet::weight(50_000)]
+        pub fn set_composition_holders(origin: OriginFor<T>, crmid: T::CrmId, holders: Vec<(T::AccountId, u8)>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            Self::set_holders(sender, crmid, HolderGroup::Composition, holders)?;
+            Ok(().into())
+        }
+        /// Set the maximum number of contracts stored, replacing MaxTotalContracts, it can be submitted by root only.
+        /// The contracts already stored above the limit are kept.
+        #[pallet::weight(10_000)]
+        pub fn set_contract_limit(origin: OriginFor<T>, new_limit: u32) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            ContractLimitOverride::<T>::put(new_limit);
+            Self::deposit_event(Event::ContractLimitSet(new_limit));
+            Ok(().into())
+        }
+        /// Exempt an account from the creation cooldown or remove the exemption, it can be submitted by root only.
+        #[pallet::weight(10_000)]
+        pub fn set_cooldown_exempt(origin: OriginFor<T>, account: T::AccountId, exempt: bool) -> DispatchResultWithPostInfo {
+            ensure_root(origin)?;
+            if exempt {
+                CooldownExempt::<T>::insert(&account, true);
+            } else {
+                CooldownExempt::<T>::remove(&account);
+            }
+            Self::deposit_event(Event::CooldownExemptSet(account, exempt));
+            Ok(().into())
+        }
+        /// Share the ownership of a contract with other accounts, it can be submitted by the owner of a contract not co-owned
+        /// yet. The owners must include the signer, they can change the contract as the owner and the transfer, the revocation
+        /// and the change of the owners require the approval of `threshold` owners with `approve_owner_action`.
+        #[pallet::weight(50_000)]
+        pub fn set_owners(origin: OriginFor<T>, crmid: T::CrmId, owners: Vec<T::AccountId>, threshold: u32) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            ensure!(CrmOwner::<T>::get(&crmid) == Some(sender.clone()), Error::<T>::SignerIsNotOwner);
+            ensure!(!CrmOwnerSets::<T>::contains_key(&crmid), Error::<T>::ApprovalRequired);
+            ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+            Self::store_owner_set(crmid, owners, threshold)?;
+            Self::deposit_event(Event::OwnersUpdated(sender, crmid));
+            Ok(().into())
+        }
+        /// Approve a sensitive action on a co-owned contract, it can be submitted by the owners only. Every action waits for
+        /// its own approvals, up to MAX_PENDING_OWNER_ACTIONS actions, and it's executed by the approval reaching the threshold.
+        /// The change of the owners (transfer or new owner set) drops the actions still pending.
+        #[pallet::weight(50_000)]
+        #[transactional]
+        pub fn approve_owner_action(origin: OriginFor<T>, crmid: T::CrmId, action: OwnerActionOf<T>) -> DispatchResultWithPostInfo {
+            // Check that the extrinsic was signed and get the signer.
+            let sender = ensure_signed(origin)?;
+            ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+            let ownerset = CrmOwnerSets::<T>::get(&crmid).ok_or(Error::<T>::SignerIsNotOwner)?;
+            ensure!(ownerset.owners.contains(&sender), Error::<T>::SignerIsNotOwner);
+            let mut pending = PendingOwnerActions::<T>::get(&crmid);
+            let mut approvals = match pending.iter().position(|(a, _)| *a == action) {
+                Some(i) => pending.remove(i).1,
+                None => {
+                    ensure!(pending.len() < MAX_PENDING_OWNER_ACTIONS, Error::<T>::TooManyPendingActions);
+                    Vec::new()
+                }
+            };
+            ensure!(!approvals.contains(&sender), Error::<T>::AlreadyApproved);
+            approvals.push(sender.clone());
+            Self::deposit_event(Event::OwnerActionApproved(sender.clone(), crmid, approvals.len() as u32));
+            if (approvals.len() as u32) < ownerset.threshold {
+                pending.push((action, approvals));
+                PendingOwnerActions::<T>::insert(crmid, pending);
+                return Ok(().into());
+            }
+            // the other actions keep their approvals
+            if pending.is_empty() {
+                PendingOwnerActions::<T>::remove(crmid);
+            } else {
+                PendingOwnerActions::<T>::insert(crmid, pending);
+            }
+            match action {
+                OwnerAction::Transfer(to) => {
+                    let owner = CrmOwner::<T>::get(&crmid).ok_or(Error::<T>::InvalidContractId)?;
+                    CrmOwnerSets::<T>::remove(crmid);
+                    PendingOwnerActions::<T>::remove(crmid);
+                    Self::transfer_ownership(crmid, owner, to)?;
+                }
+                OwnerAction::Revoke => Self::revoke(crmid, sender)?,
+                OwnerAction::SetOwners(owners, threshold) => {
+                    if owners.is_empty() {
+                        CrmOwnerSets::<T>::remove(crmid);
+                    } else {
+                        Self::store_owner_set(crmid, owners, threshold)?;
+                    }
+                    PendingOwnerActions::<T>::remove(crmid);
+                    Self::deposit_event(Event::OwnersUpdated(sender, crmid));
+                }
+                OwnerAction::SetWithholdingAccount(account) => Self::store_withholding_account(crmid, account),
+                OwnerAction::GrantLicense(requestid, expires_at) => Self::do_grant_license(crmid, requestid, expires_at)?,
+            }
+            Self::deposit_event(Event::OwnerActionExecuted(crmid));
+            Ok(().into())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    // weight of a call failing on the checks done before the json validation, after the reads given
+    fn early_exit_weight(reads: Weight) -> Weight {
+        EARLY_EXIT_WEIGHT.saturating_add(T::DbWeight::get().reads(reads))
+    }
+    // checks of a new contract that don't parse the json, done first to refund the weight of the validation when
+    // they fail: the length of the crm data, the crmid and the limits of the signer and of the chain (9 reads)
+    fn precheck_new_contract(sender: &T::AccountId, crmid: T::CrmId, crmdata: &[u8]) -> dispatch::DispatchResultWithPostInfo {
+        Self::check_crmdata_length(crmdata).map_err(|e| e.with_weight(EARLY_EXIT_WEIGHT))?;
+        ensure!(!crmid.is_zero(), Error::<T>::InvalidValue.with_weight(EARLY_EXIT_WEIGHT));
+        ensure!(crmid != Self::reserved_crm_id(), Error::<T>::ReservedCrmId.with_weight(EARLY_EXIT_WEIGHT));
+        Self::check_new_crmid(sender, crmid).map_err(|e| e.with_weight(Self::early_exit_weight(9)))?;
+        Ok(().into())
+    }
+    // check the length of the main crm data
+    fn check_crmdata_length(crmdata: &[u8]) -> dispatch::DispatchResult {
+        ensure!(crmdata.len() as u32 >= T::MinCrmDataLength::get(), Error::<T>::CrmDataTooShort); //check minimum length
+        ensure!(crmdata.len() as u32 <= T::MaxCrmDataLength::get(), Error::<T>::CrmDataTooLong);  // check maximum length
+        Ok(())
+    }
+    // validate and store a new contract owned by sender, the deposit and the creation fee are paid by sender
+    fn create_contract(sender: T::AccountId, crmid: T::CrmId, crmdata: Vec<u8>, master: Vec<u8>, composition: Vec<u8>, othercontracts: Vec<u8>) -> dispatch::DispatchResult {
+        // check crm data
+        Self::check_crmdata_length(&crmdata)?;
+        // check master data
+        ensure!(master.len() >= 8, Error::<T>::MasterTooShort); //check minimum length
+        ensure!(master.len() <= 1024, Error::<T>::MasterTooLong);  // check maximum length
+        // check composition data
+        ensure!(composition.len() >= 8, Error::<T>::CompositionTooShort); //check minimum length
+        ensure!(composition.len() <= 1024, Error::<T>::CompositionTooLong);  // check maximum length
+        // check Other Contracts data
+        ensure!(othercontracts.len() <= 1024, Error::<T>::OtherContractsTooLong);  // check maximum length
+        // check oracleid
+        ensure!(!crmid.is_zero(), Error::<T>::InvalidValue); //check for crmid length >0
+        ensure!(crmid != Self::reserved_crm_id(), Error::<T>::ReservedCrmId); //check for the reserved crmid
+        // check of the crmid is free
+        Self::check_new_crmid(&sender, crmid)?;
+        // check json validity
+        let js=crmdata.clone();
+        Self::check_json(js)?;
+        // check the fields of the crm data, a new contract has no legacy hashes
+        let (territories, parent) = Self::check_crmdata_fields(&crmdata, crmid, Some(sender.clone()), &[])?;
+
+        // check validity of master data
+        let masterclone=master.clone();
+        // check for a valid json
+        Self::check_json(masterclone)?;
+        let mut x=0;
+        let mut totpercentage:u32 = 0;
+        // check validity of records for Master Data
+        loop {
+            let jr=json_get_recordvalue(master.clone(),x);
+            if jr.is_empty() {
+                break;
+            }
+            // check for nickname
+            let nickname=json_get_value(jr.clone(),"nickname".as_bytes().to_vec());
+            ensure!(!nickname.is_empty(), Error::<T>::MissingMasterNickname);
+            // check for account address
+            let account=json_get_value(jr.clone(),"account".as_bytes().to_vec());
+            ensure!(!account.is_empty(), Error::<T>::MissingMasterAccount);
+            // check for percentage
+            let percentage=json_get_value(jr.clone(),"percentage".as_bytes().to_vec());
+            ensure!(!percentage.is_empty(), Error::<T>::MissingMasterPercentage);
+            // convert percentage from vec to u32
+            let percentagevalue=vecu8_to_u32(percentage);
+            ensure!(percentagevalue >0, Error::<T>::MissingMasterPercentage);
+            // sum percentage to totpercentage
+            totpercentage += percentagevalue;
+            x += 1;
+        }
+        // check the total percentage is = 100 TODO
+        ensure!(totpercentage == 100, Error::<T>::WrongTotalPercentageMaster);
+
+        // check validity of composition data
+        let compositionclone=composition.clone();
+        // check for a valid json
+        Self::check_json(compositionclone)?;
+        x=0;
+        totpercentage=0;
+        // check validity of records for Composition Data
+        loop {
+            let jr=json_get_recordvalue(composition.clone(),x);
+            if jr.is_empty() {
+                break;
+            }
+            // check for nickname
+            let nickname=json_get_value(jr.clone(),"nickname".as_bytes().to_vec());
+            ensure!(!nickname.is_empty(), Error::<T>::MissingCompositionNickname);
+            // check for account address
+            let account=json_get_value(jr.clone(),"account".as_bytes().to_vec());
+            ensure!(!account.is_empty(), Error::<T>::MissingCompositionAccount);
+            // check for percentage
+            let percentage=json_get_value(jr.clone(),"percentage".as_bytes().to_vec());
+            ensure!(!percentage.is_empty(), Error::<T>::MissingCompositionPercentage);
+            // convert percentage from vec to u32
+            let percentagevalue=vecu8_to_u32(percentage);
+            ensure!(percentagevalue >0, Error::<T>::MissingCompositionPercentage);
+            // sum percentage to totpercentage
+            totpercentage+=percentagevalue;
+            x+=1;
+        }
+        // check the total percentage is = 100
+        ensure!(totpercentage == 100, Error::<T>::WrongTotalPercentageComposition);
+        // check the quorums of the groups owned by a single holder
+        Self::check_single_holder_quorum(&crmdata, &master, "masterquorum")?;
+        Self::check_single_holder_quorum(&crmdata, &composition, "compositionquorum")?;
+
+
+        // Other contracts are optional we check the validity if there is a value only
+        if othercontracts.len()>10 {
+            // check validity of othercontracts data
+            let othercontractsclone=othercontracts.clone();
+            // check for a valid json
+            Self::check_json(othercontractsclone)?;
+            x=0;
+            totpercentage= 0;
+            // check validity of records for other contracts data
+            loop {
+                let jr=json_get_recordvalue(othercontracts.clone(),x);
+                if jr.is_empty() {
+                    break;
+                }
+                // check for id
+                let id=json_get_value(jr.clone(),"id".as_bytes().to_vec());
+                ensure!(!id.is_empty(), Error::<T>::MissingOtherContractsId);
+                let idvalue=vecu8_to_crmid::<T::CrmId>(id);
+                // check that the id is on chain
+                ensure!(CrmData::<T>::contains_key(&idvalue), Error::<T>::InvalidContractId);
+                // check for percentage
+                let percentage=json_get_value(jr.clone(),"percentage".as_bytes().to_vec());
+                ensure!(!percentage.is_empty(), Error::<T>::MissingOtherContractsPercentage);
+                // convert percentage from vec to u32
+                let percentagevalue=vecu8_to_u32(percentage);
+                ensure!(percentagevalue >0, Error::<T>::MissingOtherContractsPercentage);
+                // sum percentage to totpercentage
+                totpercentage+=percentagevalue;
+                x+=1;
+            }
+            // check the total percentage is = 100
+            ensure!(totpercentage == 100, Error::<T>::WrongTotalPercentageOtherContracts);
+        }
+
+        //****************************************
+        // STORING DATA
+        //****************************************
+        // charge the creation fee and reserve the deposit, the data are validated so a wrong contract is not charged
+        Self::charge_creation(&sender, crmid, crmdata.len())?;
+        // link the crowdfunding campaign
+        Self::link_crowdfunding_campaign(crmid, &crmdata);
+        // Write storage for crmdata
+        Self::store_crmdata(crmid, crmdata)?;
+        Self::store_territories(crmid, territories);
+        Self::store_parent_contract(crmid, parent);
+        Self::register_contract(&sender, crmid);
+        // Write the storage for master data
+        CrmMasterData::<T>::insert(crmid, master);
+        // Write the storage for Composition data
+        CrmCompositionData::<T>::insert(crmid, composition);
+        // write the storage for Other Contracts data (optional)
+        if !othercontracts.is_empty() {
+            // Update storage for Other Contracts data
+            CrmOtherContractsData::<T>::insert(crmid, othercontracts);
+        }
+        // Emit an event
+        Self::deposit_crm_event(crmid, Event::CrmAdded(sender, crmid));
+        // Return a successful DispatchResult
+        Ok(())
+    }
+    /// Topic of the events of a contract: `CrmAdded`, `CrmAddedByHash` and `CrmDataChanged` are indexed with it, a
+    /// client can follow the lifecycle of the contract by subscribing to it.
+    pub fn crm_topic(crmid: T::CrmId) -> T::Hash {
+        T::Hashing::hash_of(&(EVENT_TOPIC_PREFIX, crmid))
+    }
+    // deposit an event indexed with the topic of the contract
+    fn deposit_crm_event(crmid: T::CrmId, event: Event<T>) {
+        let event = <T as Config>::Event::from(event).into();
+        frame_system::Module::<T>::deposit_event_indexed(&[Self::crm_topic(crmid)], event);
+    }
+    // validate and store a contract of the genesis config, the deposit and the creation fee are not charged
+    fn store_genesis_contract(owner: &T::AccountId, crmid: T::CrmId, crmdata: Vec<u8>) -> dispatch::DispatchResult {
+        ensure!(!crmid.is_zero(), Error::<T>::InvalidValue);
+        ensure!(crmid != Self::reserved_crm_id(), Error::<T>::ReservedCrmId);
+        ensure!(!CrmData::<T>::contains_key(&crmid), Error::<T>::DuplicatedCrmId);
+        Self::check_crmdata_length(&crmdata)?;
+        Self::check_json(crmdata.clone())?;
+        Self::check_crmdata(&crmdata, crmid)?;
+        let territories = Self::check_territories(&crmdata)?;
+        let parent = Self::check_parent_contract(&crmdata, crmid)?;
+        Self::link_crowdfunding_campaign(crmid, &crmdata);
+        Self::store_crmdata(crmid, crmdata)?;
+        Self::store_territories(crmid, territories);
+        Self::store_parent_contract(crmid, parent);
+        Self::register_contract(owner, crmid);
+        Ok(())
+    }
+    /// The account of the pallet, it held the crowdfunding contributions before the escrow accounts of the campaigns.
+    pub fn account_id() -> T::AccountId {
+        T::ModuleId::get().into_account()
+    }
+    /// The escrow account of a crowdfunding campaign holding the contributions until the campaign is closed (and the
+    /// refunds of a failed campaign), a sub account of the module id distinct from the accounts of the contracts.
+    pub fn campaign_account_id(campaignid: u32) -> T::AccountId {
+        T::ModuleId::get().into_sub_account((CAMPAIGN_TASK_ID, campaignid))
+    }
+    /// The escrow account of a contract holding the royalties credited and not yet claimed,
+    /// a sub account of the module id so that the funds of different contracts are never mixed.
+    pub fn contract_account_id(crmid: T::CrmId) -> T::AccountId {
+        T::ModuleId::get().into_sub_account(crmid)
+    }
+    /// The account holding the subscription pool, the sub account of the reserved crmid that is never used by a contract.
+    pub fn pool_account_id() -> T::AccountId {
+        T::ModuleId::get().into_sub_account(Self::reserved_crm_id())
+    }
+    /// The crmid reserved as "none" marker, the maximum value of the crmid type (u32::MAX in the runtime): it cannot be
+    /// used by a contract.
+    pub fn reserved_crm_id() -> T::CrmId {
+        T::CrmId::max_value()
+    }
+    /// The balance of the escrow account of a contract.
+    pub fn get_contract_escrow_balance(crmid: T::CrmId) -> BalanceOf<T> {
+        T::Currency::free_balance(&Self::contract_account_id(crmid))
+    }
+    // issue the crowdfunding certificates of a successful campaign, the basis points are proportional to the contributions
+    // and the remainder of the integer division is assigned to the largest remainders so that the total is always 10000
+    fn issue_certificates(campaignid: u32, raised: BalanceOf<T>) {
+        let now = <frame_system::Module<T>>::block_number();
+        let raised: u128 = raised.unique_saturated_into();
+        if raised == 0 {
+            return;
+        }
+        let mut certificates: Vec<(T::AccountId, BalanceOf<T>, u32, u128)> = Vec::new();
+        let mut total: u32 = 0;
+        for (contributor, contributed) in CampaignContributions::<T>::iter_prefix(campaignid) {
+            let c: u128 = contributed.unique_saturated_into();
+            let scaled = c.saturating_mul(10_000);
+            let bps = (scaled / raised) as u32;
+            total = total.saturating_add(bps);
+            certificates.push((contributor, contributed, bps, scaled % raised));
+        }
+        // sort by the remainder so that the basis points left are assigned to the largest remainders
+        certificates.sort_by(|a, b| b.3.cmp(&a.3));
+        let mut left = 10_000u32.saturating_sub(total);
+        for (contributor, contributed, bps, _) in certificates {
+            let mut basis_points = bps;
+            if left > 0 {
+                basis_points += 1;
+                left -= 1;
+            }
+            CrowdfundingCertificates::<T>::insert(campaignid, &contributor, Certificate {
+                basis_points,
+                contributed,
+                issued_at: now,
+            });
+            Self::deposit_event(Event::CertificateIssued(contributor, campaignid, basis_points));
+        }
+    }
+    // id of the scheduled task closing a campaign
+    fn campaign_task_id(campaignid: u32) -> Vec<u8> {
+        (CAMPAIGN_TASK_ID, campaignid).encode()
+    }
+    /// The owner and the crmid of the contract funded by a crowdfunding campaign, for the lookups of other pallets.
+    pub fn campaign_contract(campaignid: u32) -> Option<(T::AccountId, T::CrmId)> {
+        let crmid = CampaignToCrm::<T>::get(&campaignid)?;
+        Some((CrmOwner::<T>::get(&crmid)?, crmid))
+    }
+    /// The crowdfunding campaign referenced by the "crowdfounders" field of a contract.
+    pub fn crm_campaign(crmid: T::CrmId) -> Option<u32> {
+        let crmdata = CrmData::<T>::get(&crmid)?;
+        let campaignid = vecu8_to_u32(json_get_value(crmdata, "crowdfounders".as_bytes().to_vec()));
+        if campaignid > 0 {
+            Some(campaignid)
+        } else {
+            None
+        }
+    }
+    // remove a contract and the crowdfunding certificates of the campaign linked through the "crowdfounders" field,
+    // with SoftDelete a tombstone is left and the licenses granted are kept
+    fn remove_contract(crmid: T::CrmId) {
+        if let Some(campaignid) = Self::crm_campaign(crmid) {
+            CrowdfundingCertificates::<T>::remove_prefix(campaignid);
+        }
+        Self::unlink_crowdfunding_campaign(crmid);
+        let inline = CrmData::<T>::take(crmid).map(|crmdata| json_get_value(crmdata, "ipfshash".as_bytes().to_vec()));
+        let byhash = CrmCommitments::<T>::take(crmid).map(|commitment| commitment.ipfscid);
+        if let Some(hash) = inline.or(byhash) {
+            TotalCrms::<T>::mutate(|n| *n = n.saturating_sub(1));
+            Self::unindex_ipfshash(crmid, &hash);
+        }
+        if let Some((depositor, deposit)) = Deposits::<T>::take(crmid) {
+            T::Currency::unreserve(&depositor, deposit);
+        }
+        if let Some(owner) = CrmOwner::<T>::take(crmid) {
+            ContractCount::<T>::mutate(&owner, |n| *n = n.saturating_sub(1));
+            OwnedContracts::<T>::mutate(&owner, |ids| ids.retain(|id| *id != crmid));
+        }
+        CrmMasterData::<T>::remove(crmid);
+        CrmCompositionData::<T>::remove(crmid);
+        CrmOtherContractsData::<T>::remove(crmid);
+        PlayCounts::<T>::remove(crmid);
+        ContractReporters::<T>::remove_prefix(crmid);
+        CrmEditors::<T>::remove(crmid);
+        CrmHolders::<T>::remove_prefix(crmid);
+        Changelog::<T>::remove(crmid);
+        LegacyIpfsHashes::<T>::remove(crmid);
+        CrmOwnerSets::<T>::remove(crmid);
+        PendingOwnerActions::<T>::remove(crmid);
+        CrmTerritories::<T>::remove(crmid);
+        CrmIsrc::<T>::remove(crmid);
+        CrmIswc::<T>::remove(crmid);
+        CrmContracts::<T>::remove(crmid);
+        DerivativeOf::<T>::remove(crmid);
+        CrmVersion::<T>::remove(crmid);
+        CrmCreatedAt::<T>::remove(crmid);
+        CrmUpdatedAt::<T>::remove(crmid);
+        WithholdingAccounts::<T>::remove(crmid);
+        if T::SoftDelete::get() {
+            CrmDeleted::<T>::insert(crmid, <frame_system::Module<T>>::block_number());
+        } else {
+            for (_, licenses) in Licenses::<T>::drain_prefix(crmid) {
+                for license in licenses {
+                    LicenseIndex::<T>::remove(license.id);
+                }
+            }
+        }
+    }
+    // record a license granted to the licensee, it returns the license id
+    fn add_license(
+        crmid: T::CrmId,
+        licensee: &T::AccountId,
+        kind: LicenseKind,
+        terms_hash: Vec<u8>,
+        expires_at: Option<T::BlockNumber>,
+    ) -> Result<u32, DispatchError> {
+        let id = NextLicenseId::<T>::get();
+        NextLicenseId::<T>::put(id.checked_add(1).ok_or(Error::<T>::NoAvailableLicenseId)?);
+        Licenses::<T>::append(crmid, licensee, License {
+            id,
+            kind,
+            terms_hash,
+            granted_at: <frame_system::Module<T>>::block_number(),
+            expires_at,
+            revoked_at: None,
+        });
+        LicenseIndex::<T>::insert(id, (crmid, licensee.clone()));
+        Ok(id)
+    }
+    /// The license with the given id, None when it's not on chain.
+    pub fn get_license(licenseid: u32) -> Option<LicenseOf<T>> {
+        let (crmid, licensee) = LicenseIndex::<T>::get(licenseid)?;
+        Licenses::<T>::get(crmid, &licensee).into_iter().find(|l| l.id == licenseid)
+    }
+    /// True when the license is on chain, not expired and not revoked.
+    pub fn is_license_active(licenseid: u32) -> bool {
+        let now = <frame_system::Module<T>>::block_number();
+        match Self::get_license(licenseid) {
+            Some(l) => l.revoked_at.is_none() && l.expires_at.map_or(true, |e| now < e),
+            None => false,
+        }
+    }
+    // increment the version of a contract and record the block of the change, it returns the new version
+    fn bump_version(crmid: T::CrmId) -> u32 {
+        let version = CrmVersion::<T>::mutate(crmid, |v| {
+            *v = v.saturating_add(1);
+            *v
+        });
+        CrmUpdatedAt::<T>::insert(crmid, <frame_system::Module<T>>::block_number());
+        Self::offchain_index_crmdata(crmid, version);
+        version
+    }
+    // write the main crm data of a contract at the version given in the off-chain indexing database, for the archive
+    // nodes serving the history of the contracts. The host ignores the write when the indexing is not enabled
+    // (--enable-offchain-indexing), the contracts stored by hash have no crm data to write.
+    fn offchain_index_crmdata(crmid: T::CrmId, version: u32) {
+        if let (Some(owner), Some(crmdata)) = (CrmOwner::<T>::get(&crmid), CrmData::<T>::get(&crmid)) {
+            let key = offchain_key(&owner, crmid, version);
+            sp_io::offchain_index::set(&key, &(owner, crmid, crmdata).encode());
+        }
+    }
+    // check the version expected by the caller, if any, against the version of the contract (0 when not on chain)
+    fn check_version(crmid: T::CrmId, expected: Option<u32>) -> dispatch::DispatchResult {
+        ensure!(expected.map_or(true, |v| v == CrmVersion::<T>::get(crmid)), Error::<T>::VersionMismatch);
+        Ok(())
+    }
+    // check the optional "territories" of the crm data, it returns the codes
+    fn check_territories(crmdata: &[u8]) -> Result<Vec<[u8; 2]>, DispatchError> {
+        let territories = json_get_territories(crmdata.to_vec()).ok_or(Error::<T>::InvalidTerritory)?;
+        ensure!(territories.len() as u32 <= T::MaxTerritories::get(), Error::<T>::TooManyTerritories);
+        Ok(territories)
+    }
+    // replace the main crm data of a contract on chain, the signer must be the owner or an editor
+    fn update_crmdata(sender: T::AccountId, crmid: T::CrmId, crmdata: Vec<u8>, kind: ChangeKind) -> dispatch::DispatchResult {
+        ensure!(Self::is_owner_or_editor(crmid, &sender), Error::<T>::SignerIsNotOwner);
+        ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+        Self::ensure_unlocked(crmid)?;
+        // check crm data
+        ensure!(crmdata.len() as u32 >= T::MinCrmDataLength::get(), Error::<T>::CrmDataTooShort); //check minimum length
+        ensure!(crmdata.len() as u32 <= T::MaxCrmDataLength::get(), Error::<T>::CrmDataTooLong);  // check maximum length
+        Self::check_json(crmdata.clone())?;
+        Self::check_crmdata(&crmdata, crmid)?;
+        // the crowdfunding share and campaign are not changeable
+        let current = CrmData::<T>::get(&crmid).unwrap_or_default();
+        for field in ["crodwfundingshares", "crowdfounders"].iter() {
+            ensure!(
+                json_get_value(current.clone(), field.as_bytes().to_vec()) == json_get_value(crmdata.clone(), field.as_bytes().to_vec()),
+                Error::<T>::CrowdfundingShareImmutable
+            );
+        }
+        Self::store_territories(crmid, json_get_territories(crmdata.clone()).unwrap_or_default());
+        Self::store_parent_contract(crmid, Self::check_parent_contract(&crmdata, crmid)?);
+        Self::store_crmdata(crmid, crmdata)?;
+        let version = Self::bump_version(crmid);
+        // Emit an event to alert the user of the crm data change done
+        Self::log_change(crmid, &sender, kind);
+        Self::deposit_crm_event(crmid, Event::CrmDataChanged(sender, crmid, version, kind));
+        Ok(())
+    }
+    // with SingleHolderQuorum, the quorum of a group with one holder (the total percentage is 100) must be 100,
+    // the check is skipped when the holders of the group are not known
+    fn check_single_holder_quorum(crmdata: &[u8], holders: &[u8], quorumkey: &str) -> dispatch::DispatchResult {
+        if !T::SingleHolderQuorum::get() || json_get_recordvalue(holders.to_vec(), 0).is_empty() {
+            return Ok(());
+        }
+        if json_get_recordvalue(holders.to_vec(), 1).is_empty() {
+            let quorum = vecu8_to_u32(json_get_value(crmdata.to_vec(), quorumkey.as_bytes().to_vec()));
+            ensure!(quorum == 100, Error::<T>::QuorumHolderMismatch);
+        }
+        Ok(())
+    }
+    // move a contract to another owner, with the counters of the owners
+    fn transfer_ownership(crmid: T::CrmId, from: T::AccountId, to: T::AccountId) -> dispatch::DispatchResult {
+        ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+        ensure!(to != from, Error::<T>::SameContractOwner);
+        ensure!(ContractCount::<T>::get(&to) < T::MaxContractsPerAccount::get(), Error::<T>::TooManyContracts);
+        // move the contract between the counters of the owners
+        CrmOwner::<T>::insert(crmid, to.clone());
+        ContractCount::<T>::mutate(&from, |n| *n = n.saturating_sub(1));
+        ContractCount::<T>::mutate(&to, |n| *n = n.saturating_add(1));
+        OwnedContracts::<T>::mutate(&from, |ids| ids.retain(|id| *id != crmid));
+        OwnedContracts::<T>::append(&to, crmid);
+        Self::deposit_event(Event::ContractTransferred(from, crmid, to));
+        Ok(())
+    }
+    // set or remove the account receiving the part of the claims withheld
+    fn store_withholding_account(crmid: T::CrmId, account: Option<T::AccountId>) {
+        match account.clone() {
+            Some(account) => WithholdingAccounts::<T>::insert(crmid, account),
+            None => WithholdingAccounts::<T>::remove(crmid),
+        }
+        Self::deposit_event(Event::WithholdingAccountSet(crmid, account));
+    }
+    // grant a license request of a contract, the offer held in the escrow account is credited to the members
+    fn do_grant_license(crmid: T::CrmId, requestid: u32, expires_at: Option<T::BlockNumber>) -> dispatch::DispatchResult {
+        let request = LicenseRequests::<T>::get(requestid).ok_or(Error::<T>::LicenseRequestNotFound)?;
+        ensure!(request.crmid == crmid, Error::<T>::LicenseRequestNotFound);
+        ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+        if let Some(expires_at) = expires_at {
+            ensure!(expires_at > <frame_system::Module<T>>::block_number(), Error::<T>::InvalidLicenseExpiry);
+        }
+        // the offer is already in the escrow account, the transfer of the part credited is a no-op
+        let escrow = Self::contract_account_id(crmid);
+        Self::do_pay_royalties(escrow, crmid, request.offer, None, ExistenceRequirement::AllowDeath)?;
+        LicenseRequests::<T>::remove(requestid);
+        Self::add_license(crmid, &request.licensee, LicenseKind::Custom, request.terms_hash, expires_at)?;
+        Self::deposit_event(Event::LicenseGranted(request.licensee, requestid, crmid));
+        Ok(())
+    }
+    // revoke a contract, it's removed later by prune_revoked
+    fn revoke(crmid: T::CrmId, sender: T::AccountId) -> dispatch::DispatchResult {
+        ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+        CrmRevoked::<T>::insert(crmid, <frame_system::Module<T>>::block_number());
+        Self::deposit_event(Event::CrmRevoked(sender, crmid));
+        Ok(())
+    }
+    // check and store the owners of a co-owned contract, the owner in CrmOwner must be one of them
+    fn store_owner_set(crmid: T::CrmId, owners: Vec<T::AccountId>, threshold: u32) -> dispatch::DispatchResult {
+        ensure!(owners.len() <= MAX_OWNERS, Error::<T>::InvalidOwnerSet);
+        ensure!(threshold >= 1 && threshold as usize <= owners.len(), Error::<T>::InvalidOwnerSet);
+        let owner = CrmOwner::<T>::get(&crmid).ok_or(Error::<T>::InvalidContractId)?;
+        ensure!(owners.contains(&owner), Error::<T>::InvalidOwnerSet);
+        for (i, account) in owners.iter().enumerate() {
+            ensure!(!owners[..i].contains(account), Error::<T>::InvalidOwnerSet);
+        }
+        CrmOwnerSets::<T>::insert(crmid, OwnerSet { owners, threshold });
+        Ok(())
+    }
+    // check and store the holders of a group of a contract
+    fn set_holders(sender: T::AccountId, crmid: T::CrmId, group: HolderGroup, holders: Vec<(T::AccountId, u8)>) -> dispatch::DispatchResult {
+        ensure!(CrmData::<T>::contains_key(&crmid), Error::<T>::InvalidContractId);
+        ensure!(Self::is_owner(crmid, &sender), Error::<T>::SignerIsNotOwner);
+        ensure!(!CrmRevoked::<T>::contains_key(&crmid), Error::<T>::ContractRevoked);
+        Self::ensure_unlocked(crmid)?;
+        ensure!(holders.len() <= MAX_HOLDERS, Error::<T>::TooManyHolders);
+        if holders.is_empty() {
+            CrmHolders::<T>::remove(crmid, group);
+        } else {
+            ensure!(holders.iter().all(|(_, p)| *p > 0), Error::<T>::WrongTotalPercentageHolders);
+            let total: u32 = holders.iter().map(|(_, p)| *p as u32).sum();
+            ensure!(total == 100, Error::<T>::WrongTotalPercentageHolders);
+            for (i, (account, _)) in holders.iter().enumerate() {
+                ensure!(!holders[..i].iter().any(|(a, _)| a == account), Error::<T>::DuplicatedHolder);
+            }
+            CrmHolders::<T>::insert(crmid, group, holders);
+        }
+        Self::log_change(crmid, &sender, ChangeKind::Holders);
+        Self::deposit_event(Event::HoldersUpdated(sender, crmid, group));
+        Ok(())
+    }
+    // check that the lock period after the creation of a contract is over, the contracts created before the lock have none
+    fn ensure_unlocked(crmid: T::CrmId) -> dispatch::DispatchResult {
+        if let Some(created) = CrmCreatedAt::<T>::get(&crmid) {
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(now >= created.saturating_add(T::UpdateLockPeriod::get()), Error::<T>::LockPeriodActive);
+        }
+        Ok(())
+    }
+    // check that a new contract can be created with the crmid by the account
+    fn check_new_crmid(sender: &T::AccountId, crmid: T::CrmId) -> dispatch::DispatchResult {
+        ensure!(!CrmData::<T>::contains_key(&crmid), Error::<T>::DuplicatedCrmId);
+        ensure!(!CrmCommitments::<T>::contains_key(&crmid), Error::<T>::DuplicatedCrmId);
+        ensure!(!CrmDeleted::<T>::contains_key(&crmid), Error::<T>::DuplicatedCrmId);
+        // check the number of contracts owned by the signer
+        ensure!(ContractCount::<T>::get(sender) < T::MaxContractsPerAccount::get(), Error::<T>::TooManyContracts);
+        // check the number of contracts created in the block
+        ensure!(ContractsInBlock::<T>::get() < T::MaxContractsPerBlock::get(), Error::<T>::BlockCapacityReached);
+        // check the number of contracts stored, the removals free the capacity
+        ensure!(TotalCrms::<T>::get() < Self::contract_limit(), Error::<T>::GlobalContractLimitReached);
+        // check the cooldown after the last creation of the signer, the creations of the same block (a batch) count as one
+        if let Some(last) = LastCreation::<T>::get(sender) {
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(
+                now == last || now >= last.saturating_add(T::CreationCooldown::get()) || CooldownExempt::<T>::get(sender),
+                Error::<T>::CreationRateLimited
+            );
+        }
+        Ok(())
+    }
+    // charge the creation fee and reserve the deposit for the length of the data stored, it fails when the account cannot afford them
+    fn charge_creation(sender: &T::AccountId, crmid: T::CrmId, len: usize) -> dispatch::DispatchResult {
+        let fee = T::CreationFee::get();
+        if !fee.is_zero() {
+            let imbalance = T::Currency::withdraw(sender, fee, WithdrawReasons::FEE, ExistenceRequirement::KeepAlive)?;
+            T::CreationFeeHandler::on_unbalanced(imbalance);
+        }
+        let deposit = Self::deposit_for(len);
+        T::Currency::reserve(sender, deposit)?;
+        Deposits::<T>::insert(crmid, (sender.clone(), deposit));
+        Ok(())
+    }
+    // write the version, the owner and the counters of a new contract
+    fn register_contract(sender: &T::AccountId, crmid: T::CrmId) {
+        CrmVersion::<T>::insert(crmid, 1);
+        CrmCreatedAt::<T>::insert(crmid, <frame_system::Module<T>>::block_number());
+        CrmUpdatedAt::<T>::insert(crmid, <frame_system::Module<T>>::block_number());
+        TotalCrms::<T>::mutate(|n| *n = n.saturating_add(1));
+        TotalContracts::<T>::mutate(|n| *n = n.saturating_add(1));
+        CrmOwner::<T>::insert(crmid, sender.clone());
+        ContractCount::<T>::mutate(sender, |n| *n = n.saturating_add(1));
+        OwnedContracts::<T>::append(sender, crmid);
+        ContractsInBlock::<T>::mutate(|n| *n = n.saturating_add(1));
+        LastCreation::<T>::insert(sender, <frame_system::Module<T>>::block_number());
+        Self::offchain_index_crmdata(crmid, 1);
+        Self::log_change(crmid, sender, ChangeKind::Created);
+    }
+    // blake2-256 hash of the main crm data stored, the hash of the commitment for the contracts stored by hash
+    fn stored_datahash(crmid: T::CrmId) -> Option<[u8; 32]> {
+        match CrmData::<T>::get(&crmid) {
+            Some(crmdata) => Some(sp_io::hashing::blake2_256(&crmdata)),
+            None => CrmCommitments::<T>::get(&crmid).map(|c| c.datahash),
+        }
+    }
+    // append a change to the changelog of a contract, the oldest entry is removed when the log is full
+    fn log_change(crmid: T::CrmId, who: &T::AccountId, action: ChangeKind) {
+        let datahash = Self::stored_datahash(crmid).unwrap_or_default();
+        let entry = ChangelogEntry { block: <frame_system::Module<T>>::block_number(), who: who.clone(), action, datahash };
+        Changelog::<T>::mutate(crmid, |log| {
+            let max = T::MaxChangelogEntries::get() as usize;
+            if max == 0 {
+                return;
+            }
+            if log.len() >= max {
+                log.drain(..log.len() + 1 - max);
+            }
+            log.push(entry);
+        });
+    }
+    // check the shares and quorums of a contract stored by hash: the quorums and the master/composition shares must be
+    // > 0 and the shares must total 100, the donation is not accepted since its account is in the json only
+    fn check_shares(shares: &CrmShares) -> dispatch::DispatchResult {
+        ensure!(shares.globalquorum.deconstruct() > 0, Error::<T>::InvalidGlobalQuorum);
+        ensure!(shares.mastershare.deconstruct() > 0, Error::<T>::InvalidMasterShare);
+        ensure!(shares.masterquorum.deconstruct() > 0, Error::<T>::InvalidMasterQuorum);
+        ensure!(shares.compositionshare.deconstruct() > 0, Error::<T>::InvalidCompositionShare);
+        ensure!(shares.compositionquorum.deconstruct() > 0, Error::<T>::InvalidCompositionQuorum);
+        ensure!(shares.donationshare.deconstruct() == 0, Error::<T>::MissingDonationAccount);
+        let total = [shares.mastershare, shares.compositionshare, shares.othercontractsshare, shares.crowdfundingshare]
+            .iter()
+            .map(|p| p.deconstruct() as u32)
+            .sum::<u32>();
+        ensure!(total == 100, Error::<T>::InvalidTotalShares);
+        Ok(())
+    }
+    // store the main crm data of a contract with its decoded form, the deposit follows the length of the crm data
+    fn store_crmdata(crmid: T::CrmId, crmdata: Vec<u8>) -> dispatch::DispatchResult {
+        // the single write of the crm data, BoundedVec is not available in frame-support 3.0 and the bound is checked here
+        ensure!(crmdata.len() as u32 <= T::MaxCrmDataLength::get(), Error::<T>::CrmDataTooLong);
+        let previous = CrmData::<T>::get(&crmid).map(|c| json_get_value(c, "ipfshash".as_bytes().to_vec())).unwrap_or_default();
+        Self::index_ipfshash(crmid, &previous, &json_get_value(crmdata.clone(), "ipfshash".as_bytes().to_vec()))?;
+        Self::adjust_deposit(crmid, crmdata.len())?;
+        // the codes are validated before storing, a malformed code is not kept
+        match json_get_isrc(crmdata.clone()).unwrap_or_default() {
+            isrc if isrc.is_empty() => CrmIsrc::<T>::remove(crmid),
+            isrc => CrmIsrc::<T>::insert(crmid, isrc),
+        }
+        match json_get_iswc(crmdata.clone()).unwrap_or_default() {
+            iswc if iswc.is_empty() => CrmIswc::<T>::remove(crmid),
+            iswc => CrmIswc::<T>::insert(crmid, iswc),
+        }
+        match json_get_contract(crmdata.clone()) {
+            Some(contract) => CrmContracts::<T>::insert(crmid, contract),
+            None => CrmContracts::<T>::remove(crmid),
+        }
+        CrmData::<T>::insert(crmid, crmdata);
+        Ok(())
+    }
+    // move the index entry of a contract from the previous ipfs hash to the new one, a hash registered by another
+    // contract is rejected when UniqueIpfsHash is set, otherwise it stays with the other contract
+    fn index_ipfshash(crmid: T::CrmId, previous: &[u8], hash: &[u8]) -> dispatch::DispatchResult {
+        if previous == hash {
+            return Ok(());
+        }
+        if !hash.is_empty() {
+            if let Some(other) = IpfsIndex::<T>::get(hash) {
+                ensure!(other == crmid || !T::UniqueIpfsHash::get(), Error::<T>::IpfsHashAlreadyRegistered);
+            }
+        }
+        if !previous.is_empty() {
+            Self::unindex_ipfshash(crmid, previous);
+        }
+        if hash.is_empty() {
+            return Ok(());
+        }
+        IpfsHashContracts::<T>::insert(hash, crmid, true);
+        if !IpfsIndex::<T>::contains_key(hash) {
+            IpfsIndex::<T>::insert(hash, crmid);
+        }
+        Ok(())
+    }
+    // drop a contract from the contracts of a hash, the index moves to another contract sharing the hash if any
+    fn unindex_ipfshash(crmid: T::CrmId, hash: &[u8]) {
+        IpfsHashContracts::<T>::remove(hash, crmid);
+        if IpfsIndex::<T>::get(hash) == Some(crmid) {
+            match IpfsHashContracts::<T>::iter_prefix(hash).next() {
+                Some((other, _)) => IpfsIndex::<T>::insert(hash, other),
+                None => IpfsIndex::<T>::remove(hash),
+            }
+        }
+    }
+    // deposit of a contract for the length of its crm data
+    fn deposit_for(len: usize) -> BalanceOf<T> {
+        let len: BalanceOf<T> = (len as u32).into();
+        T::DepositBase::get().saturating_add(T::DepositPerByte::get().saturating_mul(len))
+    }
+    // reserve or unreserve the difference of the deposit of a contract from the account that paid it
+    fn adjust_deposit(crmid: T::CrmId, len: usize) -> dispatch::DispatchResult {
+        if let Some((depositor, current)) = Deposits::<T>::get(&crmid) {
+            let deposit = Self::deposit_for(len);
+            if deposit > current {
+                T::Currency::reserve(&depositor, deposit - current)?;
+            } else if deposit < current {
+                T::Currency::unreserve(&depositor, current - deposit);
+            }
+            Deposits::<T>::insert(crmid, (depositor, deposit));
+        }
+        Ok(())
+    }
+    // check the optional "parentcontract" of a derivative work: the parent must be on chain, the passthrough 1..100 and
+    // the link must not create a cycle. It returns the parent crmid with the passthrough percentage
+    fn check_parent_contract(crmdata: &[u8], crmid: T::CrmId) -> Result<Option<(T::CrmId, Percent)>, DispatchError> {
+        let parentcontract = json_get_object(crmdata.to_vec(), "parentcontract".as_bytes().to_vec());
+        if parentcontract.is_empty() {
+            return Ok(None);
+        }
+        let parent = vecu8_to_crmid::<T::CrmId>(json_get_value(parentcontract.clone(), "id".as_bytes().to_vec()));
+        let passthrough = vecu8_to_u32(json_get_value(parentcontract, "passthrough".as_bytes().to_vec()));
+        ensure!(passthrough > 0, Error::<T>::InvalidPassthrough);
+        let passthrough = u32_to_percent(passthrough).ok_or(Error::<T>::InvalidPassthrough)?;
+        ensure!(CrmData::<T>::contains_key(&parent), Error::<T>::ParentContractNotFound);
+        // walk up the parents, the contract must not be found
+        let mut p = parent;
+        let mut depth = 1;
+        loop {
+            ensure!(p != crmid, Error::<T>::CyclicParentContract);
+            match DerivativeOf::<T>::get(&p) {
+                Some((next, _)) => p = next,
+                None => break,
+            }
+            depth += 1;
+            ensure!(depth <= MAX_PARENT_DEPTH, Error::<T>::ParentChainTooDeep);
+        }
+        Ok(Some((parent, passthrough)))
+    }
+    // store the parent of a derivative work, the entry is removed when the contract has no parent
+    fn store_parent_contract(crmid: T::CrmId, parent: Option<(T::CrmId, Percent)>) {
+        match parent {
+            Some(parent) => DerivativeOf::<T>::insert(crmid, parent),
+            None => DerivativeOf::<T>::remove(crmid),
+        }
+    }
+    // store the territories of a contract, the entry is removed for worldwide rights
+    fn store_territories(crmid: T::CrmId, territories: Vec<[u8; 2]>) {
+        if territories.is_empty() {
+            CrmTerritories::<T>::remove(crmid);
+        } else {
+            CrmTerritories::<T>::insert(crmid, territories);
+        }
+    }
+    /// True when the rights of the contract cover the territory, the contracts without territories are worldwide.
+    pub fn is_territory_covered(crmid: T::CrmId, territory: [u8; 2]) -> bool {
+        let territories = CrmTerritories::<T>::get(crmid);
+        territories.is_empty() || territories.contains(&territory)
+    }
+    /// True when the account is the owner or an editor of the contract.
+    pub fn is_owner_or_editor(crmid: T::CrmId, who: &T::AccountId) -> bool {
+        Self::is_owner(crmid, who) || CrmEditors::<T>::get(&crmid).contains(who)
+    }
+    /// True when the account is the owner of the contract or one of the owners of a co-owned contract.
+    pub fn is_owner(crmid: T::CrmId, who: &T::AccountId) -> bool {
+        CrmOwner::<T>::get(&crmid).as_ref() == Some(who)
+            || CrmOwnerSets::<T>::get(&crmid).map_or(false, |ownerset| ownerset.owners.contains(who))
+    }
+    // check the json type of the values of the main fields: the hashes are strings and the shares and quorums are
+    // numbers, bare or quoted. With StrictSchema the fields not in CRMDATA_FIELDS are rejected.
+    // The fields missing and the values out of range are rejected by the checks that follow
+    fn check_field_types(crmdata: &[u8]) -> dispatch::DispatchResult {
+        let fields = match json_get_fields(crmdata) {
+            Some(fields) => fields,
+            None => return Ok(()),
+        };
+        for (key, value) in fields.iter() {
+            ensure!(
+                !T::StrictSchema::get() || CRMDATA_FIELDS.iter().any(|field| field.as_bytes() == &key[..]),
+                Error::<T>::UnknownField
+            );
+            let error = match &key[..] {
+                b"ipfshash" if !json_is_string(value) => Error::<T>::IpfsHashNotString,
+                b"ipfshashprivate" if !json_is_string(value) && value.first() != Some(&b'[') => {
+                    Error::<T>::IpfsHashPrivateNotStringOrArray
+                }
+                b"globalquorum" if !json_is_number(value) => Error::<T>::GlobalQuorumNotNumber,
+                b"mastershare" if !json_is_number(value) => Error::<T>::MasterShareNotNumber,
+                b"masterquorum" if !json_is_number(value) => Error::<T>::MasterQuorumNotNumber,
+                b"compositionshare" if !json_is_number(value) => Error::<T>::CompositionShareNotNumber,
+                b"compositionquorum" if !json_is_number(value) => Error::<T>::CompositionQuorumNotNumber,
+                b"othercontractsshare" if !json_is_number(value) => Error::<T>::OtherContractsShareNotNumber,
+                b"othercontractsquorum" if !json_is_number(value) => Error::<T>::OtherContractsQuorumNotNumber,
+                b"crodwfundingshares" if !json_is_number(value) => Error::<T>::CrowdFundingSharesNotNumber,
+                _ => continue,
+            };
+            return Err(error.into());
+        }
+        Ok(())
+    }
+    // check the fields of the crm data of a contract on chain, the quorums of the groups owned by a single holder are checked
+    // on the holders stored and the hashes out of the bounds stored before them are kept unchanged
+    fn check_crmdata(crmdata: &[u8], crmid: T::CrmId) -> dispatch::DispatchResult {
+        Self::check_single_holder_quorum(crmdata, &CrmMasterData::<T>::get(&crmid).unwrap_or_default(), "masterquorum")?;
+        Self::check_single_holder_quorum(crmdata, &CrmCompositionData::<T>::get(&crmid).unwrap_or_default(), "compositionquorum")?;
+        Self::check_crmdata_fields(crmdata, crmid, CrmOwner::<T>::get(&crmid), &Self::legacy_hashes(crmid))?;
+        Ok(())
+    }
+    // check the fields of the crm data of a new or stored contract: ipfs hashes, quorums, shares, type and crowdfunding
+    // campaign of the owner. The legacy hashes are accepted out of the bounds. It returns the territories and the parent contract
+    fn check_crmdata_fields(
+        crmdata: &[u8],
+        crmid: T::CrmId,
+        owner: Option<T::AccountId>,
+        legacy: &[Vec<u8>],
+    ) -> Result<(Vec<[u8; 2]>, Option<(T::CrmId, Percent)>), DispatchError> {
+        // check for duplicated keys
+        ensure!(!json_has_duplicate_keys(crmdata), Error::<T>::DuplicateJsonKey);
+        // check the types of the values
+        Self::check_field_types(crmdata)?;
+        // check ipfshash
+        let jsf=crmdata.to_vec();
+        let ipfshash=json_get_value(jsf,"ipfshash".as_bytes().to_vec());
+        ensure!(Self::ipfshash_in_bounds(&ipfshash) || legacy.contains(&ipfshash), Error::<T>::InvalidIpfsHash); //check the length of the Ipfs Hash
+        ensure!(!T::StrictCid::get() || cid_multihash(&ipfshash).is_some(), Error::<T>::InvalidIpfsHash);
+        // check ipfshash private
+        let jsfp=crmdata.to_vec();
+        let ipfshashprivate=json_get_value(jsfp,"ipfshashprivate".as_bytes().to_vec());
+        ensure!(ipfshashprivate.len() as u32 >= T::MinIpfsHashLength::get() || !legacy.is_empty(), Error::<T>::InvalidIpfsHashPrivate); //check minimum length for the Ipfs Hash Private
+        // check the number of private hashes
+        let privatehashes=json_get_array(crmdata.to_vec(),"ipfshashprivate".as_bytes().to_vec());
+        ensure!(privatehashes.len() as u32 <= T::MaxPrivateHashes::get(), Error::<T>::TooManyPrivateHashes);
+        // check the private hashes are distinct and different from the public hash
+        Self::check_private_hashes(crmdata, &privatehashes, legacy)?;
+        // check globalquorum
+        let jsgq=crmdata.to_vec();
+        let globalquorum=json_get_value(jsgq,"globalquorum".as_bytes().to_vec());
+        let globalquorumvalue=vecu8_to_u32(globalquorum);
+        ensure!(globalquorumvalue > 0 && globalquorumvalue <= 100, Error::<T>::InvalidGlobalQuorum);
+        // check master shares
+        let sharescale=json_get_share_scale(crmdata.to_vec()).ok_or(Error::<T>::InvalidShareScale)?;
+        let jsms=crmdata.to_vec();
+        let mastershare=json_get_value(jsms,"mastershare".as_bytes().to_vec());
+        let mastersharevalue=vecu8_to_u32(mastershare);
+        ensure!(mastersharevalue > 0 && mastersharevalue <= sharescale, Error::<T>::InvalidMasterShare); //check Master Shares  that must be > 0
+        // check master quorum
+        let jsmq=crmdata.to_vec();
+        let masterquorum=json_get_value(jsmq,"masterquorum".as_bytes().to_vec());
+        let masterquorumvalue=vecu8_to_u32(masterquorum);
+        ensure!(masterquorumvalue > 0 && masterquorumvalue <= 100, Error::<T>::InvalidMasterQuorum); //check Master Quorum that must be > 0
+        // check composition shares
+        let jscs=crmdata.to_vec();
+        let compositionshare=json_get_value(jscs,"compositionshare".as_bytes().to_vec());
+        let compositionsharevalue=vecu8_to_u32(compositionshare);
+        ensure!(compositionsharevalue > 0 && compositionsharevalue <= sharescale, Error::<T>::InvalidCompositionShare); //check Composition Shares  that must be > 0
+        // check composition quorum
+        let jscq=crmdata.to_vec();
+        let compositionquorum=json_get_value(jscq,"compositionquorum".as_bytes().to_vec());
+        let compositionquorumvalue=vecu8_to_u32(compositionquorum);
+        ensure!(compositionquorumvalue > 0 && compositionquorumvalue <= 100, Error::<T>::InvalidCompositionQuorum); //check Composition Quorum  that must be > 0
+        // check othercontracts shares
+        let jsos=crmdata.to_vec();
+        let othercontractsshare=json_get_value(jsos,"othercontractsshare".as_bytes().to_vec());
+        let othercontractssharevalue=vecu8_to_u32(othercontractsshare);
+        ensure!(othercontractssharevalue <= sharescale, Error::<T>::InvalidOtherContractsShare); 	//check Composition Shares that must be <= the scale
+        // check other contracts quorum
+        let jsoq=crmdata.to_vec();
+        let othercontractsquorum=json_get_value(jsoq,"othercontractsquorum".as_bytes().to_vec());
+        let othercontractsquorumvalue=vecu8_to_u32(othercontractsquorum);
+        ensure!(othercontractsquorumvalue <= 100, Error::<T>::InvalidOtherContractsQuorum); //check other Contracts Quorum that must be <=100
+        // check crowdfundingshare
+        let jscf=crmdata.to_vec();
+        let crodwfundingshare=json_get_value(jscf,"crodwfundingshares".as_bytes().to_vec());
+        let crodwfundingsharevalue=vecu8_to_u32(crodwfundingshare);
+        ensure!(crodwfundingsharevalue <= sharescale, Error::<T>::InvalidCrowdFundingshares); //check Crowd Funding Shares that must be <= the scale
+        // check donation share and account (optional)
+        let donationsharevalue = Self::check_donation(crmdata, sharescale)?;
+        // check that the total shares are = 100 (10000 in basis points)
+        let totalshares=mastersharevalue+compositionsharevalue+othercontractssharevalue+crodwfundingsharevalue+donationsharevalue;
+        ensure!(totalshares == sharescale, Error::<T>::InvalidTotalShares); //check total shares that must be 100 (10000 in basis points)
+        // check the type of contract (optional)
+        ensure!(json_check_contract_type(crmdata.to_vec()), Error::<T>::InvalidContractType);
+        // check the rate per play (optional)
+        ensure!(json_check_rate_per_play(crmdata.to_vec()), Error::<T>::InvalidRatePerPlay);
+        // check the mechanical license fee (optional)
+        ensure!(json_check_mechanical_license_fee(crmdata.to_vec()), Error::<T>::InvalidMechanicalLicenseFee);
+        // check the territories (optional)
+        let territories = Self::check_territories(crmdata)?;
+        // check the withholding percent (optional)
+        ensure!(json_check_withholding_percent(crmdata.to_vec()), Error::<T>::InvalidWithholdingPercent);
+        // check the isrc and iswc codes (optional)
+        ensure!(json_get_isrc(crmdata.to_vec()).is_some(), Error::<T>::InvalidIsrc);
+        ensure!(json_get_iswc(crmdata.to_vec()).is_some(), Error::<T>::InvalidIswc);
+        // check the parent contract of a derivative work (optional)
+        let parent = Self::check_parent_contract(crmdata, crmid)?;
+        // check the crowdfunding campaign linked when the crowdfunding share is > 0
+        Self::check_crowdfunding_campaign(crmdata, owner, crmid)?;
+        Ok((territories, parent))
+    }
+    // check the validity of a json and its depth, in strict mode the trailing commas are rejected
+    fn check_json(j: Vec<u8>) -> dispatch::DispatchResult {
+        ensure!(!(T::StrictJson::get() && json_has_trailing_comma(&j)), Error::<T>::InvalidJson);
+        ensure!(json_depth(&j) <= T::MaxJsonDepth::get(), Error::<T>::JsonTooDeep);
+        ensure!(json_check_validity(j), Error::<T>::InvalidJson);
+        Ok(())
+    }
+    // check the optional "donationshare" and "donationaccount" (SS58 format), the account is required when the share is > 0
+    // and the share when the account is set. It returns the donation share in the scale of the contract
+    fn check_donation(crmdata: &[u8], sharescale: u32) -> Result<u32, DispatchError> {
+        let share = vecu8_to_u32(json_get_value(crmdata.to_vec(), "donationshare".as_bytes().to_vec()));
+        ensure!(share <= sharescale, Error::<T>::InvalidDonationShare);
+        let account = json_get_value(crmdata.to_vec(), "donationaccount".as_bytes().to_vec());
+        if account.is_empty() {
+            ensure!(share == 0, Error::<T>::MissingDonationAccount);
+        } else {
+            ensure!(Self::decode_ss58_account(&account).is_some(), Error::<T>::InvalidDonationAccount);
+            ensure!(share > 0, Error::<T>::InvalidDonationShare);
+        }
+        Ok(share)
+    }
+    // check every private hash (at the least one, alphanumeric, in the bounds of the ipfs hashes unless it's a legacy hash
+    // of the contract) and that they are distinct and different from the public "ipfshash"
+    fn check_private_hashes(crmdata: &[u8], privatehashes: &[Vec<u8>], legacy: &[Vec<u8>]) -> dispatch::DispatchResult {
+        ensure!(!privatehashes.is_empty(), Error::<T>::InvalidIpfsHashPrivate);
+        for h in privatehashes.iter() {
+            ensure!(Self::ipfshash_in_bounds(h) || legacy.contains(h), Error::<T>::InvalidIpfsHashPrivate);
+            ensure!(h.iter().all(|b| b.is_ascii_alphanumeric()), Error::<T>::InvalidIpfsHashPrivate);
+            ensure!(!T::StrictCid::get() || cid_multihash(h).is_some(), Error::<T>::InvalidIpfsHashPrivate);
+        }
+        // the CIDs are compared by their multihash, the same content in CIDv0 and CIDv1 is a duplicate
+        let key = |h: &[u8]| cid_multihash(h).unwrap_or_else(|| h.to_vec());
+        let ipfshash = key(&json_get_value(crmdata.to_vec(), "ipfshash".as_bytes().to_vec()));
+        let privatehashes: Vec<Vec<u8>> = privatehashes.iter().map(|h| key(h)).collect();
+        ensure!(!privatehashes.iter().any(|h| *h == ipfshash), Error::<T>::DuplicatePublicPrivateHash);
+        for (i, h) in privatehashes.iter().enumerate() {
+            ensure!(!privatehashes[i + 1..].contains(h), Error::<T>::DuplicatePrivateHash);
+        }
+        Ok(())
+    }
+    // true when the length of an ipfs hash is between MinIpfsHashLength and MaxIpfsHashLength
+    fn ipfshash_in_bounds(hash: &[u8]) -> bool {
+        let len = hash.len() as u32;
+        len >= T::MinIpfsHashLength::get() && len <= T::MaxIpfsHashLength::get()
+    }
+    // the public and private hashes stored of a contract marked by the migration to the version 5, empty for the others
+    fn legacy_hashes(crmid: T::CrmId) -> Vec<Vec<u8>> {
+        if !LegacyIpfsHashes::<T>::get(crmid) {
+            return Vec::new();
+        }
+        let crmdata = CrmData::<T>::get(&crmid).unwrap_or_default();
+        let mut hashes = json_get_array(crmdata.clone(), "ipfshashprivate".as_bytes().to_vec());
+        hashes.push(json_get_value(crmdata, "ipfshash".as_bytes().to_vec()));
+        hashes
+    }
+    // check the crowdfunding campaign referenced by "crowdfounders" when the crowdfunding share is > 0 and the field is set,
+    // it must be on chain, owned by the owner of the contract and not linked to a different contract
+    fn check_crowdfunding_campaign(crmdata: &[u8], owner: Option<T::AccountId>, crmid: T::CrmId) -> dispatch::DispatchResult {
+        let share = vecu8_to_u32(json_get_value(crmdata.to_vec(), "crodwfundingshares".as_bytes().to_vec()));
+        if share == 0 {
+            return Ok(());
+        }
+        // the campaign can be attached after the creation of the contract
+        let crowdfounders = json_get_value(crmdata.to_vec(), "crowdfounders".as_bytes().to_vec());
+        if crowdfounders.is_empty() {
+            return Ok(());
+        }
+        let campaignid = vecu8_to_u32(crowdfounders);
+        let c = Campaigns::<T>::get(&campaignid).ok_or(Error::<T>::CampaignNotFound)?;
+        ensure!(owner == Some(c.owner), Error::<T>::CampaignNotOwned);
+        if let Some(linked) = CampaignToCrm::<T>::get(&campaignid) {
+            ensure!(linked == crmid, Error::<T>::CampaignAlreadyLinked);
+        }
+        Ok(())
+    }
+    // link the crowdfunding campaign referenced by "crowdfounders" to the contract when the crowdfunding share is > 0
+    fn link_crowdfunding_campaign(crmid: T::CrmId, crmdata: &[u8]) {
+        let share = vecu8_to_u32(json_get_value(crmdata.to_vec(), "crodwfundingshares".as_bytes().to_vec()));
+        let campaignid = vecu8_to_u32(json_get_value(crmdata.to_vec(), "crowdfounders".as_bytes().to_vec()));
+        if share > 0 && campaignid > 0 {
+            CampaignToCrm::<T>::insert(campaignid, crmid);
+        }
+    }
+    // remove the link between the contract and its crowdfunding campaign
+    fn unlink_crowdfunding_campaign(crmid: T::CrmId) {
+        if let Some(campaignid) = Self::crm_campaign(crmid) {
+            if CampaignToCrm::<T>::get(&campaignid) == Some(crmid) {
+                CampaignToCrm::<T>::remove(campaignid);
+            }
+        }
+    }
+    // credit the royalties paid by the payer to the members of a contract, the funds are moved to the escrow account of the contract
+    fn do_pay_royalties(
+        sender: T::AccountId,
+        crmid: T::CrmId,
+        amount: BalanceOf<T>,
+        asset: Option<AssetIdOf<T>>,
+        existence: ExistenceRequirement,
+    ) -> dispatch::DispatchResult {
+        Self::pay_royalties_at_depth(sender, crmid, amount, asset, existence, 0)
+    }
+    // credit the royalties of a contract at a depth of the chain of the parents, the passthrough stops at MAX_PARENT_DEPTH
+    fn pay_royalties_at_depth(
+        sender: T::AccountId,
+        crmid: T::CrmId,
+        amount: BalanceOf<T>,
+        asset: Option<AssetIdOf<T>>,
+        existence: ExistenceRequirement,
+        depth: u32,
+    ) -> dispatch::DispatchResult {
+        if let Some(asset) = asset {
+            ensure!(T::Assets::exists(asset), Error::<T>::UnknownAsset);
+        }
+        // the passthrough of a derivative work is paid first through the parent contract, the parents removed are skipped
+        let mut amount = amount;
+        let parent = if depth < MAX_PARENT_DEPTH { DerivativeOf::<T>::get(&crmid) } else { None };
+        if let Some((parent, passthrough)) = parent {
+            let slice = passthrough.mul_floor(amount);
+            if !slice.is_zero() && CrmData::<T>::contains_key(&parent) {
+                Self::pay_royalties_at_depth(sender.clone(), parent, slice, asset, existence, depth + 1)?;
+                Self::deposit_event(Event::RoyaltiesPassedThrough(crmid, parent, slice));
+                amount = amount.saturating_sub(slice);
+            }
+        }
+        let recipients = Self::royalty_recipients(crmid)?;
+        let donation = Self::donation(crmid)?;
+        // split the amount by the weights of the recipients, the donation share is the last part
+        let mut weights: Vec<u128> = recipients.iter().map(|(_, w)| *w).collect();
+        if let Some((_, share)) = donation {
+            weights.push(share as u128 * 10_000);
+        }
+        let (mut parts, dust) = largest_remainder_split(amount.unique_saturated_into(), &weights, ROYALTY_WEIGHT_TOTAL);
+        let dust: BalanceOf<T> = dust.unique_saturated_into();
+        let paid = amount.saturating_sub(dust);
+        // the donation is transferred directly to the donation account
+        let mut donated: BalanceOf<T> = Zero::zero();
+        if let Some((account, _)) = donation {
+            donated = parts.pop().unwrap_or(0).unique_saturated_into();
+            if !donated.is_zero() {
+                match asset {
+                    None => T::Currency::transfer(&sender, &account, donated, existence)?,
+                    Some(asset) => T::Assets::transfer(asset, &sender, &account, donated)?,
+                }
+                Self::deposit_event(Event::DonationPaid(sender.clone(), crmid, account, donated));
+            }
+        }
+        let credited = paid.saturating_sub(donated);
+        // move the total credited to the escrow account, the transfers to the members are done by their claims
+        match asset {
+            None => {
+                if !credited.is_zero() {
+                    T::Currency::transfer(&sender, &Self::contract_account_id(crmid), credited, existence)?;
+                }
+                if !dust.is_zero() {
+                    let imbalance = T::Currency::withdraw(&sender, dust, WithdrawReasons::TRANSFER, existence)?;
+                    T::DustHandler::on_unbalanced(imbalance);
+                }
+            }
+            // the dust of the assets is left to the payer
+            Some(asset) => {
+                if !credited.is_zero() {
+                    T::Assets::transfer(asset, &sender, &Self::contract_account_id(crmid), credited)?;
+                }
+            }
+        }
+        for ((recipient, _), part) in recipients.into_iter().zip(parts) {
+            let credit: BalanceOf<T> = part.unique_saturated_into();
+            if credit.is_zero() {
+                continue;
+            }
+            let balance = Self::royalty_balance(crmid, asset, &recipient)
+                .checked_add(&credit)
+                .ok_or(Error::<T>::RoyaltyOverflow)?;
+            Self::set_royalty_balance(crmid, asset, &recipient, balance);
+            match asset {
+                None => Self::deposit_event(Event::RoyaltyCredited(recipient, crmid, credit)),
+                Some(asset) => Self::deposit_event(Event::AssetRoyaltyCredited(recipient, crmid, asset, credit)),
+            }
+        }
+        match asset {
+            None => Self::deposit_event(Event::RoyaltiesPaid(sender, crmid, credited, dust)),
+            Some(asset) => Self::deposit_event(Event::AssetRoyaltiesPaid(sender, crmid, asset, credited)),
+        }
+        Ok(())
+    }
+    // donation account of a contract with the donation share in basis points, None without a donation share
+    fn donation(crmid: T::CrmId) -> Result<Option<(T::AccountId, u32)>, DispatchError> {
+        let crmdata = CrmData::<T>::get(&crmid).ok_or(Error::<T>::InvalidContractId)?;
+        let share = json_get_share_bps(crmdata.clone(), "donationshare").ok_or(Error::<T>::InvalidDonationShare)?;
+        if share == 0 {
+            return Ok(None);
+        }
+        let account = json_get_value(crmdata, "donationaccount".as_bytes().to_vec());
+        let account = Self::decode_ss58_account(&account).ok_or(Error::<T>::InvalidDonationAccount)?;
+        Ok(Some((account, share)))
+    }
+    // part of a claim withheld by the "withholdingpercent" of a contract with the account receiving it,
+    // it fails when the percent is set without the withholding account
+    fn withholding(crmid: T::CrmId, amount: BalanceOf<T>) -> Result<Option<(T::AccountId, BalanceOf<T>)>, DispatchError> {
+        let percent = CrmData::<T>::get(&crmid)
+            .map(|d| vecu8_to_u32(json_get_value(d, "withholdingpercent".as_bytes().to_vec())))
+            .unwrap_or(0);
+        if percent == 0 {
+            return Ok(None);
+        }
+        let account = WithholdingAccounts::<T>::get(crmid).ok_or(Error::<T>::WithholdingAccountNotSet)?;
+        let percent = u32_to_percent(percent).ok_or(Error::<T>::InvalidWithholdingPercent)?;
+        Ok(Some((account, percent.mul_floor(amount))))
+    }
+    // transfer the royalties from the escrow account of a contract in the native currency (None) or in an asset
+    fn transfer_from_escrow(
+        crmid: T::CrmId,
+        asset: Option<AssetIdOf<T>>,
+        dest: &T::AccountId,
+        amount: BalanceOf<T>,
+    ) -> dispatch::DispatchResult {
+        if amount.is_zero() {
+            return Ok(());
+        }
+        match asset {
+            None => T::Currency::transfer(&Self::contract_account_id(crmid), dest, amount, ExistenceRequirement::AllowDeath),
+            Some(asset) => T::Assets::transfer(asset, &Self::contract_account_id(crmid), dest, amount),
+        }
+    }
+    // royalties of a contract credited to a member in the native currency (None) or in an asset
+    fn royalty_balance(crmid: T::CrmId, asset: Option<AssetIdOf<T>>, who: &T::AccountId) -> BalanceOf<T> {
+        match asset {
+            None => RoyaltyBalances::<T>::get(crmid, who),
+            Some(asset) => AssetRoyaltyBalances::<T>::get(crmid, (asset, who.clone())),
+        }
+    }
+    // store the royalties of a contract credited to a member, the entry is removed when zero
+    fn set_royalty_balance(crmid: T::CrmId, asset: Option<AssetIdOf<T>>, who: &T::AccountId, balance: BalanceOf<T>) {
+        match (asset, balance.is_zero()) {
+            (None, true) => RoyaltyBalances::<T>::remove(crmid, who),
+            (None, false) => RoyaltyBalances::<T>::insert(crmid, who, balance),
+            (Some(asset), true) => AssetRoyaltyBalances::<T>::remove(crmid, (asset, who.clone())),
+            (Some(asset), false) => AssetRoyaltyBalances::<T>::insert(crmid, (asset, who.clone()), balance),
+        }
+    }
+    // recipients of the royalties of a contract with their weight out of ROYALTY_WEIGHT_TOTAL: the members of master and composition,
+    // the owners of the other contracts and the crowdfunding backers (by the basis points of their certificates)
+    fn royalty_recipients(crmid: T::CrmId) -> Result<Vec<(T::AccountId, u128)>, DispatchError> {
+        let crmdata = CrmData::<T>::get(&crmid).ok_or(Error::<T>::InvalidContractId)?;
+        // shares in basis points, the weights are share (bps) x member percentage (%) x 100
+        let share = |key: &str| json_get_share_bps(crmdata.clone(), key).ok_or(Error::<T>::InvalidJson);
+        let (mastershare, compositionshare) = (share("mastershare")?, share("compositionshare")?);
+        let (othercontractsshare, crowdfundingshare) = (share("othercontractsshare")?, share("crodwfundingshares")?);
+        let mut recipients: Vec<(T::AccountId, u128)> = Vec::new();
+        // master and composition members with their percentage
+        for (share, group, data) in [
+            (mastershare, HolderGroup::Master, CrmMasterData::<T>::get(&crmid).unwrap_or_default()),
+            (compositionshare, HolderGroup::Composition, CrmCompositionData::<T>::get(&crmid).unwrap_or_default()),
+        ].iter() {
+            if *share == 0 {
+                continue;
+            }
+            // the holders set by the owner replace the accounts of the json data
+            let holders = CrmHolders::<T>::get(crmid, group);
+            let members = if holders.is_empty() {
+                Self::group_members(data.clone())
+            } else {
+                holders.into_iter().map(|(account, percentage)| (account, percentage as u32)).collect()
+            };
+            ensure!(!members.is_empty(), Error::<T>::GroupMembersNotSet);
+            for (account, percentage) in members {
+                recipients.push((account, *share as u128 * percentage as u128 * 100));
+            }
+        }
+        // other contracts, the part is paid to the owner of each contract
+        if othercontractsshare > 0 {
+            let data = CrmOtherContractsData::<T>::get(&crmid).unwrap_or_default();
+            let mut x = 0;
+            loop {
+                let jr = json_get_recordvalue(data.clone(), x);
+                if jr.is_empty() {
+                    break;
+                }
+                let id = vecu8_to_crmid::<T::CrmId>(json_get_value(jr.clone(), "id".as_bytes().to_vec()));
+                let percentage = vecu8_to_u32(json_get_value(jr, "percentage".as_bytes().to_vec()));
+                let owner = CrmOwner::<T>::get(&id).ok_or(Error::<T>::GroupMembersNotSet)?;
+                let percentage = u32_to_percent(percentage).ok_or(Error::<T>::MissingOtherContractsPercentage)?;
+                recipients.push((owner, othercontractsshare as u128 * percentage.deconstruct() as u128 * 100));
+                x += 1;
+            }
+            ensure!(x > 0, Error::<T>::GroupMembersNotSet);
+        }
+        // crowdfunding backers, by the basis points of the certificates
+        if crowdfundingshare > 0 {
+            let campaignid = Self::crm_campaign(crmid).ok_or(Error::<T>::GroupMembersNotSet)?;
+            let before = recipients.len();
+            for (backer, certificate) in CrowdfundingCertificates::<T>::iter_prefix(campaignid) {
+                recipients.push((backer, crowdfundingshare as u128 * certificate.basis_points as u128));
+            }
+            ensure!(recipients.len() > before, Error::<T>::GroupMembersNotSet);
+        }
+        Ok(recipients)
+    }
+    // accounts and percentages of the members of a master or composition group
+    fn group_members(data: Vec<u8>) -> Vec<(T::AccountId, u32)> {
+        let mut members = Vec::new();
+        let mut x = 0;
+        loop {
+            let jr = json_get_recordvalue(data.clone(), x);
+            if jr.is_empty() {
+                break;
+            }
+            let account = json_get_value(jr.clone(), "account".as_bytes().to_vec());
+            let percentage = vecu8_to_u32(json_get_value(jr, "percentage".as_bytes().to_vec()));
+            if let Some(accountid) = Self::decode_account(&account) {
+                members.push((accountid, percentage.min(100)));
+            }
+            x += 1;
+        }
+        members
+    }
+    // convert an account in hex format (0x...) from the json data to AccountId
+    fn decode_account(account: &[u8]) -> Option<T::AccountId> {
+        if account.len() < 3 {
+            return None;
+        }
+        let accountstr: &str = str::from_utf8(&account[3..]).ok()?;
+        let buffer: [u8; 32] = hex::FromHex::from_hex(&accountstr).ok()?;
+        T::AccountId::decode(&mut &buffer[..]).ok()
+    }
+    // convert an account in SS58 format from the json data to AccountId, the checksum and the length are verified
+    fn decode_ss58_account(account: &[u8]) -> Option<T::AccountId> {
+        let data = base58_decode(account)?;
+        // address type of 1 byte (0..63) or 2 bytes (64..16383), public key of 32 bytes and checksum of 2 bytes
+        let prefixlen = match data.first()? {
+            0..=63 => 1,
+            64..=127 => 2,
+            _ => return None,
+        };
+        if data.len() != prefixlen + 34 {
+            return None;
+        }
+        let mut preimage = b"SS58PRE".to_vec();
+        preimage.extend_from_slice(&data[..prefixlen + 32]);
+        let checksum = sp_io::hashing::blake2_512(&preimage);
+        if checksum[..2] != data[prefixlen + 32..] {
+            return None;
+        }
+        T::AccountId::decode(&mut &data[prefixlen..prefixlen + 32]).ok()
+    }
+    /// The crmids of the contracts owned by an account, in ascending order and at the most MaxListReturn: the next page
+    /// is read with get_crm_ids_from the last crmid returned + 1.
+    pub fn get_crm_ids(account: T::AccountId) -> Vec<T::CrmId> {
+        Self::get_crm_ids_from(account, Zero::zero())
+    }
+    /// The crmids >= from of the contracts owned by an account, in ascending order and at the most MaxListReturn.
+    pub fn get_crm_ids_from(account: T::AccountId, from: T::CrmId) -> Vec<T::CrmId> {
+        let mut ids = Self::owned_crmids(&account, from);
+        ids.truncate(T::MaxListReturn::get() as usize);
+        ids
+    }
+    // all the crmids >= from of the contracts owned by an account, in ascending order
+    fn owned_crmids(account: &T::AccountId, from: T::CrmId) -> Vec<T::CrmId> {
+        let mut ids: Vec<T::CrmId> = CrmOwner::<T>::iter()
+            .filter(|(crmid, owner)| *crmid >= from && owner == account)
+            .map(|(crmid, _)| crmid)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+    // move the storage kept under the prefix of decl_storage to the name of the pallet in the runtime, the keys are
+    // unchanged after the prefix. It runs before the versioned migrations, the version is stored under the prefix too
+    fn migrate_storage_prefix() -> Weight {
+        let name = match <<T as frame_system::Config>::PalletInfo as PalletInfo>::name::<Self>() {
+            Some(name) => name,
+            None => return 0,
+        };
+        if name.as_bytes() == OLD_STORAGE_PREFIX {
+            return 0;
+        }
+        let old_prefix = sp_io::hashing::twox_128(OLD_STORAGE_PREFIX);
+        let new_prefix = sp_io::hashing::twox_128(name.as_bytes());
+        let mut moved: u64 = 0;
+        let mut key = old_prefix.to_vec();
+        while let Some(next) = sp_io::storage::next_key(&key) {
+            if !next.starts_with(&old_prefix) {
+                break;
+            }
+            if let Some(value) = sp_io::storage::get(&next) {
+                let mut new_key = new_prefix.to_vec();
+                new_key.extend_from_slice(&next[old_prefix.len()..]);
+                sp_io::storage::set(&new_key, &value);
+            }
+            sp_io::storage::clear(&next);
+            moved += 1;
+            key = next;
+        }
+        if moved > 0 {
+            debug::info!("Crm storage prefix migration: {} keys moved under {}", moved, name);
+        }
+        T::DbWeight::get().reads_writes(moved + 1, moved * 2)
+    }
+    // decode the crm data stored before CrmContracts, the contracts that cannot be decoded are skipped
+    fn migrate_to_v1() -> Weight {
+        let mut count: u64 = 0;
+        let mut decoded: u64 = 0;
+        for (crmid, crmdata) in CrmData::<T>::iter() {
+            count += 1;
+            match json_get_contract(crmdata) {
+                Some(contract) => {
+                    CrmContracts::<T>::insert(crmid, contract);
+                    decoded += 1;
+                }
+                None => debug::warn!("CrmContracts migration: crm data of contract {:?} cannot be decoded, skipped", crmid),
+            }
+        }
+        CrmStorageVersion::<T>::put(1);
+        debug::info!("CrmContracts migration: {} contracts decoded, {} skipped", decoded, count - decoded);
+        T::DbWeight::get().reads_writes(count, decoded + 1)
+    }
+    // initialize the counters of the contracts, the contracts ever created are the ones stored and the tombstones
+    fn migrate_to_v2() -> Weight {
+        let active = CrmData::<T>::iter().count() as u32;
+        let deleted = CrmDeleted::<T>::iter().count() as u32;
+        TotalCrms::<T>::put(active);
+        TotalContracts::<T>::put(active.saturating_add(deleted).max(TotalContracts::<T>::get()));
+        CrmStorageVersion::<T>::put(2);
+        debug::info!("Counters migration: {} contracts on chain, {} removed", active, deleted);
+        T::DbWeight::get().reads_writes(active.saturating_add(deleted) as u64 + 1, 3)
+    }
+    // build the index of the "ipfshash", when contracts share a hash the lowest crmid is kept
+    fn migrate_to_v3() -> Weight {
+        let mut count: u64 = 0;
+        let mut indexed: u64 = 0;
+        for (crmid, crmdata) in CrmData::<T>::iter() {
+            count += 1;
+            let hash = json_get_value(crmdata, "ipfshash".as_bytes().to_vec());
+            if hash.is_empty() {
+                continue;
+            }
+            match IpfsIndex::<T>::get(&hash) {
+                // already indexed, the migration is run again
+                Some(other) if other == crmid => {}
+                Some(other) if other < crmid => {
+                    debug::warn!("IpfsIndex migration: contract {:?} has the ipfshash of contract {:?}, skipped", crmid, other)
+                }
+                Some(other) => {
+                    debug::warn!("IpfsIndex migration: contract {:?} has the ipfshash of contract {:?}, skipped", other, crmid);
+                    IpfsIndex::<T>::insert(&hash, crmid);
+                }
+                None => {
+                    IpfsIndex::<T>::insert(&hash, crmid);
+                    indexed += 1;
+                }
+            }
+        }
+        CrmStorageVersion::<T>::put(3);
+        debug::info!("IpfsIndex migration: {} hashes indexed from {} contracts", indexed, count);
+        T::DbWeight::get().reads_writes(count * 2, count + 1)
+    }
+    // record the block of the migration as the last change of the contracts on chain, their version is kept (0 for the
+    // contracts created before the versions) and the creation is not set since it starts the update lock period
+    fn migrate_to_v4() -> Weight {
+        let now = <frame_system::Module<T>>::block_number();
+        let mut count: u64 = 0;
+        let mut written: u64 = 0;
+        let crmids = CrmData::<T>::iter().map(|(crmid, _)| crmid).chain(CrmCommitments::<T>::iter().map(|(crmid, _)| crmid));
+        for crmid in crmids {
+            count += 1;
+            if !CrmUpdatedAt::<T>::contains_key(crmid) {
+                CrmUpdatedAt::<T>::insert(crmid, now);
+                written += 1;
+            }
+        }
+        CrmStorageVersion::<T>::put(4);
+        debug::info!("CrmUpdatedAt migration: {} contracts timestamped of {}", written, count);
+        T::DbWeight::get().reads_writes(count * 2, written + 1)
+    }
+    // mark the contracts with a public or private hash out of the bounds of the ipfs hashes, their hashes are kept
+    fn migrate_to_v5() -> Weight {
+        let mut count: u64 = 0;
+        let mut marked: u64 = 0;
+        for (crmid, crmdata) in CrmData::<T>::iter() {
+            count += 1;
+            let mut hashes = json_get_array(crmdata.clone(), "ipfshashprivate".as_bytes().to_vec());
+            hashes.push(json_get_value(crmdata, "ipfshash".as_bytes().to_vec()));
+            if hashes.iter().any(|h| !Self::ipfshash_in_bounds(h)) {
+                LegacyIpfsHashes::<T>::insert(crmid, true);
+                marked += 1;
+            }
+        }
+        CrmStorageVersion::<T>::put(5);
+        debug::info!("LegacyIpfsHashes migration: {} contracts with ipfs hashes out of the bounds of {}", marked, count);
+        T::DbWeight::get().reads_writes(count, marked + 1)
+    }
+    // move the funds held for the campaigns from the account of the pallet to the escrow accounts of the campaigns: the
+    // amount raised by the active campaigns and the contributions not refunded of the failed ones
+    fn migrate_to_v6() -> Weight {
+        let mut count: u64 = 0;
+        let mut moved: u64 = 0;
+        for (campaignid, c) in Campaigns::<T>::iter() {
+            count += 1;
+            let amount = match c.status {
+                CampaignStatus::Active => c.raised,
+                CampaignStatus::Failed => CampaignContributions::<T>::iter_prefix_values(campaignid)
+                    .fold(BalanceOf::<T>::zero(), |t, v| t.saturating_add(v)),
+                _ => continue,
+            };
+            if amount.is_zero() {
+                continue;
+            }
+            match T::Currency::transfer(&Self::account_id(), &Self::campaign_account_id(campaignid), amount, ExistenceRequirement::AllowDeath) {
+                Ok(()) => moved += 1,
+                Err(e) => debug::warn!("Campaign escrow migration: funds of campaign {} not moved: {:?}", campaignid, e),
+            }
+        }
+        CrmStorageVersion::<T>::put(6);
+        debug::info!("Campaign escrow migration: funds of {} campaigns moved of {}", moved, count);
+        T::DbWeight::get().reads_writes(count * 2, moved * 2 + 1)
+    }
+    // list the contracts of every "ipfshash" and cid, the contracts sharing a hash with the one in "ipfsIndex" were not
+    // recorded before and the index could not move to them
+    fn migrate_to_v7() -> Weight {
+        let mut count: u64 = 0;
+        let mut listed: u64 = 0;
+        let inline = CrmData::<T>::iter().map(|(crmid, crmdata)| (crmid, json_get_value(crmdata, "ipfshash".as_bytes().to_vec())));
+        let byhash = CrmCommitments::<T>::iter().map(|(crmid, commitment)| (crmid, commitment.ipfscid));
+        for (crmid, hash) in inline.chain(byhash) {
+            count += 1;
+            if hash.is_empty() {
+                continue;
+            }
+            IpfsHashContracts::<T>::insert(&hash, crmid, true);
+            if !IpfsIndex::<T>::contains_key(&hash) {
+                IpfsIndex::<T>::insert(&hash, crmid);
+            }
+            listed += 1;
+        }
+        CrmStorageVersion::<T>::put(7);
+        debug::info!("IpfsHashContracts migration: {} contracts listed of {}", listed, count);
+        T::DbWeight::get().reads_writes(count * 2, listed * 2 + 1)
+    }
+    // keep the action pending of the co-owned contracts in the list of the pending actions, every action waits for its
+    // own approvals from this version
+    fn migrate_to_v8() -> Weight {
+        let mut count: u64 = 0;
+        PendingOwnerActions::<T>::translate::<(OwnerActionOf<T>, Vec<T::AccountId>), _>(|_, pending| {
+            count += 1;
+            Some(vec![pending])
+        });
+        CrmStorageVersion::<T>::put(8);
+        debug::info!("PendingOwnerActions migration: {} pending actions moved", count);
+        T::DbWeight::get().reads_writes(count, count + 1)
+    }
+    /// The block of the creation, the block of the last change and the version of a contract, None when the contract is
+    /// not on chain. The contracts created before the timestamps have the block of the migration for both.
+    pub fn crm_timestamps(crmid: T::CrmId) -> Option<(T::BlockNumber, T::BlockNumber, u32)> {
+        let updated = CrmUpdatedAt::<T>::get(&crmid)?;
+        let created = CrmCreatedAt::<T>::get(&crmid).unwrap_or(updated);
+        Some((created, updated, CrmVersion::<T>::get(&crmid)))
+    }
+    /// The maximum number of contracts stored: the limit set by root or MaxTotalContracts.
+    pub fn contract_limit() -> u32 {
+        ContractLimitOverride::<T>::get().unwrap_or_else(T::MaxTotalContracts::get)
+    }
+    /// The changelog of a contract, the newest change first.
+    pub fn changelog(crmid: T::CrmId) -> Vec<ChangelogEntryOf<T>> {
+        let mut log = Changelog::<T>::get(&crmid);
+        log.reverse();
+        log
+    }
+    /// The owner and the crmid of the contract registering an ipfs hash, None when the hash is not registered.
+    pub fn contract_by_ipfshash(hash: Vec<u8>) -> Option<(T::AccountId, T::CrmId)> {
+        let crmid = IpfsIndex::<T>::get(&hash)?;
+        Some((CrmOwner::<T>::get(&crmid)?, crmid))
+    }
+    /// The main crm data of a contract decoded, None when the contract is not owned by the account.
+    /// It's the query of the runtime API of the pallet.
+    pub fn get_crm_contract_of(account: T::AccountId, crmid: T::CrmId) -> Option<CrmContract> {
+        if CrmOwner::<T>::get(&crmid)? != account {
+            return None;
+        }
+        CrmContracts::<T>::get(&crmid)
+    }
+    /// The main crm data of a contract owned by the account, an empty json object ("{}") when the contract is not on chain
+    /// or not owned by the account.
+    pub fn get_crmdata_or_empty(account: T::AccountId, crmid: T::CrmId) -> Vec<u8> {
+        match CrmOwner::<T>::get(&crmid) {
+            Some(owner) if owner == account => CrmData::<T>::get(&crmid).unwrap_or_else(|| b"{}".to_vec()),
+            _ => b"{}".to_vec(),
+        }
+    }
+    /// The blake2-256 hash of the main crm data of a contract owned by the account, for the off-chain backups verifying
+    /// the bytes exported (the hash of the commitment for the contracts stored by hash). None when the contract is not on
+    /// chain or not owned by the account. It's a query of the runtime API of the pallet.
+    pub fn crm_digest(account: T::AccountId, crmid: T::CrmId) -> Option<[u8; 32]> {
+        if CrmOwner::<T>::get(&crmid)? != account {
+            return None;
+        }
+        Self::stored_datahash(crmid)
+    }
+    /// The number of contracts on chain.
+    pub fn active_contracts() -> u32 {
+        TotalCrms::<T>::get()
+    }
+    /// True when the shares of the crm data stored sum to 100 (10000 in basis points), to detect the contracts stored by
+    /// earlier versions with wrong shares. None when the contract is not on chain.
+    pub fn is_shares_valid(crmid: T::CrmId) -> Option<bool> {
+        let crmdata = CrmData::<T>::get(&crmid)?;
+        let scale = match json_get_share_scale(crmdata.clone()) {
+            Some(scale) => scale,
+            None => return Some(false),
+        };
+        let total: u32 = ["mastershare", "compositionshare", "othercontractsshare", "crodwfundingshares", "donationshare"]
+            .iter()
+            .map(|key| vecu8_to_u32(json_get_value(crmdata.clone(), key.as_bytes().to_vec())))
+            .fold(0u32, |t, v| t.saturating_add(v));
+        Some(total == scale)
+    }
+    /// The owner of a contract, for the other pallets resolving a crmid (the crmids are unique on chain).
+    pub fn owner_of(crmid: T::CrmId) -> Option<T::AccountId> {
+        CrmOwner::<T>::get(&crmid)
+    }
+    /// The lowest crmid of the contracts owned by an account with the given "ipfshash", None when not found.
+    pub fn find_crm_by_ipfshash(account: T::AccountId, hash: Vec<u8>) -> Option<T::CrmId> {
+        Self::owned_crmids(&account, Zero::zero()).into_iter().find(|crmid| {
+            CrmData::<T>::get(crmid)
+                .map(|crmdata| json_get_value(crmdata, "ipfshash".as_bytes().to_vec()) == hash)
+                .unwrap_or(false)
+        })
+    }
+    /// The shares and quorums of a contract, None when the contract is not on chain or the values are not valid percentages.
+    pub fn get_crm_shares(crmid: T::CrmId) -> Option<CrmShares> {
+        if let Some(commitment) = CrmCommitments::<T>::get(&crmid) {
+            return Some(commitment.shares);
+        }
+        json_get_shares(CrmData::<T>::get(&crmid)?)
+    }
+    /// The split of an amount by the shares of a contract owned by the account, for the pallets paying the royalties.
+    /// Every group gets its share rounded down and the master group gets the remainder, so the parts sum to the amount.
+    /// The groups without share are omitted but the master and the composition. None when the contract is not on chain,
+    /// not owned by the account or its shares are not valid.
+    pub fn split_amount(account: T::AccountId, crmid: T::CrmId, amount: BalanceOf<T>) -> Option<Vec<(ShareGroup, BalanceOf<T>)>> {
+        if CrmOwner::<T>::get(&crmid)? != account {
+            return None;
+        }
+        let shares = Self::get_crm_shares(crmid)?;
+        let mut parts = vec![(ShareGroup::Composition, shares.compositionshare.mul_floor(amount))];
+        for (group, share) in [
+            (ShareGroup::OtherContracts, shares.othercontractsshare),
+            (ShareGroup::Crowdfunding, shares.crowdfundingshare),
+            (ShareGroup::Donation, shares.donationshare),
+        ]
+        .iter()
+        {
+            if !share.is_zero() {
+                parts.push((*group, share.mul_floor(amount)));
+            }
+        }
+        let others = parts.iter().fold(BalanceOf::<T>::zero(), |t, (_, part)| t.saturating_add(*part));
+        parts.insert(0, (ShareGroup::Master, amount.saturating_sub(others)));
+        Some(parts)
+    }
+    /// Where the main crm data of a contract is stored, None when the contract is not on chain.
+    pub fn storage_mode(crmid: T::CrmId) -> Option<CrmStorageMode> {
+        if CrmData::<T>::contains_key(&crmid) {
+            Some(CrmStorageMode::Inline)
+        } else if CrmCommitments::<T>::contains_key(&crmid) {
+            Some(CrmStorageMode::ByHash)
+        } else {
+            None
+        }
+    }
+    /// True when the json fetched from IPFS matches the hash of a contract stored by hash, None when the contract is not
+    /// stored by hash.
+    pub fn verify_commitment(crmid: T::CrmId, crmdata: &[u8]) -> Option<bool> {
+        CrmCommitments::<T>::get(&crmid).map(|c| sp_io::hashing::blake2_256(crmdata) == c.datahash)
+    }
+    /// The quorums of a contract: global, master, composition and other contracts, None when the contract is not on chain.
+    pub fn get_crm_quorums(crmid: T::CrmId) -> Option<(u64, u64, u64, u64)> {
+        let shares = Self::get_crm_shares(crmid)?;
+        Some((
+            shares.globalquorum.deconstruct() as u64,
+            shares.masterquorum.deconstruct() as u64,
+            shares.compositionquorum.deconstruct() as u64,
+            shares.othercontractsquorum.deconstruct() as u64,
+        ))
+    }
+    // close a campaign: succeeded when the soft cap is reached (funds moved to the owner), failed otherwise (contributions refundable)
+    fn finalize_campaign(campaignid: u32, c: &mut CampaignOf<T>) -> dispatch::DispatchResult {
+        if c.raised >= c.soft_cap {
+            T::Currency::transfer(&Self::campaign_account_id(campaignid), &c.owner, c.raised, ExistenceRequirement::AllowDeath)?;
+            c.status = CampaignStatus::Succeeded;
+            Self::deposit_event(Event::CampaignSucceeded(campaignid, c.raised));
+            Self::issue_certificates(campaignid, c.raised);
+        } else {
+            c.status = CampaignStatus::Failed;
+            Self::deposit_event(Event::CampaignFailed(campaignid, c.raised));
+        }
+        Ok(())
+    }
 }
 // function to validate a json string for no/std. It does not allocate of memory
 fn json_check_validity(j: Vec<u8>) -> bool {
@@ -1283,7 +4570,19 @@ fn json_get_recordvalue(ar: Vec<u8>, p: i32) -> Vec<u8> {
         }
         lb = b;
     }
-    result
+    result
+}
+
+// function to check that a key found at position x starts a field: it must follow '{', ',' or a space, so that a
+// quoted text inside a string value (\"key\":) is not taken as the key
+fn json_key_boundary(j: &[u8], x: usize) -> bool {
+    if x == 0 {
+        return true;
+    }
+    match j.get(x - 1) {
+        Some(c) => *c == b'{' || *c == b',' || c.is_ascii_whitespace(),
+        None => false,
+    }
 }
 
 // function to get value of a field for Substrate runtime (no std library and no variable allocation)
@@ -1294,63 +4593,931 @@ fn json_get_value(j: Vec<u8>, key: Vec<u8>) -> Vec<u8> {
     let jl = j.len();
     k.push(b'"');
     for xk in 0..keyl {
-        k.push(*key.get(xk).unwrap());
+        match key.get(xk) {
+            Some(c) => k.push(*c),
+            None => return result,
+        }
     }
     k.push(b'"');
     k.push(b':');
     let kl = k.len();
     for x in 0..jl {
-        let mut m = 0;
-        let mut xx = 0;
         if x + kl > jl {
             break;
         }
-        for i in x..x + kl {
-            if *j.get(i).unwrap() == *k.get(xx).unwrap() {
-                m += 1;
-            }
-            xx += 1;
-        }
-        if m == kl {
+        // checked indexing, a key out of range is not matching
+        if j.get(x..x + kl) == Some(&k[..]) && json_key_boundary(&j, x) {
             let mut lb = b' ';
             let mut op = true;
             let mut os = true;
-            for i in x + kl..jl - 1 {
-                if *j.get(i).unwrap() == b'[' && op && os {
+            // the value can end with the body when the key is the last field
+            for i in x + kl..jl {
+                let c = match j.get(i) {
+                    Some(c) => *c,
+                    None => break,
+                };
+                if c == b'[' && op && os {
                     os = false;
                 }
-                if *j.get(i).unwrap() == b'}' && op && !os {
+                if c == b'}' && op && !os {
                     os = true;
                 }
-                if *j.get(i).unwrap() == b':' && op {
+                if c == b':' && op {
+                    continue;
+                }
+                // the spaces before the value are skipped
+                if c.is_ascii_whitespace() && op && result.is_empty() {
                     continue;
                 }
-                if *j.get(i).unwrap() == b'"' && op && lb != b'\\' {
+                if c == b'"' && op && lb != b'\\' {
                     op = false;
                     continue;
                 }
-                if *j.get(i).unwrap() == b'"' && !op && lb != b'\\' {
+                if c == b'"' && !op && lb != b'\\' {
+                    break;
+                }
+                if c == b'}' && op {
                     break;
                 }
-                if *j.get(i).unwrap() == b'}' && op {
+                if c == b',' && op && os {
                     break;
                 }
-                if *j.get(i).unwrap() == b',' && op && os {
+                result.push(c);
+                lb = c;
+            }
+            break;
+        }
+    }
+    result
+}
+
+// function to get the strings of an array field ["xxx","yyy",..], a single string value is returned as array of one element
+fn json_get_array(j: Vec<u8>, key: Vec<u8>) -> Vec<Vec<u8>> {
+    let mut result: Vec<Vec<u8>> = Vec::new();
+    let mut k = Vec::new();
+    k.push(b'"');
+    k.extend_from_slice(&key);
+    k.push(b'"');
+    k.push(b':');
+    let kl = k.len();
+    let jl = j.len();
+    // search for the key
+    let mut x = 0;
+    let mut found = false;
+    while x + kl <= jl {
+        if j[x..x + kl] == k[..] && json_key_boundary(&j, x) {
+            found = true;
+            break;
+        }
+        x += 1;
+    }
+    if !found {
+        return result;
+    }
+    let mut isarray = false;
+    let mut os = false;
+    let mut lb = b' ';
+    let mut element: Vec<u8> = Vec::new();
+    for b in j.iter().skip(x + kl) {
+        let b = *b;
+        if os {
+            // end of the string
+            if b == b'"' && lb != b'\\' {
+                os = false;
+                result.push(element.clone());
+                element.clear();
+                if !isarray {
                     break;
                 }
-                result.push(j.get(i).unwrap().clone());
-                lb = j.get(i).unwrap().clone();
+            } else {
+                element.push(b);
+            }
+            lb = b;
+            continue;
+        }
+        if b == b'"' {
+            os = true;
+        } else if b == b'[' && !isarray {
+            isarray = true;
+        } else if b == b']' || b == b'}' || (b == b',' && !isarray) {
+            break;
+        }
+        lb = b;
+    }
+    result
+}
+
+// function to append a string value to an array field, a single string value is converted to an array of two elements.
+// it returns an empty Vec when the field is not found or it's not a string/array
+fn json_array_append(j: Vec<u8>, key: Vec<u8>, value: Vec<u8>) -> Vec<u8> {
+    let mut result = Vec::new();
+    let mut k = Vec::new();
+    k.push(b'"');
+    k.extend_from_slice(&key);
+    k.push(b'"');
+    k.push(b':');
+    let kl = k.len();
+    let jl = j.len();
+    // search for the key
+    let mut x = 0;
+    let mut found = false;
+    while x + kl <= jl {
+        if j[x..x + kl] == k[..] && json_key_boundary(&j, x) {
+            found = true;
+            break;
+        }
+        x += 1;
+    }
+    if !found {
+        return result;
+    }
+    // skip the spaces before the value
+    let mut p = x + kl;
+    while p < jl && j[p].is_ascii_whitespace() {
+        p += 1;
+    }
+    if p >= jl {
+        return result;
+    }
+    if j[p] == b'[' {
+        // search for the end of the array
+        let mut e = p + 1;
+        let mut os = false;
+        let mut empty = true;
+        let mut lb = b' ';
+        while e < jl {
+            let b = j[e];
+            if b == b'"' && lb != b'\\' {
+                os = !os;
+            }
+            if b == b']' && !os {
+                break;
+            }
+            if !b.is_ascii_whitespace() {
+                empty = false;
+            }
+            lb = b;
+            e += 1;
+        }
+        if e >= jl {
+            return result;
+        }
+        result.extend_from_slice(&j[..e]);
+        if !empty {
+            result.push(b',');
+        }
+        result.push(b'"');
+        result.extend_from_slice(&value);
+        result.push(b'"');
+        result.extend_from_slice(&j[e..]);
+    } else if j[p] == b'"' {
+        // search for the end of the string
+        let mut e = p + 1;
+        let mut lb = b' ';
+        while e < jl {
+            if j[e] == b'"' && lb != b'\\' {
+                break;
             }
+            lb = j[e];
+            e += 1;
+        }
+        if e >= jl {
+            return result;
+        }
+        result.extend_from_slice(&j[..p]);
+        result.push(b'[');
+        result.extend_from_slice(&j[p..e + 1]);
+        result.push(b',');
+        result.push(b'"');
+        result.extend_from_slice(&value);
+        result.push(b'"');
+        result.push(b']');
+        result.extend_from_slice(&j[e + 1..]);
+    }
+    result
+}
+
+// function to get an object field {...} with the braces, it returns an empty Vec when the field is not found or it's not an object
+fn json_get_object(j: Vec<u8>, key: Vec<u8>) -> Vec<u8> {
+    let mut result = Vec::new();
+    let mut k = Vec::new();
+    k.push(b'"');
+    k.extend_from_slice(&key);
+    k.push(b'"');
+    k.push(b':');
+    let kl = k.len();
+    let jl = j.len();
+    // search for the key
+    let mut x = 0;
+    let mut found = false;
+    while x + kl <= jl {
+        if j[x..x + kl] == k[..] && json_key_boundary(&j, x) {
+            found = true;
             break;
         }
+        x += 1;
+    }
+    if !found {
+        return result;
+    }
+    // skip the spaces before the value
+    let mut p = x + kl;
+    while p < jl && j[p].is_ascii_whitespace() {
+        p += 1;
+    }
+    if p >= jl || j[p] != b'{' {
+        return result;
+    }
+    // search for the closing brace, skipping the strings and the nested objects
+    let mut depth = 0;
+    let mut os = false;
+    let mut lb = b' ';
+    for e in p..jl {
+        let b = j[e];
+        if os {
+            if b == b'"' && lb != b'\\' {
+                os = false;
+            }
+        } else if b == b'"' {
+            os = true;
+        } else if b == b'{' {
+            depth += 1;
+        } else if b == b'}' {
+            depth -= 1;
+            if depth == 0 {
+                result.extend_from_slice(&j[p..e + 1]);
+                break;
+            }
+        }
+        lb = b;
+    }
+    result
+}
+
+// function to set the value of a top level field, the value is the raw json (a quoted string or a number) and the field is
+// appended to the object when it's not present. It returns an empty Vec when the json is not an object or the value is not closed
+fn json_set_value(j: Vec<u8>, key: Vec<u8>, value: Vec<u8>) -> Vec<u8> {
+    let mut result = Vec::new();
+    let mut k = Vec::new();
+    k.push(b'"');
+    k.extend_from_slice(&key);
+    k.push(b'"');
+    let kl = k.len();
+    let jl = j.len();
+    // search for the key at the top level, skipping the strings and the nested objects/arrays
+    let mut depth = 0;
+    let mut os = false;
+    let mut lb = b' ';
+    let mut x = 0;
+    let mut found = false;
+    while x < jl {
+        let b = j[x];
+        if os {
+            if b == b'"' && lb != b'\\' {
+                os = false;
+            }
+        } else if b == b'"' {
+            if depth == 1 && x + kl <= jl && j[x..x + kl] == k[..] {
+                // the key must be followed by ':'
+                let mut p = x + kl;
+                while p < jl && j[p].is_ascii_whitespace() {
+                    p += 1;
+                }
+                if p < jl && j[p] == b':' {
+                    x = p + 1;
+                    found = true;
+                    break;
+                }
+            }
+            os = true;
+        } else if b == b'{' || b == b'[' {
+            depth += 1;
+        } else if b == b'}' || b == b']' {
+            depth -= 1;
+        }
+        lb = b;
+        x += 1;
+    }
+    if !found {
+        // append the field before the end of the object
+        let mut e = jl;
+        while e > 0 && j[e - 1].is_ascii_whitespace() {
+            e -= 1;
+        }
+        if e == 0 || j[e - 1] != b'}' {
+            return result;
+        }
+        let empty = j[..e - 1].iter().rev().find(|b| !b.is_ascii_whitespace()) == Some(&b'{');
+        result.extend_from_slice(&j[..e - 1]);
+        if !empty {
+            result.push(b',');
+        }
+        result.extend_from_slice(&k);
+        result.push(b':');
+        result.extend_from_slice(&value);
+        result.extend_from_slice(&j[e - 1..]);
+        return result;
+    }
+    // skip the spaces before the value
+    let mut p = x;
+    while p < jl && j[p].is_ascii_whitespace() {
+        p += 1;
+    }
+    // search for the end of the value, a string or a scalar
+    let mut e = p;
+    if p < jl && j[p] == b'"' {
+        e = p + 1;
+        let mut lb = b' ';
+        while e < jl && !(j[e] == b'"' && lb != b'\\') {
+            lb = j[e];
+            e += 1;
+        }
+        if e >= jl {
+            return result;
+        }
+        e += 1;
+    } else {
+        while e < jl && j[e] != b',' && j[e] != b'}' && !j[e].is_ascii_whitespace() {
+            e += 1;
+        }
+        if e >= jl {
+            return result;
+        }
     }
+    result.extend_from_slice(&j[..p]);
+    result.extend_from_slice(&value);
+    result.extend_from_slice(&j[e..]);
     result
 }
 
-// function to convert vec<u8> to u32
+// function to split an amount by the weights given out of total, with the largest remainder rule: every part is rounded down
+// and the units left by the rounding are assigned one by one to the parts with the largest remainders (the first part wins a tie).
+// It returns the parts and the dust, the amount not covered when the sum of the weights is lower than total.
+fn largest_remainder_split(amount: u128, weights: &[u128], total: u128) -> (Vec<u128>, u128) {
+    if total == 0 {
+        return (weights.iter().map(|_| 0).collect(), amount);
+    }
+    // amount = q * total + r keeps the products in range
+    let q = amount / total;
+    let r = amount % total;
+    let mut parts: Vec<u128> = Vec::with_capacity(weights.len());
+    let mut remainders: Vec<(u128, usize)> = Vec::with_capacity(weights.len());
+    let mut sumweights: u128 = 0;
+    let mut sumparts: u128 = 0;
+    for (i, w) in weights.iter().enumerate() {
+        let w = (*w).min(total);
+        sumweights = sumweights.saturating_add(w);
+        let part = q.saturating_mul(w).saturating_add(r * w / total);
+        sumparts = sumparts.saturating_add(part);
+        parts.push(part);
+        remainders.push((r * w % total, i));
+    }
+    let sumweights = sumweights.min(total);
+    // the amount covered by the weights
+    let covered = q.saturating_mul(sumweights).saturating_add(r * sumweights / total);
+    let mut left = covered.saturating_sub(sumparts);
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, i) in remainders {
+        if left == 0 {
+            break;
+        }
+        parts[i] += 1;
+        left -= 1;
+    }
+    (parts, amount.saturating_sub(covered))
+}
+
+// function to check the optional "type" of contract, it must be one of CONTRACT_TYPES when present
+fn json_check_contract_type(j: Vec<u8>) -> bool {
+    let contracttype = json_get_value(j, "type".as_bytes().to_vec());
+    contracttype.is_empty() || CONTRACT_TYPES.iter().any(|t| t.as_bytes() == contracttype.as_slice())
+}
+
+// function to get the territories of the "territories" array, distinct ISO 3166-1 alpha-2 codes (two uppercase letters).
+// it returns None for malformed or duplicated codes and an empty Vec when the field is not set (worldwide)
+fn json_get_territories(j: Vec<u8>) -> Option<Vec<[u8; 2]>> {
+    let mut territories: Vec<[u8; 2]> = Vec::new();
+    for code in json_get_array(j, "territories".as_bytes().to_vec()) {
+        if code.len() != 2 || !code.iter().all(|b| b.is_ascii_uppercase()) {
+            return None;
+        }
+        let code = [code[0], code[1]];
+        if territories.contains(&code) {
+            return None;
+        }
+        territories.push(code);
+    }
+    Some(territories)
+}
+
+// function to check that a raw json value is a non empty string
+fn json_is_string(v: &[u8]) -> bool {
+    v.len() > 2 && v.first() == Some(&b'"') && v.last() == Some(&b'"')
+}
+
+// function to check that a raw json value is a number, the numbers can be quoted. Only the digits are accepted
+fn json_is_number(v: &[u8]) -> bool {
+    let digits = if json_is_string(v) { &v[1..v.len() - 1] } else { v };
+    !digits.is_empty() && digits.iter().all(|b| b.is_ascii_digit())
+}
+
+// kind of change of a patch of the main crm data from its fields: the shares and quorums first, then the ipfs hashes
+fn json_change_kind(patch: &[u8]) -> ChangeKind {
+    const SHARES: [&[u8]; 11] = [
+        b"globalquorum", b"mastershare", b"masterquorum", b"compositionshare", b"compositionquorum", b"othercontractsshare",
+        b"othercontractsquorum", b"crodwfundingshares", b"crowdfounders", b"donationshare", b"donationaccount",
+    ];
+    let keys: Vec<Vec<u8>> = json_get_fields(patch).unwrap_or_default().into_iter().map(|(k, _)| k).collect();
+    if keys.iter().any(|k| SHARES.contains(&&k[..])) {
+        ChangeKind::Shares
+    } else if keys.iter().any(|k| k == b"ipfshash" || k == b"ipfshashprivate") {
+        ChangeKind::IpfsHash
+    } else {
+        ChangeKind::Meta
+    }
+}
+// function to get the top level fields of a json object as (key, raw value), the raw value is a quoted string, a number,
+// an object or an array. It returns None when the json is not a closed object or a field is not complete
+fn json_get_fields(j: &[u8]) -> Option<Vec<(Vec<u8>, Vec<u8>)>> {
+    let trim = |v: &[u8]| -> Option<Vec<u8>> {
+        let s = v.iter().position(|b| !b.is_ascii_whitespace())?;
+        let e = v.iter().rposition(|b| !b.is_ascii_whitespace())?;
+        Some(v[s..=e].to_vec())
+    };
+    if j.iter().find(|b| !b.is_ascii_whitespace()) != Some(&b'{') {
+        return None;
+    }
+    let mut fields = Vec::new();
+    let mut depth = 0;
+    let mut instring = false;
+    let mut escaped = false;
+    let mut start = 0;
+    // last string closed at the top level, the key of the field when followed by :
+    let mut last: Option<Vec<u8>> = None;
+    let mut key: Option<Vec<u8>> = None;
+    let mut value: Option<usize> = None;
+    let mut closed = false;
+    for (i, b) in j.iter().enumerate() {
+        if closed {
+            if !b.is_ascii_whitespace() {
+                return None;
+            }
+            continue;
+        }
+        if instring {
+            if escaped {
+                escaped = false;
+            } else if *b == b'\\' {
+                escaped = true;
+            } else if *b == b'"' {
+                instring = false;
+                if depth == 1 && value.is_none() {
+                    last = Some(j.get(start..i)?.to_vec());
+                }
+            }
+            continue;
+        }
+        match *b {
+            b'"' => {
+                instring = true;
+                start = i + 1;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                if depth == 1 {
+                    if let Some(s) = value.take() {
+                        fields.push((key.take()?, trim(j.get(s..i)?)?));
+                    }
+                    closed = true;
+                }
+                if depth == 0 {
+                    return None;
+                }
+                depth -= 1;
+            }
+            b':' if depth == 1 && value.is_none() => {
+                key = Some(last.take()?);
+                value = Some(i + 1);
+            }
+            b',' if depth == 1 => {
+                let s = value.take()?;
+                fields.push((key.take()?, trim(j.get(s..i)?)?));
+            }
+            _ => {}
+        }
+    }
+    if closed {
+        Some(fields)
+    } else {
+        None
+    }
+}
+
+// function to merge a patch in a json object: the top level fields of the patch replace the fields with the same key and
+// the others are appended (shallow merge). It returns None when the json or the patch are not objects
+fn json_merge(j: &[u8], patch: &[u8]) -> Option<Vec<u8>> {
+    let mut fields = json_get_fields(j)?;
+    for (k, v) in json_get_fields(patch)? {
+        match fields.iter_mut().find(|f| f.0 == k) {
+            Some(f) => f.1 = v,
+            None => fields.push((k, v)),
+        }
+    }
+    let mut result = Vec::new();
+    result.push(b'{');
+    for (i, (k, v)) in fields.iter().enumerate() {
+        if i > 0 {
+            result.push(b',');
+        }
+        result.push(b'"');
+        result.extend_from_slice(k);
+        result.push(b'"');
+        result.push(b':');
+        result.extend_from_slice(v);
+    }
+    result.push(b'}');
+    Some(result)
+}
+
+// function to check for duplicated keys at the top level of a json object, nested objects and string values are skipped
+fn json_has_duplicate_keys(j: &[u8]) -> bool {
+    let mut keys: Vec<&[u8]> = Vec::new();
+    let mut depth = 0;
+    let mut instring = false;
+    let mut escaped = false;
+    let mut start = 0;
+    // last string closed at the top level, it's a key when followed by :
+    let mut last: Option<&[u8]> = None;
+    for (i, b) in j.iter().enumerate() {
+        if instring {
+            if escaped {
+                escaped = false;
+            } else if *b == b'\\' {
+                escaped = true;
+            } else if *b == b'"' {
+                instring = false;
+                if depth == 1 {
+                    last = j.get(start..i);
+                }
+            }
+            continue;
+        }
+        match *b {
+            b'"' => {
+                instring = true;
+                start = i + 1;
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                last = None;
+            }
+            b'}' | b']' => {
+                depth -= 1;
+                last = None;
+            }
+            b':' => {
+                if let Some(k) = last.take() {
+                    if keys.contains(&k) {
+                        return true;
+                    }
+                    keys.push(k);
+                }
+            }
+            b' ' | b'\t' | b'\r' | b'\n' => {}
+            _ => last = None,
+        }
+    }
+    false
+}
+
+// function to get the maximum nesting of the objects and arrays, the strings are skipped
+fn json_depth(j: &[u8]) -> u32 {
+    let mut depth: u32 = 0;
+    let mut max: u32 = 0;
+    let mut os = false;
+    let mut lb = b' ';
+    for b in j.iter() {
+        let b = *b;
+        if os {
+            if b == b'"' && lb != b'\\' {
+                os = false;
+            }
+        } else if b == b'"' {
+            os = true;
+        } else if b == b'{' || b == b'[' {
+            depth += 1;
+            max = max.max(depth);
+        } else if b == b'}' || b == b']' {
+            depth = depth.saturating_sub(1);
+        }
+        lb = b;
+    }
+    max
+}
+
+// function to find a trailing comma before the closing of an object or an array, the strings are skipped
+fn json_has_trailing_comma(j: &[u8]) -> bool {
+    let mut os = false;
+    let mut lb = b' ';
+    let mut comma = false;
+    for b in j.iter() {
+        let b = *b;
+        if os {
+            if b == b'"' && lb != b'\\' {
+                os = false;
+            }
+            lb = b;
+            continue;
+        }
+        if b.is_ascii_whitespace() {
+            continue;
+        }
+        if (b == b'}' || b == b']') && comma {
+            return true;
+        }
+        comma = b == b',';
+        if b == b'"' {
+            os = true;
+        }
+        lb = b;
+    }
+    false
+}
+
+// function to check the optional "rateperplay", it must be a number > 0 when present
+fn json_check_rate_per_play(j: Vec<u8>) -> bool {
+    let rate = json_get_value(j, "rateperplay".as_bytes().to_vec());
+    rate.is_empty() || vecu8_to_u32(rate) > 0
+}
+
+// function to check the optional "withholdingpercent", it must be a number between 0 and 50 when present
+fn json_check_withholding_percent(j: Vec<u8>) -> bool {
+    let percent = json_get_value(j, "withholdingpercent".as_bytes().to_vec());
+    percent.is_empty() || (percent.iter().all(|b| b.is_ascii_digit()) && vecu8_to_u32(percent) <= 50)
+}
+
+// function to get the optional "isrc" of a recording: 12 characters CCXXXYYNNNNN (country, registrant, year, number),
+// also written CC-XXX-YY-NNNNN. It returns the code without hyphens, empty when absent, None when malformed
+fn json_get_isrc(j: Vec<u8>) -> Option<Vec<u8>> {
+    let value = json_get_value(j, "isrc".as_bytes().to_vec());
+    if value.is_empty() {
+        return Some(value);
+    }
+    let code: Vec<u8> = if value.len() == 15 {
+        if value[2] != b'-' || value[6] != b'-' || value[9] != b'-' {
+            return None;
+        }
+        value.iter().filter(|b| **b != b'-').cloned().collect()
+    } else {
+        value
+    };
+    if code.len() != 12 {
+        return None;
+    }
+    let valid = code[..2].iter().all(|b| b.is_ascii_uppercase())
+        && code[2..5].iter().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        && code[5..].iter().all(|b| b.is_ascii_digit());
+    if valid {
+        Some(code)
+    } else {
+        None
+    }
+}
+
+// function to get the optional "iswc" of a work: T-NNNNNNNNN-C (9 digits and the check digit), also written without
+// hyphens. It returns the code without hyphens, empty when absent, None when malformed or the check digit is wrong
+fn json_get_iswc(j: Vec<u8>) -> Option<Vec<u8>> {
+    let value = json_get_value(j, "iswc".as_bytes().to_vec());
+    if value.is_empty() {
+        return Some(value);
+    }
+    let code: Vec<u8> = if value.len() == 13 {
+        if value[1] != b'-' || value[11] != b'-' {
+            return None;
+        }
+        value.iter().filter(|b| **b != b'-').cloned().collect()
+    } else {
+        value
+    };
+    if code.len() != 11 || code[0] != b'T' || !code[1..].iter().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // check digit: 1 for the T plus the digits weighted by their position, complemented to 10
+    let sum = code[1..10].iter().enumerate().fold(1u32, |s, (i, d)| s + (i as u32 + 1) * (d - b'0') as u32);
+    if (10 - sum % 10) % 10 == (code[10] - b'0') as u32 {
+        Some(code)
+    } else {
+        None
+    }
+}
+
+// function to check the optional "mechanicallicensefee", it must be a number > 0 when present
+fn json_check_mechanical_license_fee(j: Vec<u8>) -> bool {
+    let fee = json_get_value(j, "mechanicallicensefee".as_bytes().to_vec());
+    fee.is_empty() || vecu8_to_u32(fee) > 0
+}
+
+// function to decode the shares and quorums from the main crm data, it returns None when a value is not a valid percentage
+fn json_get_shares(j: Vec<u8>) -> Option<CrmShares> {
+    let percent = |key: &str| u32_to_percent(vecu8_to_u32(json_get_value(j.clone(), key.as_bytes().to_vec())));
+    // the shares in basis points are rounded down to the percent
+    let share = |key: &str| u32_to_percent(json_get_share_bps(j.clone(), key)? / 100);
+    Some(CrmShares {
+        globalquorum: percent("globalquorum")?,
+        mastershare: share("mastershare")?,
+        masterquorum: percent("masterquorum")?,
+        compositionshare: share("compositionshare")?,
+        compositionquorum: percent("compositionquorum")?,
+        othercontractsshare: share("othercontractsshare")?,
+        othercontractsquorum: percent("othercontractsquorum")?,
+        crowdfundingshare: share("crodwfundingshares")?,
+        donationshare: share("donationshare")?,
+    })
+}
+
+// function to decode the main crm data, it returns None when a field is missing or out of range
+fn json_get_contract(j: Vec<u8>) -> Option<CrmContract> {
+    let ipfshash = json_get_value(j.clone(), "ipfshash".as_bytes().to_vec());
+    if ipfshash.is_empty() {
+        return None;
+    }
+    let quorum = |key: &str| {
+        let v = vecu8_to_u32(json_get_value(j.clone(), key.as_bytes().to_vec()));
+        if v > 100 {
+            return None;
+        }
+        Some(v as u8)
+    };
+    let share = |key: &str| json_get_share_bps(j.clone(), key);
+    let campaignid = vecu8_to_u32(json_get_value(j.clone(), "crowdfounders".as_bytes().to_vec()));
+    Some(CrmContract {
+        ipfshash,
+        ipfshashprivate: json_get_array(j.clone(), "ipfshashprivate".as_bytes().to_vec()),
+        globalquorum: quorum("globalquorum")?,
+        mastershare: share("mastershare")?,
+        masterquorum: quorum("masterquorum")?,
+        compositionshare: share("compositionshare")?,
+        compositionquorum: quorum("compositionquorum")?,
+        othercontractsshare: share("othercontractsshare")?,
+        othercontractsquorum: quorum("othercontractsquorum")?,
+        crowdfundingshare: share("crodwfundingshares")?,
+        campaignid: if campaignid > 0 { Some(campaignid) } else { None },
+    })
+}
+
+// function to get the scale of the shares from the "sharescale" field: 100 for "percent" (default), 10000 for "bps"
+fn json_get_share_scale(j: Vec<u8>) -> Option<u32> {
+    match json_get_value(j, "sharescale".as_bytes().to_vec()).as_slice() {
+        b"" | b"percent" => Some(100),
+        b"bps" => Some(10_000),
+        _ => None,
+    }
+}
+
+// function to get a share in basis points, converting the shares in percent
+fn json_get_share_bps(j: Vec<u8>, key: &str) -> Option<u32> {
+    let scale = json_get_share_scale(j.clone())?;
+    let v = vecu8_to_u32(json_get_value(j, key.as_bytes().to_vec()));
+    if v > scale {
+        return None;
+    }
+    Some(v * (10_000 / scale))
+}
+
+// function to convert u32 to Percent, it returns None for values > 100
+fn u32_to_percent(v: u32) -> Option<Percent> {
+    if v > 100 {
+        return None;
+    }
+    Some(Percent::from_percent(v as u8))
+}
+
+// function to convert vec<u8> to u32, the numbers can be quoted ("51") or bare (51) in the json and the spaces around are ignored
 fn vecu8_to_u32(v: Vec<u8>) -> u32 {
     let vslice = v.as_slice();
-    let vstr = str::from_utf8(&vslice).unwrap_or("0");
+    let vstr = str::from_utf8(&vslice).unwrap_or("0").trim();
     let vvalue: u32 = u32::from_str(vstr).unwrap_or(0);
     vvalue
 }
+
+// function to convert vec<u8> to a crmid, parsed as a number like in vecu8_to_u32: 0 (invalid) when it's not a number or
+// it's out of the range of the crmid type
+fn vecu8_to_crmid<CrmId: TryFrom<u64> + Default>(v: Vec<u8>) -> CrmId {
+    let vslice = v.as_slice();
+    let vstr = str::from_utf8(&vslice).unwrap_or("0").trim();
+    u64::from_str(vstr).ok().and_then(|v| CrmId::try_from(v).ok()).unwrap_or_default()
+}
+
+// function to decode a base58 string (bitcoin alphabet), it returns None for the characters out of the alphabet
+fn base58_decode(s: &[u8]) -> Option<Vec<u8>> {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    if s.is_empty() || s.len() > 128 {
+        return None;
+    }
+    // big number in little endian
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in s.iter() {
+        let mut carry = ALPHABET.iter().position(|a| a == c)? as u32;
+        for b in bytes.iter_mut() {
+            carry += *b as u32 * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    // the leading '1' are the leading zeros
+    for _ in s.iter().take_while(|c| **c == b'1') {
+        bytes.push(0);
+    }
+    bytes.reverse();
+    Some(bytes)
+}
+
+// function to check a CIDv0: 46 base58btc characters starting with "Qm", decoding to a sha2-256 multihash (0x12 0x20 and
+// the 32 bytes of the digest)
+fn is_cidv0(h: &[u8]) -> bool {
+    if h.len() != 46 || !h.starts_with(b"Qm") {
+        return false;
+    }
+    match base58_decode(h) {
+        Some(multihash) => multihash.len() == 34 && multihash[..2] == [0x12, 0x20],
+        None => false,
+    }
+}
+
+// function to decode a CID to its multihash, the binary form shared by the CIDv0 and the CIDv1 of the same content.
+// It accepts a CIDv0 or a CIDv1 in base32 ("b" followed by RFC 4648 lower case without padding) with version 1, a
+// multicodec and a complete multihash. None when the string is not a CID
+fn cid_multihash(h: &[u8]) -> Option<Vec<u8>> {
+    if is_cidv0(h) {
+        return base58_decode(h);
+    }
+    if h.len() > MAX_IPFS_CID_LENGTH || h.first() != Some(&b'b') {
+        return None;
+    }
+    let bytes = base32_decode(&h[1..])?;
+    let (version, rest) = read_varint(&bytes)?;
+    if version != 1 {
+        return None;
+    }
+    // the multicodec of the content is not restricted
+    let (_, multihash) = read_varint(rest)?;
+    let (_, rest) = read_varint(multihash)?;
+    let (length, digest) = read_varint(rest)?;
+    if length == 0 || digest.len() as u64 != length {
+        return None;
+    }
+    Some(multihash.to_vec())
+}
+
+// function to decode base32 lower case without padding (RFC 4648), the unused bits of the last character must be zero
+fn base32_decode(s: &[u8]) -> Option<Vec<u8>> {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in s.iter() {
+        buffer = (buffer << 5) | ALPHABET.iter().position(|a| a == c)? as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    // 5 bits or more left is a truncated byte
+    if bits >= 5 || buffer != 0 {
+        return None;
+    }
+    Some(bytes)
+}
+
+// function to read an unsigned varint (LEB128, up to 9 bytes as in multiformats), it returns the value and the bytes after it
+fn read_varint(b: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value: u64 = 0;
+    for (i, byte) in b.iter().take(9).enumerate() {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, &b[i + 1..]));
+        }
+    }
+    None
+}
+
+// function to convert u32 to vec<u8> of the decimal digits
+fn u32_to_vecu8(v: u32) -> Vec<u8> {
+    let mut result = Vec::new();
+    let mut v = v;
+    loop {
+        result.push(b'0' + (v % 10) as u8);
+        v /= 10;
+        if v == 0 {
+            break;
+        }
+    }
+    result.reverse();
+    result
+}
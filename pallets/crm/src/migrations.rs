@@ -0,0 +1,119 @@
+//! Checks of the storage migrations: a snapshot of the contracts is taken before the runtime upgrade and the storage
+//! migrated is verified against it. try-runtime runs them around the upgrade through `CrmMigrationChecks`.
+
+use crate::{
+    json_get_contract, json_get_value, Config, CrmCommitments, CrmContracts, CrmData, CrmStorageVersion, IpfsHashContracts,
+    IpfsIndex, Pallet, TotalCrms, OLD_STORAGE_PREFIX, STORAGE_VERSION,
+};
+use codec::{Decode, Encode};
+use frame_support::{
+    traits::{OnRuntimeUpgrade, PalletInfo},
+    weights::Weight,
+};
+use sp_runtime::RuntimeDebug;
+use sp_std::{marker::PhantomData, prelude::*};
+
+// maximum number of contracts whose crm data hash is kept by the snapshot
+const MAX_SNAPSHOT_SAMPLES: usize = 1000;
+
+// key of the snapshot taken before the upgrade, it's removed by the check after the upgrade
+#[cfg(feature = "try-runtime")]
+const SNAPSHOT_KEY: &[u8] = b":crm:migration:snapshot";
+
+/// The contracts on chain before the upgrade: their number and the blake2-256 hash of the crm data of a sample.
+#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+pub struct CrmSnapshot<CrmId> {
+    pub count: u32,
+    pub samples: Vec<(CrmId, [u8; 32])>,
+}
+
+pub type CrmSnapshotOf<T> = CrmSnapshot<<T as Config>::CrmId>;
+
+impl<T: Config> Pallet<T> {
+    /// Snapshot of the contracts on chain, taken before the upgrade.
+    pub fn migration_snapshot() -> CrmSnapshotOf<T> {
+        let mut snapshot = CrmSnapshot::default();
+        for (crmid, crmdata) in CrmData::<T>::iter() {
+            snapshot.count += 1;
+            if snapshot.samples.len() < MAX_SNAPSHOT_SAMPLES {
+                snapshot.samples.push((crmid, sp_io::hashing::blake2_256(&crmdata)));
+            }
+        }
+        snapshot
+    }
+    /// Check the storage after the upgrade against the snapshot: the same contracts with the same crm data, the decoded
+    /// contracts matching their crm data, the counters and the ipfs index. It returns the first mismatch found.
+    pub fn check_migration(snapshot: &CrmSnapshotOf<T>) -> Result<(), &'static str> {
+        if CrmStorageVersion::<T>::get() != STORAGE_VERSION {
+            return Err("crm migration: the storage is not at the current version");
+        }
+        // nothing is left under the prefix of decl_storage, unless it's the name of the pallet in the runtime
+        let renamed = <<T as frame_system::Config>::PalletInfo as PalletInfo>::name::<Self>()
+            .map_or(false, |name| name.as_bytes() != OLD_STORAGE_PREFIX);
+        let old_prefix = sp_io::hashing::twox_128(OLD_STORAGE_PREFIX);
+        if renamed && sp_io::storage::next_key(&old_prefix).map_or(false, |key| key.starts_with(&old_prefix)) {
+            return Err("crm migration: storage left under the old prefix");
+        }
+        let mut count: u32 = 0;
+        for (crmid, crmdata) in CrmData::<T>::iter() {
+            count += 1;
+            // the contracts that cannot be decoded are skipped by the migration
+            if let Some(contract) = json_get_contract(crmdata) {
+                if CrmContracts::<T>::get(crmid) != Some(contract) {
+                    return Err("crm migration: a decoded contract does not match its crm data");
+                }
+            }
+        }
+        if count != snapshot.count {
+            return Err("crm migration: the number of contracts has changed");
+        }
+        for (crmid, hash) in snapshot.samples.iter() {
+            match CrmData::<T>::get(crmid) {
+                Some(crmdata) if sp_io::hashing::blake2_256(&crmdata) == *hash => {}
+                _ => return Err("crm migration: the crm data of a contract has changed"),
+            }
+        }
+        if CrmContracts::<T>::iter().any(|(crmid, _)| !CrmData::<T>::contains_key(crmid)) {
+            return Err("crm migration: a decoded contract has no crm data");
+        }
+        if TotalCrms::<T>::get() != count.saturating_add(CrmCommitments::<T>::iter().count() as u32) {
+            return Err("crm migration: the counter of the contracts does not match the contracts stored");
+        }
+        for (hash, crmid) in IpfsIndex::<T>::iter() {
+            let indexed = match CrmData::<T>::get(crmid) {
+                Some(crmdata) => json_get_value(crmdata, "ipfshash".as_bytes().to_vec()),
+                None => CrmCommitments::<T>::get(crmid).map(|c| c.ipfscid).unwrap_or_default(),
+            };
+            if indexed != hash {
+                return Err("crm migration: the ipfs index does not match the contracts stored");
+            }
+            if !IpfsHashContracts::<T>::contains_key(&hash, crmid) {
+                return Err("crm migration: a contract of the ipfs index is not listed for its hash");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The checks of the crm migrations for try-runtime, to be listed in the custom runtime upgrades of the Executive. The
+/// storage is migrated by the on_runtime_upgrade of the pallet, this upgrade does nothing.
+pub struct CrmMigrationChecks<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for CrmMigrationChecks<T> {
+    fn on_runtime_upgrade() -> Weight {
+        0
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade() -> Result<(), &'static str> {
+        frame_support::storage::unhashed::put(SNAPSHOT_KEY, &Pallet::<T>::migration_snapshot());
+        Ok(())
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade() -> Result<(), &'static str> {
+        let snapshot: CrmSnapshotOf<T> =
+            frame_support::storage::unhashed::take(SNAPSHOT_KEY).ok_or("crm migration: snapshot not found")?;
+        Pallet::<T>::check_migration(&snapshot)
+    }
+}
@@ -1,11 +1,18 @@
 use crate as pallet_template;
-use frame_support::parameter_types;
+use codec::{Decode, Encode};
+use frame_support::{
+    dispatch::DispatchResult,
+    parameter_types,
+    traits::{Get, OnInitialize},
+};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
+    traits::{BlakeTwo256, IdentifyAccount, IdentityLookup, Lazy, Verify},
+    ModuleId, RuntimeDebug,
 };
+use std::cell::RefCell;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -18,7 +25,10 @@ frame_support::construct_runtime!(
         UncheckedExtrinsic = UncheckedExtrinsic,
     {
         System: frame_system::{Module, Call, Config, Storage, Event<T>},
-        TemplateModule: pallet_template::{Module, Call, Storage, Event<T>},
+        Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+        Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
+        Assets: pallet_assets::{Module, Call, Storage, Event<T>},
+        TemplateModule: pallet_template::{Module, Call, Storage, Event<T>, Config<T>},
     }
 );
 
@@ -38,28 +48,532 @@ impl system::Config for Test {
     type BlockNumber = u64;
     type Hash = H256;
     type Hashing = BlakeTwo256;
-    type AccountId = u64;
+    // u128 so that the sub accounts of the module id are distinct
+    type AccountId = u128;
     type Lookup = IdentityLookup<Self::AccountId>;
     type Header = Header;
     type Event = Event;
     type BlockHashCount = BlockHashCount;
     type Version = ();
     type PalletInfo = PalletInfo;
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u64>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
     type SS58Prefix = SS58Prefix;
 }
 
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+    pub const MaxLocks: u32 = 50;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = MaxLocks;
+    type Balance = u64;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const MaximumSchedulerWeight: u64 = 1_000_000_000;
+    pub const MaxScheduledPerBlock: u32 = 50;
+}
+
+impl pallet_scheduler::Config for Test {
+    type Event = Event;
+    type Origin = Origin;
+    type PalletsOrigin = OriginCaller;
+    type Call = Call;
+    type MaximumWeight = MaximumSchedulerWeight;
+    type ScheduleOrigin = system::EnsureRoot<u128>;
+    type MaxScheduledPerBlock = MaxScheduledPerBlock;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const AssetDepositBase: u64 = 0;
+    pub const AssetDepositPerZombie: u64 = 0;
+    pub const StringLimit: u32 = 50;
+    pub const MetadataDepositBase: u64 = 0;
+    pub const MetadataDepositPerByte: u64 = 0;
+}
+
+impl pallet_assets::Config for Test {
+    type Currency = Balances;
+    type Event = Event;
+    type Balance = u64;
+    type AssetId = u32;
+    type ForceOrigin = system::EnsureRoot<u128>;
+    type AssetDepositBase = AssetDepositBase;
+    type AssetDepositPerZombie = AssetDepositPerZombie;
+    type StringLimit = StringLimit;
+    type MetadataDepositBase = MetadataDepositBase;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type WeightInfo = ();
+}
+
+// assets created at genesis, with their owner
+pub const ASSET_A: u32 = 1;
+pub const ASSET_B: u32 = 2;
+pub const ASSET_OWNER: u128 = 100;
+
+// Royalty assets on top of pallet_assets
+pub struct TestAssets;
+
+impl pallet_template::RoyaltyAssets<u128, u64> for TestAssets {
+    type AssetId = u32;
+    // the assets of the tests are minted at genesis, an asset that does not exist has no supply
+    fn exists(asset: u32) -> bool {
+        Assets::total_supply(asset) > 0
+    }
+    fn transfer(asset: u32, source: &u128, dest: &u128, amount: u64) -> DispatchResult {
+        Assets::transfer(Origin::signed(*source), asset, *dest, amount)
+    }
+}
+
+parameter_types! {
+    pub const CrmModuleId: ModuleId = ModuleId(*b"py/crmfd");
+    pub const MaxCrmDataLength: u32 = 1024;
+    pub const MinCrmDataLength: u32 = 48;
+    pub const MaxPrivateHashes: u32 = 3;
+    pub const MinIpfsHashLength: u32 = 46;
+    pub const MaxIpfsHashLength: u32 = 128;
+    pub const MaxContractsPerAccount: u32 = 10;
+    pub const MaxContractsPerBlock: u32 = 12;
+    pub const MaxPoolPayouts: u32 = 2;
+    pub const MaxTerritories: u32 = 3;
+    pub const MaxJsonDepth: u32 = 3;
+    pub const MaxListReturn: u32 = 6;
+    pub const MaxChangelogEntries: u32 = 3;
+}
+
+thread_local! {
+    static SOFT_DELETE: RefCell<bool> = RefCell::new(false);
+    static STRICT_JSON: RefCell<bool> = RefCell::new(false);
+    static UNIQUE_IPFS_HASH: RefCell<bool> = RefCell::new(false);
+    static SINGLE_HOLDER_QUORUM: RefCell<bool> = RefCell::new(false);
+    static STRICT_SCHEMA: RefCell<bool> = RefCell::new(false);
+    static STRICT_CID: RefCell<bool> = RefCell::new(false);
+    static DEPOSITS: RefCell<(u64, u64)> = RefCell::new((0, 0));
+    static CREATION_FEE: RefCell<u64> = RefCell::new(0);
+    static RESERVED_CRM_ID_MAX: RefCell<u32> = RefCell::new(0);
+    static UPDATE_LOCK_PERIOD: RefCell<u64> = RefCell::new(0);
+    static MAX_TOTAL_CONTRACTS: RefCell<u32> = RefCell::new(u32::MAX);
+    static CREATION_COOLDOWN: RefCell<u64> = RefCell::new(0);
+}
+
+// SoftDelete switched by the tests, hard delete by default
+pub struct SoftDelete;
+
+impl Get<bool> for SoftDelete {
+    fn get() -> bool {
+        SOFT_DELETE.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_soft_delete(soft: bool) {
+    SOFT_DELETE.with(|v| *v.borrow_mut() = soft);
+}
+
+// StrictJson switched by the tests, lenient validation by default
+pub struct StrictJson;
+
+impl Get<bool> for StrictJson {
+    fn get() -> bool {
+        STRICT_JSON.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_strict_json(strict: bool) {
+    STRICT_JSON.with(|v| *v.borrow_mut() = strict);
+}
+
+// UniqueIpfsHash switched by the tests, the contracts can share a hash by default
+pub struct UniqueIpfsHash;
+
+impl Get<bool> for UniqueIpfsHash {
+    fn get() -> bool {
+        UNIQUE_IPFS_HASH.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_unique_ipfs_hash(unique: bool) {
+    UNIQUE_IPFS_HASH.with(|v| *v.borrow_mut() = unique);
+}
+
+// SingleHolderQuorum switched by the tests, any quorum by default
+pub struct SingleHolderQuorum;
+
+impl Get<bool> for SingleHolderQuorum {
+    fn get() -> bool {
+        SINGLE_HOLDER_QUORUM.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_single_holder_quorum(check: bool) {
+    SINGLE_HOLDER_QUORUM.with(|v| *v.borrow_mut() = check);
+}
+
+// StrictSchema switched by the tests, any field accepted by default
+pub struct StrictSchema;
+
+impl Get<bool> for StrictSchema {
+    fn get() -> bool {
+        STRICT_SCHEMA.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_strict_schema(strict: bool) {
+    STRICT_SCHEMA.with(|v| *v.borrow_mut() = strict);
+}
+
+// StrictCid switched by the tests, any alphanumeric hash by default
+pub struct StrictCid;
+
+impl Get<bool> for StrictCid {
+    fn get() -> bool {
+        STRICT_CID.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_strict_cid(strict: bool) {
+    STRICT_CID.with(|v| *v.borrow_mut() = strict);
+}
+
+// DepositBase and DepositPerByte switched by the tests, no deposit by default
+pub struct DepositBase;
+
+impl Get<u64> for DepositBase {
+    fn get() -> u64 {
+        DEPOSITS.with(|v| v.borrow().0)
+    }
+}
+
+pub struct DepositPerByte;
+
+impl Get<u64> for DepositPerByte {
+    fn get() -> u64 {
+        DEPOSITS.with(|v| v.borrow().1)
+    }
+}
+
+pub fn set_deposits(base: u64, per_byte: u64) {
+    DEPOSITS.with(|v| *v.borrow_mut() = (base, per_byte));
+}
+
+// CreationFee switched by the tests, no fee by default
+pub struct CreationFee;
+
+impl Get<u64> for CreationFee {
+    fn get() -> u64 {
+        CREATION_FEE.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_creation_fee(fee: u64) {
+    CREATION_FEE.with(|v| *v.borrow_mut() = fee);
+}
+
+// ReservedCrmIdMax switched by the tests, no reserved range by default
+pub struct ReservedCrmIdMax;
+
+impl Get<u32> for ReservedCrmIdMax {
+    fn get() -> u32 {
+        RESERVED_CRM_ID_MAX.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_reserved_crm_id_max(max: u32) {
+    RESERVED_CRM_ID_MAX.with(|v| *v.borrow_mut() = max);
+}
+
+// UpdateLockPeriod switched by the tests, no lock by default
+pub struct UpdateLockPeriod;
+
+impl Get<u64> for UpdateLockPeriod {
+    fn get() -> u64 {
+        UPDATE_LOCK_PERIOD.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_update_lock_period(blocks: u64) {
+    UPDATE_LOCK_PERIOD.with(|v| *v.borrow_mut() = blocks);
+}
+
+// MaxTotalContracts switched by the tests, no limit by default
+pub struct MaxTotalContracts;
+
+impl Get<u32> for MaxTotalContracts {
+    fn get() -> u32 {
+        MAX_TOTAL_CONTRACTS.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_max_total_contracts(max: u32) {
+    MAX_TOTAL_CONTRACTS.with(|v| *v.borrow_mut() = max);
+}
+
+// CreationCooldown switched by the tests, no cooldown by default
+pub struct CreationCooldown;
+
+impl Get<u64> for CreationCooldown {
+    fn get() -> u64 {
+        CREATION_COOLDOWN.with(|v| *v.borrow())
+    }
+}
+
+pub fn set_creation_cooldown(blocks: u64) {
+    CREATION_COOLDOWN.with(|v| *v.borrow_mut() = blocks);
+}
+
 impl pallet_template::Config for Test {
     type Event = Event;
+    type Currency = Balances;
+    type ModuleId = CrmModuleId;
+    type MaxCrmDataLength = MaxCrmDataLength;
+    type MinCrmDataLength = MinCrmDataLength;
+    type MaxPrivateHashes = MaxPrivateHashes;
+    type MinIpfsHashLength = MinIpfsHashLength;
+    type MaxIpfsHashLength = MaxIpfsHashLength;
+    type MaxContractsPerAccount = MaxContractsPerAccount;
+    type MaxContractsPerBlock = MaxContractsPerBlock;
+    type MaxTotalContracts = MaxTotalContracts;
+    type CreationCooldown = CreationCooldown;
+    type CrmId = u32;
+    type ReservedCrmIdMax = ReservedCrmIdMax;
+    type MaxPoolPayouts = MaxPoolPayouts;
+    type MaxTerritories = MaxTerritories;
+    type SoftDelete = SoftDelete;
+    type UpdateLockPeriod = UpdateLockPeriod;
+    type StrictJson = StrictJson;
+    type UniqueIpfsHash = UniqueIpfsHash;
+    type SingleHolderQuorum = SingleHolderQuorum;
+    type StrictSchema = StrictSchema;
+    type StrictCid = StrictCid;
+    type MaxJsonDepth = MaxJsonDepth;
+    type MaxListReturn = MaxListReturn;
+    type MaxChangelogEntries = MaxChangelogEntries;
+    type DepositBase = DepositBase;
+    type DepositPerByte = DepositPerByte;
+    type CreationFee = CreationFee;
+    type CreationFeeHandler = ();
+    type SchedulerCall = Call;
+    type PalletsOrigin = OriginCaller;
+    type Scheduler = Scheduler;
+    type DustHandler = ();
+    type Signature = TestSignature;
+    type Public = TestSigner;
+    type ReporterAdminOrigin = system::EnsureRoot<u128>;
+    type Assets = TestAssets;
+    type WeightInfo = ();
+}
+
+// Signer of the test signatures, the account itself
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct TestSigner(pub u128);
+
+impl IdentifyAccount for TestSigner {
+    type AccountId = u128;
+    fn into_account(self) -> u128 {
+        self.0
+    }
+}
+
+// Test signature: the signer account and the message signed
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct TestSignature(pub u128, pub Vec<u8>);
+
+impl Verify for TestSignature {
+    type Signer = TestSigner;
+    fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &u128) -> bool {
+        self.0 == *signer && msg.get() == &self.1[..]
+    }
+}
+
+// the switches are kept by the test thread, every test starts with the defaults
+fn reset_switches() {
+    set_soft_delete(false);
+    set_strict_json(false);
+    set_unique_ipfs_hash(false);
+    set_single_holder_quorum(false);
+    set_strict_schema(false);
+    set_strict_cid(false);
+    set_deposits(0, 0);
+    set_creation_fee(0);
+    set_reserved_crm_id_max(0);
+    set_update_lock_period(0);
+    set_max_total_contracts(u32::MAX);
+    set_creation_cooldown(0);
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-    system::GenesisConfig::default()
+    reset_switches();
+    let mut t = system::GenesisConfig::default()
         .build_storage::<Test>()
-        .unwrap()
-        .into()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, 1_000), (2, 1_000), (3, 1_000), (4, 1_000)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+    let mut ext: sp_io::TestExternalities = t.into();
+    ext.execute_with(|| {
+        // events are not stored at genesis
+        System::set_block_number(1);
+        // two assets, 1_000 units each to the accounts 1 to 4
+        for asset in [ASSET_A, ASSET_B].iter() {
+            Assets::force_create(Origin::root(), *asset, ASSET_OWNER, 100, 1).unwrap();
+            for who in 1..=4 {
+                Assets::mint(Origin::signed(ASSET_OWNER), *asset, who, 1_000).unwrap();
+            }
+        }
+    });
+    ext
+}
+
+// Move to block n running the scheduler and the pallet at the beginning of every block.
+pub fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        System::set_block_number(System::block_number() + 1);
+        Scheduler::on_initialize(System::block_number());
+        TemplateModule::on_initialize(System::block_number());
+    }
+}
+
+// Mock runtime with 64 bit crmids, for the crmids beyond the range of u32. The parameters and the switches are the ones of
+// the Test runtime without reserved range, there are no assets and the campaigns are closed manually.
+pub mod wide {
+    use super::{
+        reset_switches, BlockHashCount, CreationCooldown, CreationFee, CrmModuleId, DepositBase, DepositPerByte,
+        ExistentialDeposit, MaxChangelogEntries, MaxContractsPerAccount, MaxContractsPerBlock, MaxCrmDataLength,
+        MaxIpfsHashLength, MaxJsonDepth, MaxListReturn, MaxLocks, MaxPoolPayouts, MaxPrivateHashes, MaxTerritories,
+        MaxTotalContracts, MinCrmDataLength, MinIpfsHashLength, SS58Prefix, SingleHolderQuorum, SoftDelete, StrictCid,
+        StrictJson, StrictSchema, TestSignature, TestSigner, UniqueIpfsHash, UpdateLockPeriod,
+    };
+    use crate as pallet_template;
+    use frame_support::parameter_types;
+    use frame_system as system;
+    use sp_core::H256;
+    use sp_runtime::{
+        testing::Header,
+        traits::{BlakeTwo256, IdentityLookup},
+    };
+
+    type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+    type Block = frame_system::mocking::MockBlock<Test>;
+
+    frame_support::construct_runtime!(
+        pub enum Test where
+            Block = Block,
+            NodeBlock = Block,
+            UncheckedExtrinsic = UncheckedExtrinsic,
+        {
+            System: frame_system::{Module, Call, Config, Storage, Event<T>},
+            Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+            TemplateModule: pallet_template::{Module, Call, Storage, Event<T>, Config<T>},
+        }
+    );
+
+    impl system::Config for Test {
+        type BaseCallFilter = ();
+        type BlockWeights = ();
+        type BlockLength = ();
+        type DbWeight = ();
+        type Origin = Origin;
+        type Call = Call;
+        type Index = u64;
+        type BlockNumber = u64;
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type AccountId = u128;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = Event;
+        type BlockHashCount = BlockHashCount;
+        type Version = ();
+        type PalletInfo = PalletInfo;
+        type AccountData = pallet_balances::AccountData<u64>;
+        type OnNewAccount = ();
+        type OnKilledAccount = ();
+        type SystemWeightInfo = ();
+        type SS58Prefix = SS58Prefix;
+    }
+
+    impl pallet_balances::Config for Test {
+        type MaxLocks = MaxLocks;
+        type Balance = u64;
+        type Event = Event;
+        type DustRemoval = ();
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = System;
+        type WeightInfo = ();
+    }
+
+    parameter_types! {
+        pub const ReservedCrmIdMax: u64 = 0;
+    }
+
+    impl pallet_template::Config for Test {
+        type Event = Event;
+        type Currency = Balances;
+        type ModuleId = CrmModuleId;
+        type MaxCrmDataLength = MaxCrmDataLength;
+        type MinCrmDataLength = MinCrmDataLength;
+        type MaxPrivateHashes = MaxPrivateHashes;
+        type MinIpfsHashLength = MinIpfsHashLength;
+        type MaxIpfsHashLength = MaxIpfsHashLength;
+        type MaxContractsPerAccount = MaxContractsPerAccount;
+        type MaxContractsPerBlock = MaxContractsPerBlock;
+        type MaxTotalContracts = MaxTotalContracts;
+        type CreationCooldown = CreationCooldown;
+        type CrmId = u64;
+        type ReservedCrmIdMax = ReservedCrmIdMax;
+        type MaxPoolPayouts = MaxPoolPayouts;
+        type MaxTerritories = MaxTerritories;
+        type SoftDelete = SoftDelete;
+        type UpdateLockPeriod = UpdateLockPeriod;
+        type StrictJson = StrictJson;
+        type UniqueIpfsHash = UniqueIpfsHash;
+        type SingleHolderQuorum = SingleHolderQuorum;
+        type StrictSchema = StrictSchema;
+        type StrictCid = StrictCid;
+        type MaxJsonDepth = MaxJsonDepth;
+        type MaxListReturn = MaxListReturn;
+        type MaxChangelogEntries = MaxChangelogEntries;
+        type DepositBase = DepositBase;
+        type DepositPerByte = DepositPerByte;
+        type CreationFee = CreationFee;
+        type CreationFeeHandler = ();
+        type SchedulerCall = Call;
+        type PalletsOrigin = OriginCaller;
+        type Scheduler = pallet_template::NoScheduler;
+        type DustHandler = ();
+        type Signature = TestSignature;
+        type Public = TestSigner;
+        type ReporterAdminOrigin = system::EnsureRoot<u128>;
+        type Assets = ();
+        type WeightInfo = ();
+    }
+
+    // Build genesis storage according to the mock runtime with 64 bit crmids.
+    pub fn new_test_ext() -> sp_io::TestExternalities {
+        reset_switches();
+        let mut t = system::GenesisConfig::default()
+            .build_storage::<Test>()
+            .unwrap();
+        pallet_balances::GenesisConfig::<Test> {
+            balances: vec![(1, 1_000), (2, 1_000), (3, 1_000), (4, 1_000)],
+        }
+        .assimilate_storage(&mut t)
+        .unwrap();
+        let mut ext: sp_io::TestExternalities = t.into();
+        // events are not stored at genesis
+        ext.execute_with(|| System::set_block_number(1));
+        ext
+    }
 }
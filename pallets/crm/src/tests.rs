@@ -1,5 +1,10 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{cid_multihash, is_cidv0, json_get_array, json_get_value, largest_remainder_split, mock::*, required_approvals, u32_to_percent, CampaignStatus, ChangeKind, CrmShares, CrmStorageMode, Error, Event as CrmEvent, HolderGroup, OwnerAction, ShareGroup, EARLY_EXIT_WEIGHT};
+use frame_support::{
+    assert_noop, assert_ok,
+    traits::Get,
+    weights::{GetDispatchInfo, Pays, WithPostDispatchInfo},
+};
+use sp_runtime::Percent;
 
 #[test]
 fn it_works_for_default_value() {
@@ -21,3 +26,3478 @@ fn correct_error_for_none_value() {
         );
     });
 }
+
+const IPFSHASH: &str = "QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u";
+const IPFSHASHPRIVATE: &str = "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o";
+
+// main crm data with the given json value for "ipfshashprivate"
+fn crmdata_with_private(private: &str) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":{},"globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51}}"#,
+        IPFSHASH, private
+    )
+    .into_bytes()
+}
+
+// main crm data with a crowdfunding share of 20 for the campaign given
+fn crmdata_with_crowdfunding(campaignid: u32) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":40,"masterquorum":51,"compositionshare":40,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"crodwfundingshares":20,"crowdfounders":"{}"}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, campaignid
+    )
+    .into_bytes()
+}
+
+fn crmdata() -> Vec<u8> {
+    crmdata_with_private(&format!(r#""{}""#, IPFSHASHPRIVATE))
+}
+
+// array of n distinct private hashes
+fn private_hashes(n: usize) -> String {
+    let hashes: Vec<String> = (0..n)
+        .map(|i| format!(r#""{}{}""#, &IPFSHASHPRIVATE[..44], 10 + i))
+        .collect();
+    format!("[{}]", hashes.join(","))
+}
+
+fn master() -> Vec<u8> {
+    br#"{"master": [{"nickname": "Bob","account": "0x0100000000000000000000000000000000000000000000000000000000000000","percentage":100}]}"#.to_vec()
+}
+
+fn composition() -> Vec<u8> {
+    br#"{"composition": [{"nickname": "Charlie","account": "0x0200000000000000000000000000000000000000000000000000000000000000","percentage":100}]}"#.to_vec()
+}
+
+fn new_contract(who: u128, crmid: u32, crmdata: Vec<u8>) -> frame_support::dispatch::DispatchResultWithPostInfo {
+    TemplateModule::new_contract(
+        Origin::signed(who),
+        crmid,
+        crmdata,
+        master(),
+        composition(),
+        Vec::new(),
+    )
+}
+
+fn crm_event_emitted(e: CrmEvent<Test>) -> bool {
+    System::events()
+        .iter()
+        .any(|r| r.event == Event::pallet_template(e.clone()))
+}
+
+#[test]
+fn campaign_rejects_invalid_caps() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            TemplateModule::create_campaign(Origin::signed(1), 1, 0, 100, 10),
+            Error::<Test>::InvalidCampaignCap
+        );
+        assert_noop!(
+            TemplateModule::create_campaign(Origin::signed(1), 1, 200, 100, 10),
+            Error::<Test>::InvalidCampaignCap
+        );
+    });
+}
+
+#[test]
+fn contribution_overshooting_hard_cap_is_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 150));
+        assert_noop!(
+            TemplateModule::contribute(Origin::signed(3), 1, 60),
+            Error::<Test>::HardCapExceeded
+        );
+        assert_eq!(TemplateModule::get_campaign(1).unwrap().raised, 150);
+        assert_eq!(Balances::free_balance(3), 1_000);
+    });
+}
+
+#[test]
+fn campaign_closes_early_at_hard_cap() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 150));
+        assert!(crm_event_emitted(CrmEvent::CampaignSoftCapReached(1, 150)));
+        assert_ok!(TemplateModule::contribute(Origin::signed(3), 1, 50));
+        assert!(crm_event_emitted(CrmEvent::CampaignHardCapReached(1, 200)));
+        assert_eq!(
+            TemplateModule::get_campaign(1).unwrap().status,
+            CampaignStatus::Succeeded
+        );
+        assert_eq!(Balances::free_balance(1), 1_200);
+        assert_noop!(
+            TemplateModule::contribute(Origin::signed(4), 1, 1),
+            Error::<Test>::CampaignClosed
+        );
+    });
+}
+
+#[test]
+fn campaign_closed_at_deadline_exactly_at_soft_cap_succeeds() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 40));
+        assert_ok!(TemplateModule::contribute(Origin::signed(3), 1, 60));
+        assert_noop!(
+            TemplateModule::close_campaign(Origin::signed(4), 1),
+            Error::<Test>::CampaignStillActive
+        );
+        System::set_block_number(11);
+        assert_ok!(TemplateModule::close_campaign(Origin::signed(4), 1));
+        assert!(crm_event_emitted(CrmEvent::CampaignSucceeded(1, 100)));
+        assert_eq!(
+            TemplateModule::get_campaign(1).unwrap().status,
+            CampaignStatus::Succeeded
+        );
+        assert_eq!(Balances::free_balance(1), 1_100);
+    });
+}
+
+#[test]
+fn failed_campaign_contributions_are_refundable() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 99));
+        System::set_block_number(11);
+        assert_ok!(TemplateModule::close_campaign(Origin::signed(4), 1));
+        assert_eq!(
+            TemplateModule::get_campaign(1).unwrap().status,
+            CampaignStatus::Failed
+        );
+        assert_ok!(TemplateModule::refund_contribution(Origin::signed(2), 1));
+        assert_eq!(Balances::free_balance(2), 1_000);
+        assert_noop!(
+            TemplateModule::refund_contribution(Origin::signed(2), 1),
+            Error::<Test>::NothingToRefund
+        );
+    });
+}
+
+#[test]
+fn campaigns_have_distinct_escrow_accounts() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 2, 100, 200, 10));
+        assert_ne!(TemplateModule::campaign_account_id(1), TemplateModule::campaign_account_id(2));
+        assert_ne!(TemplateModule::campaign_account_id(1), TemplateModule::contract_account_id(1));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 60));
+        assert_ok!(TemplateModule::contribute(Origin::signed(3), 1, 30));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 2, 150));
+        // the totals of each campaign reconcile independently
+        assert_eq!(Balances::free_balance(TemplateModule::campaign_account_id(1)), 90);
+        assert_eq!(Balances::free_balance(TemplateModule::campaign_account_id(2)), 150);
+        assert_eq!(Balances::free_balance(TemplateModule::account_id()), 0);
+        System::set_block_number(11);
+        assert_ok!(TemplateModule::close_campaign(Origin::signed(4), 1));
+        assert_ok!(TemplateModule::close_campaign(Origin::signed(4), 2));
+        // the failed campaign keeps the contributions not refunded, the succeeded one paid the owner
+        assert_ok!(TemplateModule::refund_contribution(Origin::signed(2), 1));
+        assert_eq!(Balances::free_balance(TemplateModule::campaign_account_id(1)), 30);
+        assert_eq!(Balances::free_balance(TemplateModule::campaign_account_id(2)), 0);
+        assert_eq!(Balances::free_balance(1), 1_150);
+    });
+}
+
+#[test]
+fn migration_moves_the_campaign_funds() {
+    use frame_support::traits::OnRuntimeUpgrade;
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 60));
+        // the contributions held by the account of the pallet before the escrow accounts of the campaigns
+        Balances::make_free_balance_be(&TemplateModule::campaign_account_id(1), 0);
+        Balances::make_free_balance_be(&TemplateModule::account_id(), 60);
+        crate::CrmStorageVersion::<Test>::put(5);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::crm_storage_version(), crate::STORAGE_VERSION);
+        assert_eq!(Balances::free_balance(TemplateModule::campaign_account_id(1)), 60);
+        assert_eq!(Balances::free_balance(TemplateModule::account_id()), 0);
+    });
+}
+
+#[test]
+fn private_hashes_at_the_limit_are_accepted() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata_with_private(&private_hashes(3))));
+        assert!(TemplateModule::get_crmdata(1).is_some());
+    });
+}
+
+#[test]
+fn private_hashes_over_the_limit_are_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_private(&private_hashes(4))),
+            Error::<Test>::TooManyPrivateHashes
+        );
+    });
+}
+
+#[test]
+fn campaign_is_closed_by_the_scheduler_at_the_deadline() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 5));
+        assert!(!crm_event_emitted(CrmEvent::CampaignCloseNotScheduled(1, 5)));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 100));
+        run_to_block(4);
+        assert_eq!(
+            TemplateModule::get_campaign(1).unwrap().status,
+            CampaignStatus::Active
+        );
+        run_to_block(5);
+        assert_eq!(
+            TemplateModule::get_campaign(1).unwrap().status,
+            CampaignStatus::Succeeded
+        );
+        assert_eq!(Balances::free_balance(1), 1_100);
+    });
+}
+
+#[test]
+fn campaign_not_scheduled_is_closed_manually() {
+    use codec::Encode;
+    new_test_ext().execute_with(|| {
+        // the task id of the campaign is taken, the scheduling fails
+        let id = (*b"crmcampg", 1u32).encode();
+        let call = Box::new(Call::System(frame_system::Call::remark(Vec::new())));
+        assert_ok!(Scheduler::schedule_named(Origin::root(), id, 10, None, 0, call));
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 5));
+        assert!(crm_event_emitted(CrmEvent::CampaignCloseNotScheduled(1, 5)));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 100));
+        run_to_block(5);
+        assert_eq!(
+            TemplateModule::get_campaign(1).unwrap().status,
+            CampaignStatus::Active
+        );
+        assert_ok!(TemplateModule::close_campaign(Origin::signed(3), 1));
+        assert_eq!(
+            TemplateModule::get_campaign(1).unwrap().status,
+            CampaignStatus::Succeeded
+        );
+    });
+}
+
+#[test]
+fn campaign_below_soft_cap_fails_at_the_deadline() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 5));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 50));
+        run_to_block(5);
+        assert_eq!(
+            TemplateModule::get_campaign(1).unwrap().status,
+            CampaignStatus::Failed
+        );
+        assert_noop!(
+            TemplateModule::contribute(Origin::signed(3), 1, 10),
+            Error::<Test>::CampaignClosed
+        );
+    });
+}
+
+#[test]
+fn hard_cap_cancels_the_scheduled_closing() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 5));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 200));
+        System::reset_events();
+        run_to_block(5);
+        assert!(System::events().is_empty());
+        assert_eq!(
+            TemplateModule::get_campaign(1).unwrap().status,
+            CampaignStatus::Succeeded
+        );
+    });
+}
+
+#[test]
+fn private_hash_is_appended_to_a_single_hash() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let hash = format!("{}10", &IPFSHASHPRIVATE[..44]).into_bytes();
+        assert_ok!(TemplateModule::add_private_hash(Origin::signed(1), 1, hash.clone(), None));
+        let hashes = json_get_array(
+            TemplateModule::get_crmdata(1).unwrap(),
+            b"ipfshashprivate".to_vec(),
+        );
+        assert_eq!(hashes, vec![IPFSHASHPRIVATE.as_bytes().to_vec(), hash]);
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(1, 1, 2, ChangeKind::IpfsHash)));
+    });
+}
+
+#[test]
+fn private_hash_is_appended_to_an_array() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata_with_private(&private_hashes(2))));
+        let hash = format!("{}20", &IPFSHASHPRIVATE[..44]).into_bytes();
+        assert_ok!(TemplateModule::add_private_hash(Origin::signed(1), 1, hash.clone(), None));
+        let hashes = json_get_array(
+            TemplateModule::get_crmdata(1).unwrap(),
+            b"ipfshashprivate".to_vec(),
+        );
+        assert_eq!(hashes.len(), 3);
+        assert_eq!(hashes[2], hash);
+        // the limit of private hashes is enforced
+        assert_noop!(
+            TemplateModule::add_private_hash(Origin::signed(1), 1, hash, None),
+            Error::<Test>::TooManyPrivateHashes
+        );
+    });
+}
+
+#[test]
+fn private_hash_can_be_added_by_the_owner_only() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(
+            TemplateModule::add_private_hash(Origin::signed(2), 1, IPFSHASH.as_bytes().to_vec(), None),
+            Error::<Test>::SignerIsNotOwner
+        );
+    });
+}
+
+// campaign 1 owned by account 1 with a soft cap of 300 fully funded by accounts 2,3,4
+fn funded_campaign() {
+    assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 300, 300, 10));
+    assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 100));
+    assert_ok!(TemplateModule::contribute(Origin::signed(3), 1, 100));
+    assert_ok!(TemplateModule::contribute(Origin::signed(4), 1, 100));
+}
+
+#[test]
+fn certificates_are_issued_to_the_backers() {
+    new_test_ext().execute_with(|| {
+        funded_campaign();
+        let mut total = 0;
+        for backer in 2..=4 {
+            let c = TemplateModule::get_crowdfunding_certificate(1, backer).unwrap();
+            assert_eq!(c.contributed, 100);
+            assert_eq!(c.issued_at, 1);
+            assert!(c.basis_points == 3_333 || c.basis_points == 3_334);
+            total += c.basis_points;
+        }
+        assert_eq!(total, 10_000);
+        assert!(crm_event_emitted(CrmEvent::CertificateIssued(2, 1, 3_334))
+            || crm_event_emitted(CrmEvent::CertificateIssued(3, 1, 3_334))
+            || crm_event_emitted(CrmEvent::CertificateIssued(4, 1, 3_334)));
+    });
+}
+
+#[test]
+fn uneven_certificates_sum_to_10000_basis_points() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 70, 70, 10));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 11));
+        assert_ok!(TemplateModule::contribute(Origin::signed(3), 1, 23));
+        assert_ok!(TemplateModule::contribute(Origin::signed(4), 1, 36));
+        let total: u32 = (2..=4)
+            .map(|b| TemplateModule::get_crowdfunding_certificate(1, b).unwrap().basis_points)
+            .sum();
+        assert_eq!(total, 10_000);
+    });
+}
+
+#[test]
+fn certificates_are_removed_with_the_contract() {
+    new_test_ext().execute_with(|| {
+        funded_campaign();
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        assert_noop!(
+            TemplateModule::force_remove_contract(Origin::signed(1), 1),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert!(TemplateModule::get_crmdata(1).is_none());
+        assert!(TemplateModule::get_crowdfunding_certificate(1, 2).is_none());
+    });
+}
+
+#[test]
+fn percent_conversion_rejects_invalid_percentages() {
+    assert_eq!(u32_to_percent(51), Some(Percent::from_percent(51)));
+    assert_eq!(u32_to_percent(100), Some(Percent::from_percent(100)));
+    assert_eq!(u32_to_percent(101), None);
+}
+
+#[test]
+fn contract_shares_are_decoded_as_percent() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let shares = TemplateModule::get_crm_shares(1).unwrap();
+        assert_eq!(shares.mastershare, Percent::from_percent(50));
+        assert_eq!(shares.masterquorum, Percent::from_percent(51));
+        assert_eq!(shares.globalquorum, Percent::from_percent(100));
+        assert_eq!(shares.crowdfundingshare, Percent::from_percent(0));
+        assert_eq!(TemplateModule::get_crm_shares(2), None);
+    });
+}
+
+#[test]
+fn contract_quorums_are_decoded() {
+    new_test_ext().execute_with(|| {
+        let crmdata = format!(
+            r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":80,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":60,"othercontractsshare":0,"othercontractsquorum":75}}"#,
+            IPFSHASH, IPFSHASHPRIVATE
+        );
+        assert_ok!(new_contract(1, 1, crmdata.into_bytes()));
+        assert_eq!(TemplateModule::get_crm_quorums(1), Some((80, 51, 60, 75)));
+        assert_eq!(TemplateModule::get_crm_quorums(2), None);
+    });
+}
+
+#[test]
+fn crowdfunding_share_partial_transfer() {
+    new_test_ext().execute_with(|| {
+        funded_campaign();
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        let bps = TemplateModule::get_crowdfunding_certificate(1, 2).unwrap().basis_points;
+        assert_ok!(TemplateModule::transfer_crowdfunding_share(Origin::signed(2), 1, 3, 1_000));
+        assert_eq!(
+            TemplateModule::get_crowdfunding_certificate(1, 2).unwrap().basis_points,
+            bps - 1_000
+        );
+        let total: u32 = (2..=4)
+            .map(|b| TemplateModule::get_crowdfunding_certificate(1, b).unwrap().basis_points)
+            .sum();
+        assert_eq!(total, 10_000);
+        assert!(crm_event_emitted(CrmEvent::CrowdfundingShareTransferred(2, 3, 1, 1_000)));
+    });
+}
+
+#[test]
+fn crowdfunding_share_full_transfer_removes_the_certificate() {
+    new_test_ext().execute_with(|| {
+        funded_campaign();
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        let bps = TemplateModule::get_crowdfunding_certificate(1, 2).unwrap().basis_points;
+        assert_noop!(
+            TemplateModule::transfer_crowdfunding_share(Origin::signed(2), 1, 5, bps + 1),
+            Error::<Test>::InsufficientCrowdfundingShare
+        );
+        assert_noop!(
+            TemplateModule::transfer_crowdfunding_share(Origin::signed(2), 1, 2, bps),
+            Error::<Test>::ShareTransferToSelf
+        );
+        assert_ok!(TemplateModule::transfer_crowdfunding_share(Origin::signed(2), 1, 5, bps));
+        assert!(TemplateModule::get_crowdfunding_certificate(1, 2).is_none());
+        let c = TemplateModule::get_crowdfunding_certificate(1, 5).unwrap();
+        assert_eq!(c.basis_points, bps);
+        assert_eq!(c.contributed, 100);
+    });
+}
+
+#[test]
+fn crowdfunding_share_transfer_requires_a_finalized_campaign() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 300, 300, 10));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 100));
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        assert_noop!(
+            TemplateModule::transfer_crowdfunding_share(Origin::signed(2), 1, 3, 100),
+            Error::<Test>::CampaignNotSucceeded
+        );
+    });
+}
+
+#[test]
+fn revoked_contracts_are_pruned_in_batches() {
+    new_test_ext().execute_with(|| {
+        for crmid in 1..=5 {
+            assert_ok!(new_contract(1, crmid, crmdata()));
+            assert_ok!(TemplateModule::revoke_contract(Origin::signed(1), crmid));
+        }
+        assert_ok!(new_contract(1, 6, crmdata()));
+        assert_noop!(
+            TemplateModule::revoke_contract(Origin::signed(2), 6),
+            Error::<Test>::SignerIsNotOwner.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        assert_noop!(
+            TemplateModule::prune_revoked(Origin::signed(1), 2),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_ok!(TemplateModule::prune_revoked(Origin::root(), 2));
+        assert!(crm_event_emitted(CrmEvent::CrmRevokedPruned(2)));
+        assert_eq!((1..=5).filter(|c| TemplateModule::get_crmdata(c).is_some()).count(), 3);
+        assert_ok!(TemplateModule::prune_revoked(Origin::root(), 2));
+        assert_ok!(TemplateModule::prune_revoked(Origin::root(), 2));
+        assert!(crm_event_emitted(CrmEvent::CrmRevokedPruned(1)));
+        assert_eq!((1..=5).filter(|c| TemplateModule::get_crmdata(c).is_some()).count(), 0);
+        // the contract not revoked is kept
+        assert!(TemplateModule::get_crmdata(6).is_some());
+        assert!(TemplateModule::get_crm_revoked(1).is_none());
+    });
+}
+
+#[test]
+fn crowdfunding_campaign_is_linked_to_the_contract() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        assert_eq!(TemplateModule::get_campaign_crm(1), Some(1));
+        assert_eq!(TemplateModule::campaign_contract(1), Some((1, 1)));
+        assert_eq!(TemplateModule::campaign_contract(2), None);
+        // no campaign is required without a crowdfunding share
+        assert_ok!(new_contract(1, 2, crmdata()));
+    });
+}
+
+#[test]
+fn crowdfunding_campaign_must_exist() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_crowdfunding(9)),
+            Error::<Test>::CampaignNotFound
+        );
+    });
+}
+
+#[test]
+fn crowdfunding_campaign_must_be_owned_by_the_contract_owner() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(2), 1, 100, 200, 10));
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_crowdfunding(1)),
+            Error::<Test>::CampaignNotOwned
+        );
+    });
+}
+
+#[test]
+fn crowdfunding_campaign_cannot_be_linked_twice() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        assert_noop!(
+            new_contract(1, 2, crmdata_with_crowdfunding(1)),
+            Error::<Test>::CampaignAlreadyLinked
+        );
+    });
+}
+
+#[test]
+fn limits_are_exposed_as_module_constants() {
+    new_test_ext().execute_with(|| {
+        let constants = TemplateModule::module_constants_metadata();
+        let names: Vec<_> = constants.iter().map(|c| c.name.clone()).collect();
+        assert_eq!(
+            format!("{:?}", names),
+            "[\"MaxCrmDataLength\", \"MaxPrivateHashes\", \"MaxContractsPerAccount\"]"
+        );
+        assert_eq!(<Test as crate::Config>::MaxCrmDataLength::get(), 1024);
+        assert_eq!(<Test as crate::Config>::MaxPrivateHashes::get(), 3);
+        assert_eq!(<Test as crate::Config>::MaxContractsPerAccount::get(), 10);
+    });
+}
+
+#[test]
+fn campaign_funded_is_emitted_once() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 150, 300, 10));
+        assert_ok!(TemplateModule::contribute(Origin::signed(2), 1, 100));
+        assert!(!crm_event_emitted(CrmEvent::CampaignFunded(1, 1, 100)));
+        assert_ok!(TemplateModule::contribute(Origin::signed(3), 1, 100));
+        assert_ok!(TemplateModule::contribute(Origin::signed(4), 1, 50));
+        let funded = System::events()
+            .iter()
+            .filter(|r| matches!(r.event, Event::pallet_template(CrmEvent::CampaignFunded(..))))
+            .count();
+        assert_eq!(funded, 1);
+        assert!(crm_event_emitted(CrmEvent::CampaignFunded(1, 1, 200)));
+    });
+}
+
+// sum of the royalties of a contract credited and not claimed
+fn royalty_ledger_total(crmid: u32) -> u64 {
+    crate::RoyaltyBalances::<Test>::iter_prefix(crmid).map(|(_, b)| b).sum()
+}
+
+#[test]
+fn royalties_are_distributed_by_the_shares() {
+    new_test_ext().execute_with(|| {
+        funded_campaign();
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        // master 40% to 1, composition 40% to 2, crowdfunding 20% to the backers 2, 3 and 4
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 1, 500, None));
+        assert!(crm_event_emitted(CrmEvent::RoyaltyCredited(1, 1, 200)));
+        assert!(crm_event_emitted(CrmEvent::RoyaltyCredited(2, 1, 200)));
+        assert!(crm_event_emitted(CrmEvent::RoyaltiesPaid(3, 1, 500, 0)));
+        // the royalties are credited to the ledger and held in the escrow account
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 200);
+        // the crowdfunding part of 100 is split 34/33/33, the unit left goes to the certificate of 3334 basis points
+        let mut backers = vec![
+            TemplateModule::get_royalty_balance(1, 2) - 200,
+            TemplateModule::get_royalty_balance(1, 3),
+            TemplateModule::get_royalty_balance(1, 4),
+        ];
+        backers.sort();
+        assert_eq!(backers, vec![33, 33, 34]);
+        assert_eq!(Balances::free_balance(3), 400);
+        assert_eq!(
+            TemplateModule::get_contract_escrow_balance(1),
+            royalty_ledger_total(1)
+        );
+        // a second payment accrues on the same entries
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(4), 1, 100, None));
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 240);
+        assert_eq!(
+            TemplateModule::get_contract_escrow_balance(1),
+            royalty_ledger_total(1)
+        );
+    });
+}
+
+#[test]
+fn royalties_are_rejected_without_the_group_members() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        // no certificates until the campaign succeeds
+        assert_noop!(
+            TemplateModule::pay_royalties(Origin::signed(3), 1, 500, None),
+            Error::<Test>::GroupMembersNotSet
+        );
+        assert_noop!(
+            TemplateModule::pay_royalties(Origin::signed(3), 1, 0, None),
+            Error::<Test>::InvalidRoyaltyAmount.with_weight(EARLY_EXIT_WEIGHT)
+        );
+    });
+}
+
+#[test]
+fn reserved_crmid_is_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            new_contract(1, u32::MAX, crmdata()),
+            Error::<Test>::ReservedCrmId.with_weight(EARLY_EXIT_WEIGHT)
+        );
+    });
+}
+
+#[test]
+fn auto_crmids_are_assigned_in_sequence() {
+    new_test_ext().execute_with(|| {
+        for crmid in 1..=3 {
+            assert_ok!(TemplateModule::new_contract_auto(
+                Origin::signed(1),
+                crmdata(),
+                master(),
+                composition(),
+                Vec::new()
+            ));
+            assert!(crm_event_emitted(CrmEvent::CrmAdded(1, crmid)));
+        }
+        assert_eq!(TemplateModule::next_crmid(), 4);
+        // the ids used manually are skipped
+        assert_ok!(new_contract(1, 4, crmdata()));
+        assert_ok!(TemplateModule::new_contract_auto(
+            Origin::signed(1),
+            crmdata(),
+            master(),
+            composition(),
+            Vec::new()
+        ));
+        assert!(crm_event_emitted(CrmEvent::CrmAdded(1, 5)));
+    });
+}
+
+#[test]
+fn auto_crmid_search_is_bounded() {
+    new_test_ext().execute_with(|| {
+        let probes = crate::MAX_CRMID_PROBES;
+        for crmid in 1..=probes {
+            crate::CrmDeleted::<Test>::insert(crmid, 1);
+        }
+        let auto = || TemplateModule::new_contract_auto(Origin::signed(1), crmdata(), master(), composition(), Vec::new());
+        assert_eq!(auto(), Err(Error::<Test>::NoAvailableCrmId.into()));
+        // the next call resumes the search after the ids probed
+        assert_eq!(TemplateModule::next_crmid(), probes + 1);
+        assert_ok!(auto());
+        assert!(crm_event_emitted(CrmEvent::CrmAdded(1, probes + 1)));
+    });
+}
+
+// main crm data with the given type of contract
+fn crmdata_with_type(contracttype: &str) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"type":"{}"}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, contracttype
+    )
+    .into_bytes()
+}
+
+#[test]
+fn contract_types_are_validated() {
+    new_test_ext().execute_with(|| {
+        for (crmid, contracttype) in ["single", "album", "ep", "compilation"].iter().enumerate() {
+            assert_ok!(new_contract(1, crmid as u32 + 1, crmdata_with_type(contracttype)));
+        }
+        assert_noop!(
+            new_contract(1, 10, crmdata_with_type("mixtape")),
+            Error::<Test>::InvalidContractType
+        );
+    });
+}
+
+#[test]
+fn royalties_are_claimed_once() {
+    new_test_ext().execute_with(|| {
+        funded_campaign();
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 1, 500, None));
+        let credited = TemplateModule::get_royalty_balance(1, 2);
+        // partial claim
+        assert_ok!(TemplateModule::claim_royalties(Origin::signed(2), 1, Some(100), None));
+        assert_eq!(TemplateModule::get_royalty_balance(1, 2), credited - 100);
+        assert_noop!(
+            TemplateModule::claim_royalties(Origin::signed(2), 1, Some(credited - 99), None),
+            Error::<Test>::InsufficientRoyaltyBalance.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        // full claim
+        assert_ok!(TemplateModule::claim_royalties(Origin::signed(2), 1, None, None));
+        assert!(crm_event_emitted(CrmEvent::RoyaltiesClaimed(2, 1, credited - 100)));
+        assert_eq!(Balances::free_balance(2), 900 + credited);
+        assert_noop!(
+            TemplateModule::claim_royalties(Origin::signed(2), 1, None, None),
+            Error::<Test>::NothingToClaim.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        assert_eq!(
+            TemplateModule::get_contract_escrow_balance(1),
+            royalty_ledger_total(1)
+        );
+    });
+}
+
+#[test]
+fn crm_ids_of_an_account_are_sorted() {
+    new_test_ext().execute_with(|| {
+        for crmid in [7, 3, 5, 1].iter() {
+            assert_ok!(new_contract(1, *crmid, crmdata()));
+        }
+        assert_ok!(new_contract(2, 4, crmdata()));
+        assert_eq!(TemplateModule::get_crm_ids(1), vec![1, 3, 5, 7]);
+        assert_eq!(TemplateModule::get_crm_ids(2), vec![4]);
+        assert!(TemplateModule::get_crm_ids(3).is_empty());
+    });
+}
+
+#[test]
+fn contracts_have_distinct_escrow_accounts() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, crmdata()));
+        assert_ne!(TemplateModule::contract_account_id(1), TemplateModule::contract_account_id(2));
+        assert_ne!(TemplateModule::contract_account_id(1), TemplateModule::account_id());
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 1, 100, None));
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 2, 300, None));
+        assert_ok!(TemplateModule::claim_royalties(Origin::signed(2), 2, None, None));
+        // the totals of each contract reconcile independently
+        assert_eq!(TemplateModule::get_contract_escrow_balance(1), 100);
+        assert_eq!(TemplateModule::get_contract_escrow_balance(1), royalty_ledger_total(1));
+        assert_eq!(TemplateModule::get_contract_escrow_balance(2), 150);
+        assert_eq!(TemplateModule::get_contract_escrow_balance(2), royalty_ledger_total(2));
+    });
+}
+
+#[test]
+fn royalties_in_assets_have_their_own_ledgers() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 1, 100, None));
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 1, 200, Some(ASSET_A)));
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(4), 1, 400, Some(ASSET_B)));
+        assert!(crm_event_emitted(CrmEvent::AssetRoyaltyCredited(1, 1, ASSET_A, 100)));
+        assert!(crm_event_emitted(CrmEvent::AssetRoyaltiesPaid(3, 1, ASSET_A, 200)));
+        // master 50% to 1 and composition 50% to 2 on each ledger
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 50);
+        assert_eq!(TemplateModule::get_asset_royalty_balance(1, (ASSET_A, 1)), 100);
+        assert_eq!(TemplateModule::get_asset_royalty_balance(1, (ASSET_B, 1)), 200);
+        let escrow = TemplateModule::contract_account_id(1);
+        assert_eq!(Assets::balance(ASSET_A, 3), 800);
+        assert_eq!(Assets::balance(ASSET_A, escrow), 200);
+        assert_eq!(Assets::balance(ASSET_B, escrow), 400);
+        // the claim withdraws from the ledger of the asset given only
+        assert_ok!(TemplateModule::claim_royalties(Origin::signed(2), 1, None, Some(ASSET_B)));
+        assert!(crm_event_emitted(CrmEvent::AssetRoyaltiesClaimed(2, 1, ASSET_B, 200)));
+        assert_eq!(Assets::balance(ASSET_B, 2), 1_200);
+        assert_eq!(TemplateModule::get_asset_royalty_balance(1, (ASSET_A, 2)), 100);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 2), 50);
+        assert_noop!(
+            TemplateModule::claim_royalties(Origin::signed(2), 1, None, Some(ASSET_B)),
+            Error::<Test>::NothingToClaim.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        assert_eq!(TemplateModule::get_contract_escrow_balance(1), royalty_ledger_total(1));
+    });
+}
+
+#[test]
+fn royalties_in_unknown_assets_are_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(
+            TemplateModule::pay_royalties(Origin::signed(3), 1, 100, Some(3)),
+            Error::<Test>::UnknownAsset
+        );
+    });
+}
+
+fn upsert(who: u128, crmid: u32, crmdata: Vec<u8>) -> frame_support::dispatch::DispatchResultWithPostInfo {
+    TemplateModule::upsert_crmdata(
+        Origin::signed(who),
+        crmid,
+        crmdata,
+        master(),
+        composition(),
+        Vec::new(),
+        None,
+    )
+}
+
+#[test]
+fn upsert_creates_then_updates_the_contract() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(upsert(1, 1, crmdata()));
+        assert!(crm_event_emitted(CrmEvent::CrmAdded(1, 1)));
+        assert_ok!(upsert(1, 1, crmdata_with_type("single")));
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(1, 1, 2, ChangeKind::FullReplace)));
+        assert_eq!(TemplateModule::get_crmdata(1), Some(crmdata_with_type("single")));
+        assert_noop!(upsert(2, 1, crmdata()), Error::<Test>::SignerIsNotOwner);
+    });
+}
+
+#[test]
+fn upsert_cannot_change_the_crowdfunding_share() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_ok!(upsert(1, 1, crmdata_with_crowdfunding(1)));
+        assert_noop!(upsert(1, 1, crmdata()), Error::<Test>::CrowdfundingShareImmutable);
+        assert_ok!(upsert(1, 1, crmdata_with_crowdfunding(1)));
+    });
+}
+
+#[test]
+fn royalty_split_has_no_dust() {
+    // deterministic pseudo random amounts and share layouts
+    let mut seed: u64 = 42;
+    let mut next = || {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) as u128
+    };
+    let total = 100 * 100 * 10_000;
+    for _ in 0..10_000 {
+        let amount = next() * (next() % 1_000 + 1);
+        let n = (next() % 8 + 1) as usize;
+        let mut weights = Vec::new();
+        let mut left = total;
+        for i in 0..n {
+            let w = if i == n - 1 { left } else { next() % (left + 1) };
+            left -= w;
+            weights.push(w);
+        }
+        let (parts, dust) = largest_remainder_split(amount, &weights, total);
+        assert_eq!(dust, 0);
+        assert_eq!(parts.iter().sum::<u128>(), amount);
+        // without the last weight the amount not covered is the dust
+        weights.pop();
+        let (parts, dust) = largest_remainder_split(amount, &weights, total);
+        assert_eq!(parts.iter().sum::<u128>() + dust, amount);
+    }
+}
+
+#[test]
+fn private_hash_equal_to_the_public_hash_is_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_private(&format!(r#"["{}","{}"]"#, IPFSHASHPRIVATE, IPFSHASH))),
+            Error::<Test>::DuplicatePublicPrivateHash
+        );
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(
+            TemplateModule::add_private_hash(Origin::signed(1), 1, IPFSHASH.as_bytes().to_vec(), None),
+            Error::<Test>::DuplicatePublicPrivateHash
+        );
+    });
+}
+
+#[test]
+fn duplicated_private_hashes_are_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_private(&format!(r#"["{}","{}"]"#, IPFSHASHPRIVATE, IPFSHASHPRIVATE))),
+            Error::<Test>::DuplicatePrivateHash
+        );
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(
+            TemplateModule::add_private_hash(Origin::signed(1), 1, IPFSHASHPRIVATE.as_bytes().to_vec(), None),
+            Error::<Test>::DuplicatePrivateHash
+        );
+    });
+}
+
+// main crm data with the given rate per play
+fn crmdata_with_rate(rate: u64) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"rateperplay":{}}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, rate
+    )
+    .into_bytes()
+}
+
+#[test]
+fn plays_are_paid_by_the_rate_per_play() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(new_contract(1, 1, crmdata_with_rate(0)), Error::<Test>::InvalidRatePerPlay);
+        assert_ok!(new_contract(1, 1, crmdata_with_rate(10)));
+        assert_noop!(
+            TemplateModule::report_plays(Origin::signed(3), 1, 5),
+            Error::<Test>::NotAuthorizedReporter.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        assert_ok!(TemplateModule::add_reporter(Origin::root(), 3));
+        assert_ok!(TemplateModule::report_plays(Origin::signed(3), 1, 5));
+        assert!(crm_event_emitted(CrmEvent::RoyaltiesPaid(3, 1, 50, 0)));
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 25);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 2), 25);
+    });
+}
+
+#[test]
+fn invalid_play_reports_are_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::add_reporter(Origin::root(), 3));
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, crmdata_with_rate(1_000)));
+        // contract without a rate
+        assert_noop!(
+            TemplateModule::report_plays(Origin::signed(3), 1, 5),
+            Error::<Test>::MissingRatePerPlay
+        );
+        assert_noop!(
+            TemplateModule::report_plays(Origin::signed(3), 2, 0),
+            Error::<Test>::InvalidPlays.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        // plays x rate overflows the balance
+        assert_noop!(
+            TemplateModule::report_plays(Origin::signed(3), 2, u64::MAX),
+            Error::<Test>::RoyaltyOverflow
+        );
+    });
+}
+
+// payload signed by the artist for new_contract_delegated, on the chain of the tests
+fn delegation_payload(nonce: u32, crmid: u32, crmdata: Vec<u8>) -> Vec<u8> {
+    use codec::Encode;
+    let genesis = System::block_hash(0);
+    (genesis, nonce, crmid, crmdata, master(), composition(), Vec::<u8>::new()).encode()
+}
+
+#[test]
+fn contract_is_created_on_behalf_of_the_artist() {
+    new_test_ext().execute_with(|| {
+        let payload = delegation_payload(0, 1, crmdata());
+        // tampered signature: the crm data signed are different
+        let tampered = delegation_payload(0, 1, crmdata_with_type("single"));
+        assert_noop!(
+            TemplateModule::new_contract_delegated(
+                Origin::signed(3), 2, 1, crmdata(), master(), composition(), Vec::new(), TestSignature(2, tampered)
+            ),
+            Error::<Test>::BadSignature
+        );
+        // signature of a different account
+        assert_noop!(
+            TemplateModule::new_contract_delegated(
+                Origin::signed(3), 2, 1, crmdata(), master(), composition(), Vec::new(), TestSignature(3, payload.clone())
+            ),
+            Error::<Test>::BadSignature
+        );
+        assert_ok!(TemplateModule::new_contract_delegated(
+            Origin::signed(3), 2, 1, crmdata(), master(), composition(), Vec::new(), TestSignature(2, payload)
+        ));
+        assert_eq!(TemplateModule::get_crm_owner(1), Some(2));
+        assert!(crm_event_emitted(CrmEvent::CrmAdded(2, 1)));
+        assert_eq!(TemplateModule::delegation_nonce(2), 1);
+    });
+}
+
+#[test]
+fn delegated_signature_cannot_be_replayed_after_a_removal() {
+    new_test_ext().execute_with(|| {
+        let delegated = |payload: Vec<u8>| {
+            TemplateModule::new_contract_delegated(
+                Origin::signed(3), 2, 1, crmdata(), master(), composition(), Vec::new(), TestSignature(2, payload)
+            )
+        };
+        assert_ok!(delegated(delegation_payload(0, 1, crmdata())));
+        let reserved = Balances::reserved_balance(2);
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        // the crmid is free again but the signature has been used
+        assert_noop!(delegated(delegation_payload(0, 1, crmdata())), Error::<Test>::BadSignature);
+        // a signature for another chain is rejected
+        let mut othergenesis = delegation_payload(1, 1, crmdata());
+        othergenesis[0] = 1;
+        assert_noop!(delegated(othergenesis), Error::<Test>::BadSignature);
+        // a new signature of the artist with the next nonce
+        assert_ok!(delegated(delegation_payload(1, 1, crmdata())));
+        assert_eq!(Balances::reserved_balance(2), reserved);
+        assert_eq!(TemplateModule::delegation_nonce(2), 2);
+    });
+}
+
+#[test]
+fn plays_are_counted_across_reporters() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::add_reporter(Origin::root(), 3));
+        assert_ok!(TemplateModule::add_reporter(Origin::root(), 4));
+        assert_ok!(new_contract(1, 1, crmdata_with_rate(1)));
+        assert_ok!(TemplateModule::report_plays(Origin::signed(3), 1, 10));
+        assert_ok!(TemplateModule::report_plays(Origin::signed(4), 1, 20));
+        assert_ok!(TemplateModule::report_plays(Origin::signed(3), 1, 5));
+        assert!(crm_event_emitted(CrmEvent::PlaysReported(4, 1, 20, 30)));
+        assert!(crm_event_emitted(CrmEvent::PlaysReported(3, 1, 5, 35)));
+        assert_eq!(TemplateModule::get_play_count(1), 35);
+        // the counter is removed with the contract
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(TemplateModule::get_play_count(1), 0);
+    });
+}
+
+#[test]
+fn json_value_of_the_last_field_is_extracted() {
+    let get = |j: &str, k: &str| json_get_value(j.as_bytes().to_vec(), k.as_bytes().to_vec());
+    assert_eq!(get(r#"{"a":"x","b":25}"#, "b"), b"25".to_vec());
+    assert_eq!(get(r#"{"a":"x","b":"yy"}"#, "b"), b"yy".to_vec());
+    // no trailing content after the value or the key
+    assert_eq!(get(r#"{"a":"x","b":25"#, "b"), b"25".to_vec());
+    assert!(get(r#"{"a":"x","b":"#, "b").is_empty());
+    assert!(get(r#"{"a":"x","b"#, "b").is_empty());
+    assert!(get("", "b").is_empty());
+}
+
+#[test]
+fn json_value_matches_the_whole_key() {
+    let get = |j: &str, k: &str| json_get_value(j.as_bytes().to_vec(), k.as_bytes().to_vec());
+    assert_eq!(get(r#"{"mastershare":50,"share":10}"#, "share"), b"10".to_vec());
+    assert_eq!(get(r#"{"share":10,"mastershare":50}"#, "share"), b"10".to_vec());
+    assert_eq!(get(r#"{"share":10,"mastershare":50}"#, "mastershare"), b"50".to_vec());
+    assert_eq!(get(r#"{ "mastershare":50, "share":10}"#, "share"), b"10".to_vec());
+    // a key quoted inside a string value is not a field
+    assert_eq!(get(r#"{"note":"x"share":99","share":10}"#, "share"), b"10".to_vec());
+    assert!(get(r#"{"note":"x"share":99"}"#, "share").is_empty());
+}
+
+#[test]
+fn removed_reporter_cannot_report() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata_with_rate(1)));
+        assert_noop!(
+            TemplateModule::add_reporter(Origin::signed(1), 3),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_ok!(TemplateModule::add_reporter(Origin::root(), 3));
+        assert!(TemplateModule::is_reporter(3));
+        assert_ok!(TemplateModule::report_plays(Origin::signed(3), 1, 2));
+        assert_ok!(TemplateModule::remove_reporter(Origin::root(), 3));
+        assert!(crm_event_emitted(CrmEvent::ReporterRemoved(3)));
+        assert_noop!(
+            TemplateModule::report_plays(Origin::signed(3), 1, 2),
+            Error::<Test>::NotAuthorizedReporter.with_weight(EARLY_EXIT_WEIGHT)
+        );
+    });
+}
+
+#[test]
+fn contract_owner_can_restrict_the_reporters() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata_with_rate(1)));
+        assert_ok!(TemplateModule::add_reporter(Origin::root(), 3));
+        assert_ok!(TemplateModule::add_reporter(Origin::root(), 4));
+        assert_noop!(
+            TemplateModule::set_contract_reporter(Origin::signed(2), 1, 4, true),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_ok!(TemplateModule::set_contract_reporter(Origin::signed(1), 1, 4, true));
+        assert_noop!(
+            TemplateModule::report_plays(Origin::signed(3), 1, 2),
+            Error::<Test>::NotAuthorizedReporter
+        );
+        assert_ok!(TemplateModule::report_plays(Origin::signed(4), 1, 2));
+        // without allowlist every reporter can report again
+        assert_ok!(TemplateModule::set_contract_reporter(Origin::signed(1), 1, 4, false));
+        assert_ok!(TemplateModule::report_plays(Origin::signed(3), 1, 2));
+    });
+}
+
+#[test]
+fn editors_can_update_the_contract() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(
+            TemplateModule::add_editor(Origin::signed(2), 1, 2),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_ok!(TemplateModule::add_editor(Origin::signed(1), 1, 2));
+        assert!(crm_event_emitted(CrmEvent::EditorAdded(1, 2)));
+        assert_noop!(
+            TemplateModule::add_editor(Origin::signed(1), 1, 2),
+            Error::<Test>::EditorAlreadyAdded
+        );
+        assert_ok!(upsert(2, 1, crmdata_with_type("ep")));
+        assert_eq!(TemplateModule::get_crmdata(1), Some(crmdata_with_type("ep")));
+        // a non editor is rejected
+        assert_noop!(upsert(3, 1, crmdata()), Error::<Test>::SignerIsNotOwner);
+        assert_ok!(TemplateModule::remove_editor(Origin::signed(1), 1, 2));
+        assert!(crm_event_emitted(CrmEvent::EditorRemoved(1, 2)));
+        assert_noop!(upsert(2, 1, crmdata()), Error::<Test>::SignerIsNotOwner);
+        assert_noop!(
+            TemplateModule::remove_editor(Origin::signed(1), 1, 2),
+            Error::<Test>::EditorNotFound
+        );
+    });
+}
+
+#[test]
+fn total_crms_follows_creates_and_removals() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TemplateModule::total_crms(), 0);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, crmdata()));
+        assert_ok!(new_contract(2, 3, crmdata()));
+        assert_eq!(TemplateModule::total_crms(), 3);
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 2));
+        assert_eq!(TemplateModule::total_crms(), 2);
+        assert_noop!(
+            TemplateModule::force_remove_contract(Origin::root(), 2),
+            Error::<Test>::InvalidContractId.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        assert_eq!(TemplateModule::total_crms(), 2);
+    });
+}
+
+#[test]
+fn subscription_pool_is_split_by_the_plays() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(TemplateModule::add_reporter(Origin::root(), 3));
+        for crmid in 1..=3 {
+            assert_ok!(new_contract(1, crmid, crmdata_with_rate(1)));
+        }
+        assert_ok!(TemplateModule::report_plays(Origin::signed(3), 1, 1));
+        assert_ok!(TemplateModule::report_plays(Origin::signed(3), 2, 3));
+        assert_ok!(TemplateModule::report_plays(Origin::signed(3), 3, 7));
+        assert_ok!(TemplateModule::fund_pool(Origin::signed(4), 600));
+        assert!(crm_event_emitted(CrmEvent::PoolFunded(4, 600)));
+        assert_noop!(
+            TemplateModule::distribute_pool(Origin::signed(1), 0),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_noop!(
+            TemplateModule::distribute_pool(Origin::root(), 1),
+            Error::<Test>::InvalidPeriod
+        );
+        // two contracts are paid by the first call, the period is closed
+        assert_ok!(TemplateModule::distribute_pool(Origin::root(), 0));
+        assert_eq!(TemplateModule::current_period(), 1);
+        assert!(TemplateModule::get_pool_distribution(0).is_some());
+        // the plays reported now are in the next period
+        assert_ok!(TemplateModule::report_plays(Origin::signed(3), 1, 2));
+        assert_eq!(TemplateModule::get_period_plays(1, 1), 2);
+        // the distribution is resumed and completed
+        assert_ok!(TemplateModule::distribute_pool(Origin::root(), 0));
+        assert!(TemplateModule::get_pool_distribution(0).is_none());
+        // 600 x 1/11, 600 x 3/11 and 600 x 7/11 rounded down
+        assert!(crm_event_emitted(CrmEvent::PoolSlicePaid(0, 1, 54)));
+        assert!(crm_event_emitted(CrmEvent::PoolSlicePaid(0, 2, 163)));
+        assert!(crm_event_emitted(CrmEvent::PoolSlicePaid(0, 3, 381)));
+        assert!(crm_event_emitted(CrmEvent::PoolDistributed(0, 598, 2)));
+        assert_eq!(TemplateModule::get_contract_escrow_balance(1), 1 + 54 + 2);
+        assert_eq!(TemplateModule::get_contract_escrow_balance(3), 7 + 381);
+        assert_eq!(TemplateModule::get_contract_escrow_balance(3), royalty_ledger_total(3));
+        for crmid in 1..=3 {
+            assert_eq!(TemplateModule::get_period_plays(0, crmid), 0);
+        }
+        // the rounding is carried to the current period
+        assert_eq!(TemplateModule::pool_balance(), 2);
+        assert_eq!(Balances::free_balance(TemplateModule::pool_account_id()), 2);
+    });
+}
+
+// main crm data with the master and composition shares in the scale given (empty for the default)
+fn crmdata_with_shares(sharescale: &str, mastershare: u32, compositionshare: u32) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":{},"masterquorum":51,"compositionshare":{},"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"sharescale":"{}"}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, mastershare, compositionshare, sharescale
+    )
+    .into_bytes()
+}
+
+#[test]
+fn shares_in_basis_points_total_10000() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata_with_shares("bps", 3333, 6667)));
+        assert_noop!(
+            new_contract(1, 2, crmdata_with_shares("bps", 50, 50)),
+            Error::<Test>::InvalidTotalShares
+        );
+        assert_noop!(
+            new_contract(1, 2, crmdata_with_shares("permille", 500, 500)),
+            Error::<Test>::InvalidShareScale
+        );
+        // the royalties are split by the basis points
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 1, 500, None));
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 167);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 2), 333);
+    });
+}
+
+#[test]
+fn shares_in_percent_total_100() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata_with_shares("", 40, 60)));
+        assert_ok!(new_contract(1, 2, crmdata_with_shares("percent", 40, 60)));
+        assert_noop!(
+            new_contract(1, 3, crmdata_with_shares("percent", 3333, 6667)),
+            Error::<Test>::InvalidMasterShare
+        );
+        assert_eq!(TemplateModule::get_crm_shares(2).unwrap().mastershare, Percent::from_percent(40));
+    });
+}
+
+// main crm data with the given mechanical license fee
+fn crmdata_with_license_fee(fee: u64) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"mechanicallicensefee":{}}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, fee
+    )
+    .into_bytes()
+}
+
+#[test]
+fn mechanical_license_fee_is_distributed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_license_fee(0)),
+            Error::<Test>::InvalidMechanicalLicenseFee
+        );
+        assert_ok!(new_contract(1, 1, crmdata_with_license_fee(100)));
+        assert_ok!(TemplateModule::acquire_mechanical_license(Origin::signed(3), 1));
+        assert!(crm_event_emitted(CrmEvent::MechanicalLicenseGranted(3, 1, 100)));
+        assert_eq!(Balances::free_balance(3), 900);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 50);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 2), 50);
+        let licenses = TemplateModule::get_licenses(1, 3);
+        assert_eq!(licenses.len(), 1);
+        assert_eq!(licenses[0].kind, crate::LicenseKind::Mechanical);
+        assert_eq!(licenses[0].granted_at, 1);
+        // the license is granted once
+        assert_noop!(
+            TemplateModule::acquire_mechanical_license(Origin::signed(3), 1),
+            Error::<Test>::LicenseAlreadyGranted
+        );
+    });
+}
+
+#[test]
+fn mechanical_license_requires_the_fee() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(
+            TemplateModule::acquire_mechanical_license(Origin::signed(3), 1),
+            Error::<Test>::MechanicalLicenseNotAvailable
+        );
+        assert_noop!(
+            TemplateModule::acquire_mechanical_license(Origin::signed(3), 2),
+            Error::<Test>::InvalidContractId
+        );
+    });
+}
+
+#[test]
+fn license_requests_are_granted_by_the_owner() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(TemplateModule::request_license(Origin::signed(3), 1, b"QmTerms".to_vec(), 200));
+        assert!(crm_event_emitted(CrmEvent::LicenseRequested(3, 0, 1, 200)));
+        // the offer is held in the escrow account of the contract
+        assert_eq!(Balances::free_balance(3), 800);
+        assert_eq!(TemplateModule::get_contract_escrow_balance(1), 200);
+        assert_noop!(
+            TemplateModule::grant_license(Origin::signed(2), 0, None),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_ok!(TemplateModule::grant_license(Origin::signed(1), 0, None));
+        assert!(crm_event_emitted(CrmEvent::LicenseGranted(3, 0, 1)));
+        assert!(TemplateModule::get_license_request(0).is_none());
+        let licenses = TemplateModule::get_licenses(1, 3);
+        assert_eq!(licenses.len(), 1);
+        assert_eq!(licenses[0].kind, crate::LicenseKind::Custom);
+        assert_eq!(licenses[0].terms_hash, b"QmTerms".to_vec());
+        // the offer is credited to the members of the contract
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 100);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 2), 100);
+        assert_eq!(TemplateModule::get_contract_escrow_balance(1), royalty_ledger_total(1));
+    });
+}
+
+#[test]
+fn rejected_license_requests_are_refunded() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(TemplateModule::request_license(Origin::signed(4), 1, b"QmTerms".to_vec(), 300));
+        assert_eq!(Balances::free_balance(4), 700);
+        assert_noop!(
+            TemplateModule::reject_license(Origin::signed(2), 0),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_ok!(TemplateModule::reject_license(Origin::signed(1), 0));
+        assert!(crm_event_emitted(CrmEvent::LicenseRejected(4, 0, 1, 300)));
+        assert_eq!(Balances::free_balance(4), 1_000);
+        assert_eq!(TemplateModule::get_contract_escrow_balance(1), 0);
+        assert!(TemplateModule::get_licenses(1, 4).is_empty());
+        assert_noop!(
+            TemplateModule::reject_license(Origin::signed(1), 0),
+            Error::<Test>::LicenseRequestNotFound
+        );
+    });
+}
+
+#[test]
+fn license_requests_need_a_contract() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            TemplateModule::request_license(Origin::signed(3), 9, b"QmTerms".to_vec(), 200),
+            Error::<Test>::InvalidContractId
+        );
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(
+            TemplateModule::request_license(Origin::signed(3), 1, Vec::new(), 200),
+            Error::<Test>::InvalidTermsHash
+        );
+        assert_noop!(
+            TemplateModule::request_license(Origin::signed(3), 1, b"QmTerms".to_vec(), 0),
+            Error::<Test>::InvalidLicenseOffer
+        );
+    });
+}
+
+#[test]
+fn removed_contracts_free_the_crmid() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert!(TemplateModule::get_crm_deleted(1).is_none());
+        assert_ok!(new_contract(1, 1, crmdata()));
+    });
+}
+
+#[test]
+fn soft_deleted_contracts_leave_a_tombstone() {
+    new_test_ext().execute_with(|| {
+        set_soft_delete(true);
+        assert_ok!(new_contract(1, 1, crmdata_with_license_fee(100)));
+        assert_ok!(TemplateModule::acquire_mechanical_license(Origin::signed(3), 1));
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert!(TemplateModule::get_crmdata(1).is_none());
+        assert_eq!(TemplateModule::get_crm_deleted(1), Some(1));
+        assert_eq!(TemplateModule::total_crms(), 0);
+        // the licenses granted are kept and the crmid is never reused
+        assert_eq!(TemplateModule::get_licenses(1, 3).len(), 1);
+        assert_noop!(new_contract(1, 1, crmdata()), Error::<Test>::DuplicatedCrmId.with_weight(EARLY_EXIT_WEIGHT));
+        assert_ok!(TemplateModule::new_contract_auto(
+            Origin::signed(1),
+            crmdata(),
+            master(),
+            composition(),
+            Vec::new()
+        ));
+        assert!(crm_event_emitted(CrmEvent::CrmAdded(1, 2)));
+    });
+}
+
+// grant the license requested by 3 on the contract 1, it returns the license id
+fn granted_license(expires_at: Option<u64>) -> u32 {
+    assert_ok!(TemplateModule::request_license(Origin::signed(3), 1, b"QmTerms".to_vec(), 200));
+    let requestid = TemplateModule::next_license_request_id() - 1;
+    assert_ok!(TemplateModule::grant_license(Origin::signed(1), requestid, expires_at));
+    TemplateModule::next_license_id() - 1
+}
+
+#[test]
+fn licenses_expire_at_the_expiry_block() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(TemplateModule::request_license(Origin::signed(3), 1, b"QmTerms".to_vec(), 200));
+        assert_noop!(
+            TemplateModule::grant_license(Origin::signed(1), 0, Some(1)),
+            Error::<Test>::InvalidLicenseExpiry
+        );
+        assert_ok!(TemplateModule::grant_license(Origin::signed(1), 0, Some(10)));
+        let licenseid = TemplateModule::next_license_id() - 1;
+        assert_eq!(TemplateModule::get_license(licenseid).unwrap().expires_at, Some(10));
+        System::set_block_number(9);
+        assert!(TemplateModule::is_license_active(licenseid));
+        System::set_block_number(10);
+        assert!(!TemplateModule::is_license_active(licenseid));
+        assert_noop!(
+            TemplateModule::revoke_license(Origin::signed(1), licenseid, b"QmReason".to_vec()),
+            Error::<Test>::LicenseExpired
+        );
+        // the licenses without expiry stay active
+        let licenseid = granted_license(None);
+        System::set_block_number(1_000);
+        assert!(TemplateModule::is_license_active(licenseid));
+    });
+}
+
+#[test]
+fn licenses_are_revoked_by_the_owner() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let licenseid = granted_license(Some(100));
+        assert!(TemplateModule::is_license_active(licenseid));
+        assert_noop!(
+            TemplateModule::revoke_license(Origin::signed(2), licenseid, b"QmReason".to_vec()),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_ok!(TemplateModule::revoke_license(Origin::signed(1), licenseid, b"QmReason".to_vec()));
+        assert!(crm_event_emitted(CrmEvent::LicenseRevoked(3, licenseid, 1, b"QmReason".to_vec())));
+        assert!(!TemplateModule::is_license_active(licenseid));
+        assert_noop!(
+            TemplateModule::revoke_license(Origin::signed(1), licenseid, b"QmReason".to_vec()),
+            Error::<Test>::LicenseAlreadyRevoked
+        );
+        assert_noop!(
+            TemplateModule::revoke_license(Origin::signed(1), licenseid + 1, b"QmReason".to_vec()),
+            Error::<Test>::LicenseNotFound
+        );
+        assert!(!TemplateModule::is_license_active(licenseid + 1));
+    });
+}
+
+#[test]
+fn duplicated_json_keys_are_rejected() {
+    new_test_ext().execute_with(|| {
+        let crmdata = format!(
+            r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"mastershare":60}}"#,
+            IPFSHASH, IPFSHASHPRIVATE
+        );
+        assert_noop!(
+            new_contract(1, 1, crmdata.clone().into_bytes()),
+            Error::<Test>::DuplicateJsonKey
+        );
+        assert_ok!(new_contract(1, 1, crmdata_with_type("album")));
+        assert_noop!(upsert(1, 1, crmdata.into_bytes()), Error::<Test>::DuplicateJsonKey);
+    });
+}
+
+// main crm data with the given json array of territories
+fn crmdata_with_territories(territories: &str) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"territories":{}}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, territories
+    )
+    .into_bytes()
+}
+
+#[test]
+fn territories_are_validated() {
+    new_test_ext().execute_with(|| {
+        for territories in [r#"["de","FR"]"#, r#"["DEU"]"#, r#"["DE","FR","DE"]"#, r#"["D1"]"#].iter() {
+            assert_noop!(
+                new_contract(1, 1, crmdata_with_territories(territories)),
+                Error::<Test>::InvalidTerritory
+            );
+        }
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_territories(r#"["DE","FR","IT","ES"]"#)),
+            Error::<Test>::TooManyTerritories
+        );
+        assert_ok!(new_contract(1, 1, crmdata_with_territories(r#"["DE","FR"]"#)));
+        assert_eq!(TemplateModule::get_crm_territories(1), vec![*b"DE", *b"FR"]);
+        assert!(TemplateModule::is_territory_covered(1, *b"FR"));
+        assert!(!TemplateModule::is_territory_covered(1, *b"US"));
+        // an empty array means worldwide
+        assert_ok!(new_contract(1, 2, crmdata_with_territories("[]")));
+        assert!(TemplateModule::get_crm_territories(2).is_empty());
+        assert!(TemplateModule::is_territory_covered(2, *b"US"));
+        // the territories follow the updates
+        assert_ok!(upsert(1, 1, crmdata_with_territories(r#"["US"]"#)));
+        assert_eq!(TemplateModule::get_crm_territories(1), vec![*b"US"]);
+    });
+}
+
+#[test]
+fn versioned_updates_detect_stale_versions() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_eq!(TemplateModule::crm_version(1), 1);
+        let update = |version| {
+            TemplateModule::upsert_crmdata(
+                Origin::signed(1),
+                1,
+                crmdata_with_type("single"),
+                master(),
+                composition(),
+                Vec::new(),
+                version,
+            )
+        };
+        assert_ok!(update(Some(1)));
+        assert_eq!(TemplateModule::crm_version(1), 2);
+        // an editor working on the version 1 is rejected
+        assert_noop!(update(Some(1)), Error::<Test>::VersionMismatch.with_weight(EARLY_EXIT_WEIGHT));
+        assert_noop!(
+            TemplateModule::add_private_hash(Origin::signed(1), 1, b"QmVwUSG9shzP8CLsRNGvoCe3zyRugKUqDeaRTCcvXz1nbw".to_vec(), Some(1)),
+            Error::<Test>::VersionMismatch
+        );
+        // the updates without version are not checked
+        assert_ok!(update(None));
+        assert_eq!(TemplateModule::crm_version(1), 3);
+    });
+}
+
+// main crm data with the given withholding percent
+fn crmdata_with_withholding(percent: u32) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"withholdingpercent":{}}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, percent
+    )
+    .into_bytes()
+}
+
+#[test]
+fn claims_are_withheld_by_the_withholding_percent() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_withholding(51)),
+            Error::<Test>::InvalidWithholdingPercent
+        );
+        assert_ok!(new_contract(1, 1, crmdata_with_withholding(15)));
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 1, 502, None));
+        assert_eq!(TemplateModule::get_royalty_balance(1, 2), 251);
+        // no withholding account, the claim fails
+        assert_noop!(
+            TemplateModule::claim_royalties(Origin::signed(2), 1, None, None),
+            Error::<Test>::WithholdingAccountNotSet
+        );
+        assert_noop!(
+            TemplateModule::set_withholding_account(Origin::signed(2), 1, Some(5)),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_ok!(TemplateModule::set_withholding_account(Origin::signed(1), 1, Some(5)));
+        assert_ok!(TemplateModule::claim_royalties(Origin::signed(2), 1, None, None));
+        // 15% of 251 is 37.65, rounded down
+        assert!(crm_event_emitted(CrmEvent::RoyaltiesWithheld(2, 1, 5, 37)));
+        assert_eq!(Balances::free_balance(5), 37);
+        assert_eq!(Balances::free_balance(2), 1_000 + 214);
+        assert_eq!(Balances::free_balance(5) + Balances::free_balance(2) - 1_000, 251);
+        assert_eq!(TemplateModule::get_contract_escrow_balance(1), royalty_ledger_total(1));
+    });
+}
+
+// main crm data with a crowdfunding share and no campaign
+fn crmdata_without_crowdfounders() -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":40,"masterquorum":51,"compositionshare":40,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"crodwfundingshares":20}}"#,
+        IPFSHASH, IPFSHASHPRIVATE
+    )
+    .into_bytes()
+}
+
+#[test]
+fn crowdfounders_can_be_set_after_the_creation() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata_without_crowdfounders()));
+        assert_eq!(TemplateModule::crm_campaign(1), None);
+        funded_campaign();
+        assert_noop!(
+            TemplateModule::set_crowdfounders(Origin::signed(2), 1, 1),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_noop!(
+            TemplateModule::set_crowdfounders(Origin::signed(1), 1, 9),
+            Error::<Test>::CampaignNotFound
+        );
+        assert_ok!(TemplateModule::set_crowdfounders(Origin::signed(1), 1, 1));
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(1, 1, 2, ChangeKind::Shares)));
+        assert_eq!(TemplateModule::crm_campaign(1), Some(1));
+        assert_eq!(TemplateModule::get_campaign_crm(1), Some(1));
+        assert_eq!(TemplateModule::crm_version(1), 2);
+        // the backers get the crowdfunding share
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 1, 500, None));
+        assert!(TemplateModule::get_royalty_balance(1, 4) > 0);
+        // the campaign is updated and the link is moved
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 2, 100, 200, 10));
+        assert_ok!(TemplateModule::set_crowdfounders(Origin::signed(1), 1, 2));
+        assert_eq!(TemplateModule::crm_campaign(1), Some(2));
+        assert_eq!(TemplateModule::get_campaign_crm(1), None);
+        assert_eq!(TemplateModule::get_campaign_crm(2), Some(1));
+    });
+}
+
+#[test]
+fn crowdfounders_require_a_crowdfunding_share() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(TemplateModule::create_campaign(Origin::signed(1), 1, 100, 200, 10));
+        assert_noop!(
+            TemplateModule::set_crowdfounders(Origin::signed(1), 1, 1),
+            Error::<Test>::NoCrowdFundingShare
+        );
+        assert_noop!(
+            TemplateModule::set_crowdfounders(Origin::signed(1), 2, 1),
+            Error::<Test>::InvalidContractId
+        );
+    });
+}
+
+// SS58 address (generic substrate format) of the account 7
+const DONATION_ACCOUNT: &str = "5CDtC7L6cnpYG3PZB9CXwJJ4pvMy7gc1uSmwWyNNqdCtFnze";
+
+// main crm data with the given donation fields
+fn crmdata_with_donation(donation: &str) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":45,"masterquorum":51,"compositionshare":45,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51{}}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, donation
+    )
+    .into_bytes()
+}
+
+#[test]
+fn donation_share_is_paid_to_the_donation_account() {
+    new_test_ext().execute_with(|| {
+        let donation = format!(r#","donationshare":10,"donationaccount":"{}""#, DONATION_ACCOUNT);
+        assert_ok!(new_contract(1, 1, crmdata_with_donation(&donation)));
+        assert_eq!(TemplateModule::get_crm_shares(1).unwrap().donationshare, Percent::from_percent(10));
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 1, 500, None));
+        assert!(crm_event_emitted(CrmEvent::DonationPaid(3, 1, 7, 50)));
+        assert!(crm_event_emitted(CrmEvent::RoyaltiesPaid(3, 1, 450, 0)));
+        assert_eq!(Balances::free_balance(7), 50);
+        assert_eq!(Balances::free_balance(3), 500);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 225);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 2), 225);
+        assert_eq!(TemplateModule::get_contract_escrow_balance(1), royalty_ledger_total(1));
+    });
+}
+
+#[test]
+fn donation_account_must_be_a_valid_ss58_address() {
+    new_test_ext().execute_with(|| {
+        // checksum changed
+        let invalid = format!(r#","donationshare":10,"donationaccount":"{}f""#, &DONATION_ACCOUNT[..DONATION_ACCOUNT.len() - 1]);
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_donation(&invalid)),
+            Error::<Test>::InvalidDonationAccount
+        );
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_donation(r#","donationshare":10,"donationaccount":"0x07""#)),
+            Error::<Test>::InvalidDonationAccount
+        );
+    });
+}
+
+#[test]
+fn donation_share_requires_the_donation_account() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_donation(r#","donationshare":10"#)),
+            Error::<Test>::MissingDonationAccount
+        );
+        // the donation share is included in the total of the shares
+        let donation = format!(r#","donationshare":20,"donationaccount":"{}""#, DONATION_ACCOUNT);
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_donation(&donation)),
+            Error::<Test>::InvalidTotalShares
+        );
+    });
+}
+
+// main crm data of a derivative work with the given parent contract, the crmid of any runtime
+fn crmdata_with_parent(parent: impl std::fmt::Display, passthrough: u32) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"parentcontract":{{"id":{},"passthrough":{}}}}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, parent, passthrough
+    )
+    .into_bytes()
+}
+
+#[test]
+fn derivative_works_pass_the_royalties_through_the_parents() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(new_contract(1, 2, crmdata_with_parent(1, 10)), Error::<Test>::ParentContractNotFound);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(new_contract(1, 2, crmdata_with_parent(1, 0)), Error::<Test>::InvalidPassthrough);
+        assert_noop!(new_contract(1, 2, crmdata_with_parent(1, 101)), Error::<Test>::InvalidPassthrough);
+        // 3 is a cover of 2 (50%) that is a remix of 1 (10%)
+        assert_ok!(new_contract(1, 2, crmdata_with_parent(1, 10)));
+        assert_ok!(new_contract(1, 3, crmdata_with_parent(2, 50)));
+        assert_eq!(TemplateModule::get_derivative_of(3), Some((2, Percent::from_percent(50))));
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 3, 600, None));
+        assert!(crm_event_emitted(CrmEvent::RoyaltiesPassedThrough(3, 2, 300)));
+        assert!(crm_event_emitted(CrmEvent::RoyaltiesPassedThrough(2, 1, 30)));
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 15);
+        assert_eq!(TemplateModule::get_royalty_balance(2, 1), 135);
+        assert_eq!(TemplateModule::get_royalty_balance(3, 1), 150);
+        assert_eq!(TemplateModule::get_royalty_balance(3, 2), 150);
+        assert_eq!(Balances::free_balance(3), 400);
+        for crmid in 1..=3 {
+            assert_eq!(TemplateModule::get_contract_escrow_balance(crmid), royalty_ledger_total(crmid));
+        }
+    });
+}
+
+#[test]
+fn passthrough_stops_at_the_maximum_depth() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        for crmid in 2..=9 {
+            assert_ok!(new_contract(1, crmid, crmdata_with_parent(crmid - 1, 50)));
+        }
+        // a parent set later on the root makes the chain of 9 one level deeper than MAX_PARENT_DEPTH
+        assert_ok!(new_contract(1, 10, crmdata()));
+        assert_ok!(upsert(1, 1, crmdata_with_parent(10, 50)));
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(3), 9, 512, None));
+        let passed = System::events()
+            .iter()
+            .filter(|r| matches!(r.event, Event::pallet_template(CrmEvent::RoyaltiesPassedThrough(..))))
+            .count();
+        assert_eq!(passed as u32, crate::MAX_PARENT_DEPTH);
+        assert!(crm_event_emitted(CrmEvent::RoyaltiesPassedThrough(2, 1, 2)));
+        assert_eq!(TemplateModule::get_contract_escrow_balance(10), 0);
+        assert_eq!(Balances::free_balance(3), 488);
+    });
+}
+
+#[test]
+fn cyclic_derivative_works_are_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, crmdata_with_parent(1, 10)));
+        assert_ok!(new_contract(1, 3, crmdata_with_parent(2, 10)));
+        // 1 cannot become a derivative of 3, or of itself
+        assert_noop!(upsert(1, 1, crmdata_with_parent(3, 10)), Error::<Test>::CyclicParentContract);
+        assert_noop!(upsert(1, 1, crmdata_with_parent(1, 10)), Error::<Test>::CyclicParentContract);
+        assert_eq!(TemplateModule::get_derivative_of(1), None);
+    });
+}
+
+// main crm data with a trailing comma
+fn crmdata_with_trailing_comma() -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,}}"#,
+        IPFSHASH, IPFSHASHPRIVATE
+    )
+    .into_bytes()
+}
+
+#[test]
+fn strict_json_rejects_trailing_commas_in_objects() {
+    new_test_ext().execute_with(|| {
+        // the lenient validation accepts the trailing comma
+        assert_ok!(new_contract(1, 1, crmdata_with_trailing_comma()));
+        set_strict_json(true);
+        assert_noop!(new_contract(1, 2, crmdata_with_trailing_comma()), Error::<Test>::InvalidJson);
+        assert_ok!(new_contract(1, 2, crmdata()));
+    });
+}
+
+#[test]
+fn strict_json_rejects_trailing_commas_in_arrays() {
+    new_test_ext().execute_with(|| {
+        set_strict_json(true);
+        let master = br#"{"master": [{"nickname": "Bob","account": "0x0100000000000000000000000000000000000000000000000000000000000000","percentage":100},]}"#.to_vec();
+        assert_noop!(
+            TemplateModule::new_contract(Origin::signed(1), 1, crmdata(), master, composition(), Vec::new()),
+            Error::<Test>::InvalidJson
+        );
+        // a comma in a string is not a trailing comma
+        let master = br#"{"master": [{"nickname": "Bob,]","account": "0x0100000000000000000000000000000000000000000000000000000000000000","percentage":100}]}"#.to_vec();
+        assert_ok!(TemplateModule::new_contract(Origin::signed(1), 1, crmdata(), master, composition(), Vec::new()));
+        let private = format!(r#"["{}",]"#, IPFSHASHPRIVATE);
+        assert_noop!(new_contract(1, 2, crmdata_with_private(&private)), Error::<Test>::InvalidJson);
+    });
+}
+
+#[test]
+fn crm_data_is_stored_decoded() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata_with_shares("percent", 40, 60)));
+        let contract = TemplateModule::get_crm_contract(1).unwrap();
+        assert_eq!(contract.ipfshash, IPFSHASH.as_bytes().to_vec());
+        assert_eq!(contract.ipfshashprivate, vec![IPFSHASHPRIVATE.as_bytes().to_vec()]);
+        assert_eq!((contract.mastershare, contract.compositionshare), (4_000, 6_000));
+        assert_eq!(contract.globalquorum, 100);
+        assert_eq!(contract.campaignid, None);
+        // the json submitted is kept
+        assert_eq!(TemplateModule::get_crmdata(1), Some(crmdata_with_shares("percent", 40, 60)));
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(TemplateModule::get_crm_contract(1), None);
+    });
+}
+
+#[test]
+fn migration_decodes_the_crm_data_stored() {
+    use frame_support::traits::OnRuntimeUpgrade;
+    new_test_ext().execute_with(|| {
+        // storage in the old format, the crm data only
+        crate::CrmData::<Test>::insert(1, crmdata());
+        crate::CrmData::<Test>::insert(2, crmdata_with_crowdfunding(5));
+        crate::CrmData::<Test>::insert(3, br#"{"ipfshash":"","globalquorum":200}"#.to_vec());
+        assert_eq!(TemplateModule::crm_storage_version(), 0);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::crm_storage_version(), crate::STORAGE_VERSION);
+        assert_eq!(TemplateModule::get_crm_contract(1).unwrap().mastershare, 5_000);
+        assert_eq!(TemplateModule::get_crm_contract(2).unwrap().campaignid, Some(5));
+        assert_eq!(TemplateModule::get_crm_contract(2).unwrap().crowdfundingshare, 2_000);
+        // the contract that cannot be decoded is skipped and kept
+        assert_eq!(TemplateModule::get_crm_contract(3), None);
+        assert!(TemplateModule::get_crmdata(3).is_some());
+        // the migration runs once
+        crate::CrmData::<Test>::insert(4, crmdata());
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::get_crm_contract(4), None);
+    });
+}
+
+#[test]
+fn numbers_can_be_quoted_or_bare() {
+    new_test_ext().execute_with(|| {
+        let quoted = format!(
+            r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":"100","mastershare":"50","masterquorum":"51","compositionshare": "50","compositionquorum": 51 ,"othercontractsshare":"0","othercontractsquorum":"51"}}"#,
+            IPFSHASH, IPFSHASHPRIVATE
+        );
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, quoted.into_bytes()));
+        assert_eq!(TemplateModule::get_crm_shares(1), TemplateModule::get_crm_shares(2));
+        assert_eq!(TemplateModule::get_crm_contract(1), TemplateModule::get_crm_contract(2));
+        assert_eq!(TemplateModule::get_crm_contract(2).unwrap().masterquorum, 51);
+        // a quoted quorum out of range is rejected as the bare one
+        let quoted = format!(
+            r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":"101","compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51}}"#,
+            IPFSHASH, IPFSHASHPRIVATE
+        );
+        assert_noop!(new_contract(1, 3, quoted.into_bytes()), Error::<Test>::InvalidMasterQuorum);
+    });
+}
+
+// main crm data padded with a "note" field to the given length
+fn crmdata_with_length(len: usize) -> Vec<u8> {
+    let base = format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"note":""}}"#,
+        IPFSHASH, IPFSHASHPRIVATE
+    );
+    let padding = "x".repeat(len - base.len());
+    base.replace(r#""note":"""#, &format!(r#""note":"{}""#, padding)).into_bytes()
+}
+
+#[test]
+fn crm_data_length_follows_the_config() {
+    new_test_ext().execute_with(|| {
+        let min = <Test as crate::Config>::MinCrmDataLength::get() as usize;
+        let max = <Test as crate::Config>::MaxCrmDataLength::get() as usize;
+        let short = |len: usize| format!(r#"{{"a":"{}"}}"#, "x".repeat(len - 8)).into_bytes();
+        assert_noop!(new_contract(1, 1, short(min - 1)), Error::<Test>::CrmDataTooShort.with_weight(EARLY_EXIT_WEIGHT));
+        // the length is accepted, the content is not
+        assert_eq!(short(min).len(), min);
+        assert!(new_contract(1, 1, short(min)) != Err(Error::<Test>::CrmDataTooShort.with_weight(EARLY_EXIT_WEIGHT)));
+        assert_eq!(crmdata_with_length(max).len(), max);
+        assert_ok!(new_contract(1, 1, crmdata_with_length(max)));
+        assert_noop!(new_contract(1, 2, crmdata_with_length(max + 1)), Error::<Test>::CrmDataTooLong.with_weight(EARLY_EXIT_WEIGHT));
+    });
+}
+
+#[test]
+fn contracts_per_block_are_capped() {
+    new_test_ext().execute_with(|| {
+        let cap = <Test as crate::Config>::MaxContractsPerBlock::get();
+        // two accounts, the cap is global
+        for crmid in 1..=cap {
+            assert_ok!(new_contract(if crmid % 2 == 0 { 1 } else { 2 }, crmid, crmdata()));
+        }
+        assert_eq!(TemplateModule::contracts_in_block(), cap);
+        assert_noop!(new_contract(3, cap + 1, crmdata()), Error::<Test>::BlockCapacityReached.with_weight(EARLY_EXIT_WEIGHT));
+        // the counter is reset in the next block
+        run_to_block(2);
+        assert_eq!(TemplateModule::contracts_in_block(), 0);
+        assert_ok!(new_contract(3, cap + 1, crmdata()));
+        assert_eq!(TemplateModule::contracts_in_block(), 1);
+    });
+}
+
+#[test]
+fn contracts_per_account_are_capped() {
+    new_test_ext().execute_with(|| {
+        let max = <Test as crate::Config>::MaxContractsPerAccount::get();
+        for crmid in 1..=max {
+            assert_ok!(new_contract(1, crmid, crmdata()));
+        }
+        assert_eq!(TemplateModule::get_contract_count(1), max);
+        assert_noop!(new_contract(1, max + 1, crmdata()), Error::<Test>::TooManyContracts.with_weight(EARLY_EXIT_WEIGHT));
+        // a removal frees a slot
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(TemplateModule::get_contract_count(1), max - 1);
+        assert_ok!(new_contract(1, max + 1, crmdata()));
+        assert_eq!(TemplateModule::get_contract_count(1), max);
+    });
+}
+
+#[test]
+fn change_proposals_are_capped_by_the_maximum_length() {
+    new_test_ext().execute_with(|| {
+        let max = <Test as crate::Config>::MaxCrmDataLength::get() as usize;
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(
+            TemplateModule::change_proposal_crmdata(Origin::signed(1), 1, crmdata_with_length(max + 1)),
+            Error::<Test>::CrmDataTooLong.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        // the length is accepted, the content is not (no crmid)
+        assert!(
+            TemplateModule::change_proposal_crmdata(Origin::signed(1), 1, crmdata_with_length(max))
+                != Err(Error::<Test>::CrmDataTooLong.with_weight(EARLY_EXIT_WEIGHT))
+        );
+    });
+}
+
+#[test]
+fn transfer_moves_the_contract_count() {
+    new_test_ext().execute_with(|| {
+        let max = <Test as crate::Config>::MaxContractsPerAccount::get();
+        for crmid in 1..=max {
+            assert_ok!(new_contract(1, crmid, crmdata()));
+        }
+        assert_ok!(new_contract(2, max + 1, crmdata()));
+        // the account 1 is at the cap and cannot receive contracts
+        assert_noop!(
+            TemplateModule::transfer_contract(Origin::signed(2), max + 1, 1),
+            Error::<Test>::TooManyContracts
+        );
+        assert_noop!(
+            TemplateModule::transfer_contract(Origin::signed(2), 1, 3),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_ok!(TemplateModule::transfer_contract(Origin::signed(1), 1, 2));
+        assert!(crm_event_emitted(CrmEvent::ContractTransferred(1, 1, 2)));
+        assert_eq!(TemplateModule::get_crm_owner(1), Some(2));
+        assert_eq!(TemplateModule::get_contract_count(1), max - 1);
+        assert_eq!(TemplateModule::get_contract_count(2), 2);
+        // the removal decrements the new owner
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(TemplateModule::get_contract_count(2), 1);
+        assert_ok!(new_contract(1, max + 2, crmdata()));
+    });
+}
+
+#[test]
+fn contracts_are_found_by_ipfshash() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata_with_type("single")));
+        assert_ok!(new_contract(2, 2, crmdata()));
+        assert_eq!(TemplateModule::find_crm_by_ipfshash(1, IPFSHASH.as_bytes().to_vec()), Some(1));
+        assert_eq!(TemplateModule::find_crm_by_ipfshash(2, IPFSHASH.as_bytes().to_vec()), Some(2));
+        // the hash of another account or a partial hash is not found
+        assert_eq!(TemplateModule::find_crm_by_ipfshash(3, IPFSHASH.as_bytes().to_vec()), None);
+        assert_eq!(TemplateModule::find_crm_by_ipfshash(1, IPFSHASH[..10].as_bytes().to_vec()), None);
+    });
+}
+
+#[test]
+fn crmids_are_unique_between_the_accounts() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 7, crmdata()));
+        assert_noop!(new_contract(2, 7, crmdata()), Error::<Test>::DuplicatedCrmId.with_weight(EARLY_EXIT_WEIGHT));
+        assert_eq!(TemplateModule::owner_of(7), Some(1));
+        assert_ok!(TemplateModule::transfer_contract(Origin::signed(1), 7, 2));
+        assert_eq!(TemplateModule::owner_of(7), Some(2));
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 7));
+        assert_eq!(TemplateModule::owner_of(7), None);
+        assert_ok!(new_contract(2, 7, crmdata()));
+    });
+}
+
+// main crm data with an extra field nested to the given depth (the crm data itself is the first level)
+fn crmdata_with_depth(depth: usize) -> Vec<u8> {
+    let nested = format!("{}1{}", r#"{"a":"#.repeat(depth - 1), "}".repeat(depth - 1));
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"extra":{}}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, nested
+    )
+    .into_bytes()
+}
+
+#[test]
+fn json_deeper_than_the_maximum_is_rejected() {
+    new_test_ext().execute_with(|| {
+        let max = <Test as crate::Config>::MaxJsonDepth::get() as usize;
+        assert_ok!(new_contract(1, 1, crmdata_with_depth(max)));
+        assert_noop!(new_contract(1, 2, crmdata_with_depth(max + 1)), Error::<Test>::JsonTooDeep);
+        // the brackets in the strings are not counted
+        let brackets = format!(
+            r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"extra":"[[[["}}"#,
+            IPFSHASH, IPFSHASHPRIVATE
+        );
+        assert_ok!(new_contract(1, 2, brackets.into_bytes()));
+    });
+}
+
+#[test]
+fn contracts_of_owner_are_listed_in_order() {
+    new_test_ext().execute_with(|| {
+        for crmid in [5, 3, 9, 1, 7].iter() {
+            assert_ok!(new_contract(1, *crmid, crmdata()));
+        }
+        assert_eq!(TemplateModule::contracts_of_owner(1), vec![5, 3, 9, 1, 7]);
+        // the removal keeps the order of the others
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 9));
+        assert_eq!(TemplateModule::contracts_of_owner(1), vec![5, 3, 1, 7]);
+        assert_ok!(new_contract(2, 2, crmdata()));
+        assert_ok!(TemplateModule::transfer_contract(Origin::signed(1), 3, 2));
+        assert_eq!(TemplateModule::contracts_of_owner(1), vec![5, 1, 7]);
+        assert_eq!(TemplateModule::contracts_of_owner(2), vec![2, 3]);
+        assert_eq!(TemplateModule::get_contract_count(1), 3);
+        assert_eq!(TemplateModule::get_contract_count(2), 2);
+    });
+}
+
+#[test]
+fn shares_of_the_stored_contracts_are_verified() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, crmdata_with_shares("bps", 4_000, 6_000)));
+        assert_eq!(TemplateModule::is_shares_valid(1), Some(true));
+        assert_eq!(TemplateModule::is_shares_valid(2), Some(true));
+        // body stored by an earlier version without the check of the total
+        crate::CrmData::<Test>::insert(3, crmdata_with_shares("percent", 40, 50));
+        assert_eq!(TemplateModule::is_shares_valid(3), Some(false));
+        assert_eq!(TemplateModule::is_shares_valid(4), None);
+    });
+}
+
+#[test]
+fn contract_counters_follow_creates_and_removals() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, crmdata()));
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_ok!(new_contract(1, 3, crmdata()));
+        assert_eq!(TemplateModule::total_contracts(), 3);
+        assert_eq!(TemplateModule::active_contracts(), 2);
+    });
+}
+
+#[test]
+fn migration_initializes_the_contract_counters() {
+    use frame_support::traits::OnRuntimeUpgrade;
+    new_test_ext().execute_with(|| {
+        // storage seeded before the counters, one contract removed with a tombstone
+        crate::CrmData::<Test>::insert(1, crmdata());
+        crate::CrmData::<Test>::insert(2, crmdata());
+        crate::CrmDeleted::<Test>::insert(3, 1);
+        crate::CrmStorageVersion::<Test>::put(1);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::crm_storage_version(), crate::STORAGE_VERSION);
+        assert_eq!(TemplateModule::active_contracts(), 2);
+        assert_eq!(TemplateModule::total_contracts(), 3);
+        // the counters are not initialized twice
+        crate::CrmData::<Test>::insert(4, crmdata());
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::active_contracts(), 2);
+    });
+}
+
+#[test]
+fn owner_index_follows_every_mutation() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(upsert(2, 2, crmdata()));
+        assert_ok!(TemplateModule::new_contract_auto(Origin::signed(3), crmdata(), master(), composition(), Vec::new()));
+        assert_eq!(TemplateModule::get_crm_owner(1), Some(1));
+        assert_eq!(TemplateModule::get_crm_owner(2), Some(2));
+        assert_eq!(TemplateModule::get_crm_owner(3), Some(3));
+        assert_ok!(TemplateModule::transfer_contract(Origin::signed(1), 1, 4));
+        assert_eq!(TemplateModule::get_crm_owner(1), Some(4));
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(TemplateModule::get_crm_owner(1), None);
+        assert_ok!(TemplateModule::revoke_contract(Origin::signed(2), 2));
+        assert_ok!(TemplateModule::prune_revoked(Origin::root(), 10));
+        assert_eq!(TemplateModule::get_crm_owner(2), None);
+        // every contract on chain has an owner and every owner entry has a contract
+        for (crmid, _) in crate::CrmOwner::<Test>::iter() {
+            assert!(TemplateModule::get_crmdata(crmid).is_some());
+        }
+        assert_eq!(crate::CrmOwner::<Test>::iter().count() as u32, TemplateModule::active_contracts());
+    });
+}
+
+#[test]
+fn deposit_is_reserved_on_creation_and_follows_the_crm_data() {
+    new_test_ext().execute_with(|| {
+        set_deposits(100, 2);
+        assert_ok!(new_contract(1, 1, crmdata_with_length(200)));
+        assert_eq!(Balances::reserved_balance(1), 500);
+        assert_eq!(Balances::free_balance(1), 500);
+        assert_eq!(TemplateModule::get_deposit(1), Some((1, 500)));
+        // a longer crm data reserves the difference, a shorter one releases it
+        assert_ok!(upsert(1, 1, crmdata_with_length(250)));
+        assert_eq!(Balances::reserved_balance(1), 600);
+        assert_ok!(upsert(1, 1, crmdata_with_length(150)));
+        assert_eq!(Balances::reserved_balance(1), 400);
+        assert_eq!(TemplateModule::get_deposit(1), Some((1, 400)));
+        // the deposit is released when the contract is removed
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 1_000);
+        assert_eq!(TemplateModule::get_deposit(1), None);
+    });
+}
+
+#[test]
+fn contract_creation_fails_when_the_deposit_cannot_be_reserved() {
+    new_test_ext().execute_with(|| {
+        set_deposits(100, 5);
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_length(200)),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+        // an update that cannot be covered leaves the contract unchanged
+        set_deposits(100, 2);
+        assert_ok!(new_contract(1, 1, crmdata_with_length(200)));
+        set_deposits(100, 4);
+        assert_noop!(
+            upsert(1, 1, crmdata_with_length(300)),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+        assert_eq!(Balances::reserved_balance(1), 500);
+    });
+}
+
+#[test]
+fn creation_fee_is_charged_for_valid_contracts_only() {
+    new_test_ext().execute_with(|| {
+        set_creation_fee(30);
+        set_deposits(100, 2);
+        // a wrong contract is not charged
+        let mut wrong = crmdata_with_length(200);
+        wrong.truncate(150);
+        assert!(new_contract(1, 1, wrong).is_err());
+        assert_eq!(Balances::free_balance(1), 1_000);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        // the fee is not charged when the deposit cannot be reserved
+        set_deposits(900, 2);
+        assert_noop!(
+            new_contract(1, 1, crmdata_with_length(200)),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+        assert_eq!(Balances::free_balance(1), 1_000);
+        // the fee is withdrawn and the deposit reserved
+        set_deposits(100, 2);
+        assert_ok!(new_contract(1, 1, crmdata_with_length(200)));
+        assert_eq!(Balances::free_balance(1), 1_000 - 30 - 500);
+        assert_eq!(Balances::reserved_balance(1), 500);
+        assert_eq!(Balances::total_issuance(), 4_000 - 30);
+    });
+}
+
+#[test]
+fn reserved_crmids_are_created_by_root_only() {
+    new_test_ext().execute_with(|| {
+        set_reserved_crm_id_max(10);
+        assert_noop!(new_contract(1, 1, crmdata()), Error::<Test>::ReservedCrmIdRange.with_weight(EARLY_EXIT_WEIGHT));
+        assert_noop!(new_contract(1, 10, crmdata()), Error::<Test>::ReservedCrmIdRange.with_weight(EARLY_EXIT_WEIGHT));
+        assert_noop!(upsert(1, 5, crmdata()), Error::<Test>::ReservedCrmIdRange.with_weight(EARLY_EXIT_WEIGHT));
+        assert_noop!(
+            TemplateModule::force_new_contract(Origin::signed(1), 1, 5, crmdata(), master(), composition(), Vec::new()),
+            sp_runtime::traits::BadOrigin
+        );
+        assert_ok!(TemplateModule::force_new_contract(Origin::root(), 1, 5, crmdata(), master(), composition(), Vec::new()));
+        assert!(crm_event_emitted(CrmEvent::CrmAdded(1, 5)));
+        assert_eq!(TemplateModule::get_crm_owner(5), Some(1));
+        // the owner can change a system contract, the users create above the range
+        assert_ok!(upsert(1, 5, crmdata_with_type("single")));
+        assert_ok!(new_contract(1, 11, crmdata()));
+        assert_ok!(TemplateModule::new_contract_auto(Origin::signed(2), crmdata(), master(), composition(), Vec::new()));
+        assert!(crm_event_emitted(CrmEvent::CrmAdded(2, 12)));
+    });
+}
+
+fn shares(master: u8, composition: u8, othercontracts: u8) -> CrmShares {
+    CrmShares {
+        globalquorum: Percent::from_percent(100),
+        mastershare: Percent::from_percent(master),
+        masterquorum: Percent::from_percent(51),
+        compositionshare: Percent::from_percent(composition),
+        compositionquorum: Percent::from_percent(51),
+        othercontractsshare: Percent::from_percent(othercontracts),
+        othercontractsquorum: Percent::from_percent(51),
+        ..Default::default()
+    }
+}
+
+fn new_contract_by_hash(who: u128, crmid: u32, shares: CrmShares) -> frame_support::dispatch::DispatchResultWithPostInfo {
+    let datahash = sp_io::hashing::blake2_256(&crmdata());
+    TemplateModule::new_contract_by_hash(Origin::signed(who), crmid, IPFSHASH.as_bytes().to_vec(), datahash, shares)
+}
+
+#[test]
+fn contract_by_hash_validates_the_shares() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(new_contract_by_hash(1, 1, shares(50, 40, 0)), Error::<Test>::InvalidTotalShares);
+        assert_noop!(new_contract_by_hash(1, 1, shares(0, 100, 0)), Error::<Test>::InvalidMasterShare);
+        assert_noop!(new_contract_by_hash(1, 1, shares(100, 0, 0)), Error::<Test>::InvalidCompositionShare);
+        let mut noquorum = shares(50, 50, 0);
+        noquorum.globalquorum = Percent::from_percent(0);
+        assert_noop!(new_contract_by_hash(1, 1, noquorum), Error::<Test>::InvalidGlobalQuorum);
+        let mut donation = shares(50, 40, 0);
+        donation.donationshare = Percent::from_percent(10);
+        assert_noop!(new_contract_by_hash(1, 1, donation), Error::<Test>::MissingDonationAccount);
+        assert_noop!(
+            TemplateModule::new_contract_by_hash(Origin::signed(1), 1, b"short".to_vec(), [0u8; 32], shares(50, 50, 0)),
+            Error::<Test>::InvalidIpfsHash
+        );
+    });
+}
+
+#[test]
+fn contract_by_hash_keeps_the_commitment() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(new_contract_by_hash(1, 1, shares(50, 50, 0)), Error::<Test>::DuplicatedCrmId);
+        assert_ok!(new_contract_by_hash(1, 2, shares(50, 30, 20)));
+        assert!(crm_event_emitted(CrmEvent::CrmAddedByHash(1, 2)));
+        assert_noop!(new_contract(1, 2, crmdata()), Error::<Test>::DuplicatedCrmId.with_weight(EARLY_EXIT_WEIGHT));
+        // the modes are distinct and the json fetched from IPFS is verified against the hash
+        assert_eq!(TemplateModule::storage_mode(1), Some(CrmStorageMode::Inline));
+        assert_eq!(TemplateModule::storage_mode(2), Some(CrmStorageMode::ByHash));
+        assert_eq!(TemplateModule::storage_mode(3), None);
+        assert!(TemplateModule::get_crmdata(2).is_none());
+        let commitment = TemplateModule::get_crm_commitment(2).unwrap();
+        assert_eq!(commitment.ipfscid, IPFSHASH.as_bytes().to_vec());
+        assert_eq!(commitment.shares, shares(50, 30, 20));
+        assert_eq!(TemplateModule::get_crm_shares(2), Some(shares(50, 30, 20)));
+        assert_eq!(TemplateModule::verify_commitment(2, &crmdata()), Some(true));
+        assert_eq!(TemplateModule::verify_commitment(2, b"{}"), Some(false));
+        assert_eq!(TemplateModule::verify_commitment(1, &crmdata()), None);
+        assert_eq!(TemplateModule::get_crm_owner(2), Some(1));
+        assert_eq!(TemplateModule::active_contracts(), 2);
+        // the commitment is removed with the contract
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 2));
+        assert_eq!(TemplateModule::storage_mode(2), None);
+        assert_eq!(TemplateModule::active_contracts(), 1);
+    });
+}
+
+#[test]
+fn patch_merges_the_fields_and_validates_the_result() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        // the shares must still total 100
+        assert_noop!(
+            TemplateModule::patch_crmdata(Origin::signed(1), 1, br#"{"mastershare":60}"#.to_vec()),
+            Error::<Test>::InvalidTotalShares
+        );
+        assert_noop!(
+            TemplateModule::patch_crmdata(Origin::signed(2), 1, br#"{"mastershare":40,"compositionshare":60}"#.to_vec()),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_noop!(
+            TemplateModule::patch_crmdata(Origin::signed(1), 2, br#"{"mastershare":40}"#.to_vec()),
+            Error::<Test>::InvalidContractId.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        assert_ok!(TemplateModule::patch_crmdata(Origin::signed(1), 1, br#"{"mastershare":40, "compositionshare":60}"#.to_vec()));
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(1, 1, 2, ChangeKind::Shares)));
+        assert_eq!(TemplateModule::crm_version(1), 2);
+        let crmdata = TemplateModule::get_crmdata(1).unwrap();
+        assert_eq!(json_get_value(crmdata.clone(), b"mastershare".to_vec()), b"40".to_vec());
+        assert_eq!(json_get_value(crmdata.clone(), b"compositionshare".to_vec()), b"60".to_vec());
+        // the other fields persist
+        assert_eq!(json_get_value(crmdata.clone(), b"ipfshash".to_vec()), IPFSHASH.as_bytes().to_vec());
+        assert_eq!(json_get_value(crmdata.clone(), b"masterquorum".to_vec()), b"51".to_vec());
+        assert_eq!(json_get_array(crmdata, b"ipfshashprivate".to_vec()), vec![IPFSHASHPRIVATE.as_bytes().to_vec()]);
+        // a new field is added
+        assert_ok!(TemplateModule::patch_crmdata(Origin::signed(1), 1, br#"{"type":"single"}"#.to_vec()));
+        assert_eq!(json_get_value(TemplateModule::get_crmdata(1).unwrap(), b"type".to_vec()), b"single".to_vec());
+    });
+}
+
+// main crm data with the n-th ipfs hash
+fn crmdata_with_ipfshash(n: u32) -> Vec<u8> {
+    let hash = format!("{}{}", &IPFSHASH[..45], n);
+    String::from_utf8(crmdata()).unwrap().replace(IPFSHASH, &hash).into_bytes()
+}
+
+#[test]
+fn ipfs_index_follows_the_contracts() {
+    new_test_ext().execute_with(|| {
+        set_unique_ipfs_hash(true);
+        let hash = |n: u32| format!("{}{}", &IPFSHASH[..45], n).into_bytes();
+        assert_ok!(new_contract(1, 1, crmdata_with_ipfshash(1)));
+        assert_eq!(TemplateModule::contract_by_ipfshash(hash(1)), Some((1, 1)));
+        assert_eq!(TemplateModule::contract_by_ipfshash(hash(2)), None);
+        // a second contract cannot register the same hash
+        assert_noop!(new_contract(2, 2, crmdata_with_ipfshash(1)), Error::<Test>::IpfsHashAlreadyRegistered);
+        assert_ok!(new_contract(2, 2, crmdata_with_ipfshash(2)));
+        assert_noop!(upsert(2, 2, crmdata_with_ipfshash(1)), Error::<Test>::IpfsHashAlreadyRegistered);
+        // an update moves the index to the new hash and a transfer changes the owner returned
+        assert_ok!(upsert(1, 1, crmdata_with_ipfshash(3)));
+        assert_eq!(TemplateModule::contract_by_ipfshash(hash(1)), None);
+        assert_eq!(TemplateModule::contract_by_ipfshash(hash(3)), Some((1, 1)));
+        assert_ok!(TemplateModule::transfer_contract(Origin::signed(1), 1, 4));
+        assert_eq!(TemplateModule::contract_by_ipfshash(hash(3)), Some((4, 1)));
+        // the hash is free again after the removal
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(TemplateModule::contract_by_ipfshash(hash(3)), None);
+        assert_ok!(new_contract(3, 3, crmdata_with_ipfshash(3)));
+        assert_eq!(TemplateModule::contract_by_ipfshash(hash(3)), Some((3, 3)));
+    });
+}
+
+#[test]
+fn shared_ipfs_hash_keeps_the_first_contract() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(2, 2, crmdata()));
+        assert_eq!(TemplateModule::contract_by_ipfshash(IPFSHASH.as_bytes().to_vec()), Some((1, 1)));
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 2));
+        assert_eq!(TemplateModule::contract_by_ipfshash(IPFSHASH.as_bytes().to_vec()), Some((1, 1)));
+    });
+}
+
+#[test]
+fn shared_ipfs_hash_moves_to_the_next_contract() {
+    new_test_ext().execute_with(|| {
+        let hash = |n: u32| format!("{}{}", &IPFSHASH[..45], n).into_bytes();
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(2, 2, crmdata()));
+        assert_ok!(new_contract(3, 3, crmdata_with_ipfshash(1)));
+        // the removal of the first contract moves the index to the other contract of the hash
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(TemplateModule::contract_by_ipfshash(IPFSHASH.as_bytes().to_vec()), Some((2, 2)));
+        // as the change of its hash
+        assert_ok!(upsert(3, 3, crmdata()));
+        assert_ok!(upsert(2, 2, crmdata_with_ipfshash(2)));
+        assert_eq!(TemplateModule::contract_by_ipfshash(IPFSHASH.as_bytes().to_vec()), Some((3, 3)));
+        assert_eq!(TemplateModule::contract_by_ipfshash(hash(1)), None);
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 3));
+        assert_eq!(TemplateModule::contract_by_ipfshash(IPFSHASH.as_bytes().to_vec()), None);
+    });
+}
+
+#[test]
+fn migration_builds_the_ipfs_index() {
+    use frame_support::traits::OnRuntimeUpgrade;
+    new_test_ext().execute_with(|| {
+        crate::CrmData::<Test>::insert(3, crmdata_with_ipfshash(1));
+        crate::CrmData::<Test>::insert(1, crmdata_with_ipfshash(1));
+        crate::CrmData::<Test>::insert(2, crmdata_with_ipfshash(2));
+        crate::CrmOwner::<Test>::insert(1, 1);
+        crate::CrmOwner::<Test>::insert(2, 2);
+        crate::CrmStorageVersion::<Test>::put(2);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::crm_storage_version(), crate::STORAGE_VERSION);
+        // the lowest crmid is kept for a shared hash
+        assert_eq!(TemplateModule::get_ipfs_index(format!("{}1", &IPFSHASH[..45]).into_bytes()), Some(1));
+        assert_eq!(TemplateModule::contract_by_ipfshash(format!("{}2", &IPFSHASH[..45]).into_bytes()), Some((2, 2)));
+        // the contracts sharing a hash are listed, the index moves to contract 3 when contract 1 is removed
+        crate::CrmOwner::<Test>::insert(3, 3);
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(TemplateModule::get_ipfs_index(format!("{}1", &IPFSHASH[..45]).into_bytes()), Some(3));
+    });
+}
+
+#[test]
+fn approved_change_proposal_moves_the_ipfs_index() {
+    new_test_ext().execute_with(|| {
+        set_unique_ipfs_hash(true);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let proposal = String::from_utf8(crmdata_with_ipfshash(7)).unwrap().replacen("{", r#"{"crmid":1,"#, 1);
+        assert_ok!(TemplateModule::change_proposal_crmdata(Origin::signed(1), 1, proposal.into_bytes()));
+        // the master (50%) and the composition (50%) approve the proposal
+        assert_ok!(TemplateModule::vote_proposal_crmdata(Origin::signed(1), 1, true));
+        assert_ok!(TemplateModule::vote_proposal_crmdata(Origin::signed(2), 1, true));
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(2, 1, 2, ChangeKind::FullReplace)));
+        let newhash = format!("{}7", &IPFSHASH[..45]).into_bytes();
+        assert_eq!(TemplateModule::get_ipfs_index(newhash), Some(1));
+        // the previous hash is released for the other contracts
+        assert_eq!(TemplateModule::get_ipfs_index(IPFSHASH.as_bytes().to_vec()), None);
+        assert_ok!(new_contract(1, 2, crmdata()));
+        assert_eq!(TemplateModule::get_ipfs_index(IPFSHASH.as_bytes().to_vec()), Some(2));
+    });
+}
+
+#[test]
+fn change_proposal_respects_the_lock_period() {
+    new_test_ext().execute_with(|| {
+        set_update_lock_period(10);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let proposal = String::from_utf8(crmdata_with_ipfshash(7)).unwrap().replacen("{", r#"{"crmid":1,"#, 1).into_bytes();
+        assert_noop!(
+            TemplateModule::change_proposal_crmdata(Origin::signed(1), 1, proposal.clone()),
+            Error::<Test>::LockPeriodActive
+        );
+        run_to_block(11);
+        assert_ok!(TemplateModule::change_proposal_crmdata(Origin::signed(1), 1, proposal));
+        // a longer lock period set after the proposal blocks its approval, the approving vote is reverted
+        set_update_lock_period(20);
+        assert_ok!(TemplateModule::vote_proposal_crmdata(Origin::signed(1), 1, true));
+        assert_noop!(TemplateModule::vote_proposal_crmdata(Origin::signed(2), 1, true), Error::<Test>::LockPeriodActive);
+        run_to_block(21);
+        assert_ok!(TemplateModule::vote_proposal_crmdata(Origin::signed(2), 1, true));
+        assert_eq!(TemplateModule::get_ipfs_index(format!("{}7", &IPFSHASH[..45]).into_bytes()), Some(1));
+    });
+}
+
+#[test]
+fn change_proposal_of_a_revoked_contract_is_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let proposal = String::from_utf8(crmdata_with_ipfshash(7)).unwrap().replacen("{", r#"{"crmid":1,"#, 1).into_bytes();
+        assert_ok!(TemplateModule::change_proposal_crmdata(Origin::signed(1), 1, proposal.clone()));
+        assert_ok!(TemplateModule::vote_proposal_crmdata(Origin::signed(1), 1, true));
+        assert_ok!(TemplateModule::revoke_contract(Origin::signed(1), 1));
+        // the proposal pending is not approved and no new proposal is accepted
+        assert_noop!(TemplateModule::vote_proposal_crmdata(Origin::signed(2), 1, true), Error::<Test>::ContractRevoked);
+        assert_noop!(
+            TemplateModule::change_proposal_crmdata(Origin::signed(1), 2, proposal),
+            Error::<Test>::ContractRevoked
+        );
+        assert_eq!(TemplateModule::get_crmdata(1), Some(crmdata()));
+    });
+}
+
+#[test]
+fn crm_ids_are_capped_and_paginated() {
+    new_test_ext().execute_with(|| {
+        let max = <Test as crate::Config>::MaxListReturn::get();
+        for crmid in 1..=max + 2 {
+            assert_ok!(new_contract(1, crmid, crmdata()));
+        }
+        let first = TemplateModule::get_crm_ids(1);
+        assert_eq!(first.len() as u32, max);
+        assert_eq!(first, (1..=max).collect::<Vec<u32>>());
+        // the next page starts after the last crmid returned
+        let next = TemplateModule::get_crm_ids_from(1, first[first.len() - 1] + 1);
+        assert_eq!(next, vec![max + 1, max + 2]);
+        // the lookups are not limited by the cap
+        let hash = IPFSHASH.as_bytes().to_vec();
+        assert_eq!(TemplateModule::find_crm_by_ipfshash(1, hash), Some(1));
+    });
+}
+
+#[test]
+fn migrations_are_idempotent() {
+    use frame_support::traits::OnRuntimeUpgrade;
+    new_test_ext().execute_with(|| {
+        // storage in the old format: crm data, owners and a tombstone only
+        crate::CrmData::<Test>::insert(1, crmdata_with_ipfshash(1));
+        crate::CrmData::<Test>::insert(2, crmdata_with_ipfshash(2));
+        crate::CrmData::<Test>::insert(3, crmdata_with_ipfshash(1));
+        crate::CrmOwner::<Test>::insert(1, 1);
+        crate::CrmOwner::<Test>::insert(2, 2);
+        crate::CrmOwner::<Test>::insert(3, 1);
+        crate::CrmDeleted::<Test>::insert(4, 1);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::crm_storage_version(), crate::STORAGE_VERSION);
+        let state = || {
+            (
+                crate::CrmContracts::<Test>::iter().collect::<Vec<_>>(),
+                crate::IpfsIndex::<Test>::iter().collect::<Vec<_>>(),
+                TemplateModule::total_crms(),
+                TemplateModule::total_contracts(),
+            )
+        };
+        let migrated = state();
+        assert_eq!(migrated.0.len(), 3);
+        assert_eq!(migrated.1.len(), 2);
+        assert_eq!((migrated.2, migrated.3), (3, 4));
+        // the storage at the current version is not migrated again, the old prefix and the version are read
+        let weight = TemplateModule::on_runtime_upgrade();
+        assert_eq!(weight, <Test as frame_system::Config>::DbWeight::get().reads(2));
+        // running every migration again leaves the same storage
+        crate::CrmStorageVersion::<Test>::put(0);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(state(), migrated);
+    });
+}
+
+// main crm data with the "isrc" and "iswc" codes given
+fn crmdata_with_codes(isrc: &str, iswc: &str) -> Vec<u8> {
+    format!(
+        r#"{{"ipfshash":"{}","ipfshashprivate":"{}","globalquorum":100,"mastershare":50,"masterquorum":51,"compositionshare":50,"compositionquorum":51,"othercontractsshare":0,"othercontractsquorum":51,"isrc":"{}","iswc":"{}"}}"#,
+        IPFSHASH, IPFSHASHPRIVATE, isrc, iswc
+    )
+    .into_bytes()
+}
+
+#[test]
+fn isrc_and_iswc_codes_are_validated() {
+    new_test_ext().execute_with(|| {
+        for isrc in ["USRC1760783", "usrc17607839", "US-RC17-607839", "U1RC17607839", "USRC1760783A"].iter() {
+            assert_noop!(new_contract(1, 1, crmdata_with_codes(isrc, "T-034524680-1")), Error::<Test>::InvalidIsrc);
+        }
+        for iswc in ["T-034524680-2", "T-03452468-01", "X-034524680-1", "T034524680"].iter() {
+            assert_noop!(new_contract(1, 1, crmdata_with_codes("US-RC1-76-07839", iswc)), Error::<Test>::InvalidIswc);
+        }
+        assert_ok!(new_contract(1, 1, crmdata_with_codes("US-RC1-76-07839", "T-034524680-1")));
+        assert_eq!(TemplateModule::get_crm_isrc(1), Some(b"USRC17607839".to_vec()));
+        assert_eq!(TemplateModule::get_crm_iswc(1), Some(b"T0345246801".to_vec()));
+        // the codes follow the updates and are optional
+        assert_noop!(upsert(1, 1, crmdata_with_codes("USRC17607839", "T0345246802")), Error::<Test>::InvalidIswc);
+        assert_ok!(upsert(1, 1, crmdata_with_codes("GBAYE0601498", "T0000000010")));
+        assert_eq!(TemplateModule::get_crm_isrc(1), Some(b"GBAYE0601498".to_vec()));
+        assert_eq!(TemplateModule::get_crm_iswc(1), Some(b"T0000000010".to_vec()));
+        assert_ok!(upsert(1, 1, crmdata()));
+        assert_eq!(TemplateModule::get_crm_isrc(1), None);
+        assert_eq!(TemplateModule::get_crm_iswc(1), None);
+    });
+}
+
+#[test]
+fn migration_check_catches_a_corrupted_contract() {
+    use frame_support::traits::OnRuntimeUpgrade;
+    new_test_ext().execute_with(|| {
+        // storage in the old format
+        crate::CrmData::<Test>::insert(1, crmdata_with_ipfshash(1));
+        crate::CrmData::<Test>::insert(2, crmdata_with_ipfshash(2));
+        crate::CrmData::<Test>::insert(3, br#"{"ipfshash":"","globalquorum":200}"#.to_vec());
+        let snapshot = TemplateModule::migration_snapshot();
+        assert_eq!(snapshot.count, 3);
+        assert_eq!(TemplateModule::check_migration(&snapshot), Err("crm migration: the storage is not at the current version"));
+        TemplateModule::on_runtime_upgrade();
+        assert_ok!(TemplateModule::check_migration(&snapshot));
+        // a decoded contract not matching its crm data
+        let mut contract = TemplateModule::get_crm_contract(1).unwrap();
+        contract.mastershare = 1;
+        crate::CrmContracts::<Test>::insert(1, contract);
+        assert_eq!(
+            TemplateModule::check_migration(&snapshot),
+            Err("crm migration: a decoded contract does not match its crm data")
+        );
+        // the migration run again decodes the contract
+        crate::CrmStorageVersion::<Test>::put(0);
+        TemplateModule::on_runtime_upgrade();
+        assert_ok!(TemplateModule::check_migration(&snapshot));
+        // crm data changed or lost
+        crate::CrmData::<Test>::insert(2, crmdata_with_ipfshash(3));
+        assert!(TemplateModule::check_migration(&snapshot).is_err());
+        crate::CrmData::<Test>::remove(2);
+        assert_eq!(TemplateModule::check_migration(&snapshot), Err("crm migration: the number of contracts has changed"));
+    });
+}
+
+#[test]
+fn contract_is_locked_after_the_creation() {
+    new_test_ext().execute_with(|| {
+        set_update_lock_period(10);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_eq!(TemplateModule::get_crm_created_at(1), Some(1));
+        let hash = format!("{}10", &IPFSHASHPRIVATE[..44]).into_bytes();
+        // the last block of the lock period
+        run_to_block(10);
+        assert_noop!(upsert(1, 1, crmdata_with_type("single")), Error::<Test>::LockPeriodActive);
+        assert_noop!(
+            TemplateModule::patch_crmdata(Origin::signed(1), 1, br#"{"type":"single"}"#.to_vec()),
+            Error::<Test>::LockPeriodActive
+        );
+        assert_noop!(
+            TemplateModule::add_private_hash(Origin::signed(1), 1, hash.clone(), None),
+            Error::<Test>::LockPeriodActive
+        );
+        // the lock ends at the creation block plus the period
+        run_to_block(11);
+        assert_ok!(upsert(1, 1, crmdata_with_type("single")));
+        assert_ok!(TemplateModule::add_private_hash(Origin::signed(1), 1, hash, None));
+        // the lock is removed with the contract
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_eq!(TemplateModule::get_crm_created_at(1), None);
+    });
+}
+
+#[test]
+fn storage_is_moved_from_the_decl_storage_prefix() {
+    use codec::Encode;
+    use frame_support::traits::OnRuntimeUpgrade;
+    new_test_ext().execute_with(|| {
+        // storage written by the pallet declared with decl_storage, under its prefix
+        let old_prefix = sp_io::hashing::twox_128(crate::OLD_STORAGE_PREFIX);
+        let old_key = |key: &[u8]| [&old_prefix[..], &key[16..]].concat();
+        sp_io::storage::set(&old_key(&crate::CrmData::<Test>::hashed_key_for(1)), &crmdata().encode());
+        sp_io::storage::set(&old_key(&crate::CrmOwner::<Test>::hashed_key_for(1)), &1u128.encode());
+        assert_eq!(TemplateModule::get_crmdata(1), None);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::get_crmdata(1), Some(crmdata()));
+        assert_eq!(TemplateModule::get_crm_owner(1), Some(1));
+        assert_eq!(sp_io::storage::next_key(&old_prefix).filter(|k| k.starts_with(&old_prefix)), None);
+        // the storage moved is migrated to the current version
+        assert_eq!(TemplateModule::crm_storage_version(), crate::STORAGE_VERSION);
+        assert!(TemplateModule::get_crm_contract(1).is_some());
+        assert_eq!(TemplateModule::total_crms(), 1);
+        let snapshot = TemplateModule::migration_snapshot();
+        assert_ok!(TemplateModule::check_migration(&snapshot));
+    });
+}
+
+#[test]
+fn field_values_must_have_the_expected_type() {
+    new_test_ext().execute_with(|| {
+        // the main crm data with the value of a field replaced
+        let with = |field: &str, value: &str| {
+            let fields = [
+                ("ipfshash", format!(r#""{}""#, IPFSHASH)),
+                ("ipfshashprivate", format!(r#""{}""#, IPFSHASHPRIVATE)),
+                ("globalquorum", "100".to_string()),
+                ("mastershare", "50".to_string()),
+                ("masterquorum", "51".to_string()),
+                ("compositionshare", "50".to_string()),
+                ("compositionquorum", "51".to_string()),
+                ("othercontractsshare", "0".to_string()),
+                ("othercontractsquorum", "51".to_string()),
+            ];
+            let body: Vec<String> = fields
+                .iter()
+                .map(|(k, v)| format!(r#""{}":{}"#, k, if *k == field { value } else { v }))
+                .collect();
+            format!("{{{}}}", body.join(",")).into_bytes()
+        };
+        assert_noop!(new_contract(1, 1, with("mastershare", r#""abc""#)), Error::<Test>::MasterShareNotNumber);
+        assert_noop!(new_contract(1, 1, with("masterquorum", "true")), Error::<Test>::MasterQuorumNotNumber);
+        assert_noop!(new_contract(1, 1, with("globalquorum", r#""""#)), Error::<Test>::GlobalQuorumNotNumber);
+        assert_noop!(new_contract(1, 1, with("compositionshare", "-50")), Error::<Test>::CompositionShareNotNumber);
+        assert_noop!(new_contract(1, 1, with("othercontractsquorum", "[51]")), Error::<Test>::OtherContractsQuorumNotNumber);
+        assert_noop!(new_contract(1, 1, with("ipfshash", "12345")), Error::<Test>::IpfsHashNotString);
+        assert_noop!(new_contract(1, 1, with("ipfshash", r#""""#)), Error::<Test>::IpfsHashNotString);
+        assert_noop!(
+            new_contract(1, 1, with("ipfshashprivate", r#"{"a":1}"#)),
+            Error::<Test>::IpfsHashPrivateNotStringOrArray
+        );
+        // the numbers can be quoted and the private hashes can be an array
+        assert_ok!(new_contract(1, 1, with("mastershare", r#""50""#)));
+        let private = format!(r#"["{}"]"#, IPFSHASHPRIVATE);
+        assert_ok!(new_contract(1, 2, with("ipfshashprivate", &private)));
+        // the updates are checked as well
+        assert_noop!(
+            TemplateModule::patch_crmdata(Origin::signed(1), 1, br#"{"compositionquorum":"x"}"#.to_vec()),
+            Error::<Test>::CompositionQuorumNotNumber
+        );
+    });
+}
+
+#[test]
+fn contracts_of_the_owner_are_deleted_in_batches() {
+    new_test_ext().execute_with(|| {
+        for crmid in 1..=5 {
+            assert_ok!(new_contract(1, crmid, crmdata()));
+        }
+        assert_ok!(new_contract(2, 6, crmdata()));
+        assert_ok!(TemplateModule::delete_all_crm(Origin::signed(1), 3));
+        assert!(crm_event_emitted(CrmEvent::CrmRemoved(1, 1)));
+        assert!(crm_event_emitted(CrmEvent::CrmRemoved(1, 3)));
+        assert_eq!(TemplateModule::contracts_of_owner(1), vec![4, 5]);
+        assert_eq!(TemplateModule::get_contract_count(1), 2);
+        assert_eq!(TemplateModule::total_crms(), 3);
+        // the second call removes the contracts left
+        let post = TemplateModule::delete_all_crm(Origin::signed(1), 3).unwrap();
+        // the weight of the contract not found is refunded
+        assert_eq!(post.actual_weight, Some(150_000));
+        assert!(crm_event_emitted(CrmEvent::CrmRemoved(1, 5)));
+        assert!(TemplateModule::contracts_of_owner(1).is_empty());
+        assert_eq!(TemplateModule::get_contract_count(1), 0);
+        assert_eq!((1..=5).filter(|c| TemplateModule::get_crmdata(c).is_some()).count(), 0);
+        // the contracts of the other accounts are kept
+        assert!(TemplateModule::get_crmdata(6).is_some());
+        assert_eq!(TemplateModule::total_crms(), 1);
+    });
+}
+
+#[test]
+fn deleting_the_contracts_keeps_the_crowdfunding_certificates() {
+    new_test_ext().execute_with(|| {
+        funded_campaign();
+        assert_ok!(new_contract(1, 1, crmdata_with_crowdfunding(1)));
+        assert_ok!(new_contract(1, 2, crmdata()));
+        assert_ok!(TemplateModule::delete_all_crm(Origin::signed(1), 10));
+        assert!(crm_event_emitted(CrmEvent::CrmRemoved(1, 2)));
+        assert!(!crm_event_emitted(CrmEvent::CrmRemoved(1, 1)));
+        // the contract with the campaign linked is skipped and the backers keep their certificates
+        assert_eq!(TemplateModule::contracts_of_owner(1), vec![1]);
+        for backer in 2..=4 {
+            assert!(TemplateModule::get_crowdfunding_certificate(1, backer).is_some());
+        }
+        assert_eq!(TemplateModule::crm_campaign(1), Some(1));
+    });
+}
+
+#[test]
+fn new_contract_weight_follows_the_length_of_the_crm_data() {
+    new_test_ext().execute_with(|| {
+        let max = <Test as crate::Config>::MaxCrmDataLength::get() as usize;
+        let weight = |crmdata: Vec<u8>| {
+            crate::Call::<Test>::new_contract(1, crmdata, master(), composition(), Vec::new()).get_dispatch_info().weight
+        };
+        let short = weight(crmdata());
+        let long = weight(crmdata_with_length(max));
+        assert!(long > short);
+        // the json is not validated when the length is out of bounds, the weight is refunded
+        let err = new_contract(1, 1, crmdata_with_length(max + 1)).unwrap_err();
+        assert_eq!(err.post_info.actual_weight, Some(EARLY_EXIT_WEIGHT));
+        assert_eq!(err.post_info.pays_fee, Pays::Yes);
+        assert!(EARLY_EXIT_WEIGHT < weight(crmdata_with_length(max + 1)));
+        let err = new_contract(1, 1, b"{}".to_vec()).unwrap_err();
+        assert_eq!(err.post_info.actual_weight, Some(EARLY_EXIT_WEIGHT));
+        // the other failures are charged the whole weight
+        let err = new_contract(1, 1, crmdata_with_length(max).into_iter().skip(1).collect()).unwrap_err();
+        assert_eq!(err.post_info.actual_weight, None);
+    });
+}
+
+#[test]
+fn early_exit_is_charged_the_checks_done_only() {
+    new_test_ext().execute_with(|| {
+        let call = crate::Call::<Test>::new_contract(1, crmdata(), master(), composition(), Vec::new());
+        let weight = call.get_dispatch_info().weight;
+        // the successful creation is charged the whole weight
+        let post = new_contract(1, 1, crmdata()).unwrap();
+        assert_eq!(post.actual_weight, None);
+        assert_eq!(post.calc_actual_weight(&call.get_dispatch_info()), weight);
+        // the duplicated crmid fails before the json validation
+        let err = new_contract(2, 1, crmdata()).unwrap_err();
+        assert_eq!(err.error, Error::<Test>::DuplicatedCrmId.into());
+        assert_eq!(err.post_info.pays_fee, Pays::Yes);
+        assert!(err.post_info.calc_actual_weight(&call.get_dispatch_info()) < weight);
+        // the invalid json is checked after the cheap checks, it's charged the whole weight
+        let err = new_contract(2, 2, crmdata().into_iter().skip(1).collect()).unwrap_err();
+        assert_eq!(err.post_info.actual_weight, None);
+    });
+}
+
+#[test]
+fn early_exit_of_the_changes_removals_and_payouts_is_refunded() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let proposal = String::from_utf8(crmdata()).unwrap().replacen("{", r#"{"crmid":1,"#, 1).into_bytes();
+        assert_ok!(TemplateModule::change_proposal_crmdata(Origin::signed(1), 1, proposal.clone()));
+        // the duplicated changeid fails before the json validation
+        let err = TemplateModule::change_proposal_crmdata(Origin::signed(1), 1, proposal).unwrap_err();
+        assert_eq!(err.error, Error::<Test>::ChangeIdDuplicated.into());
+        assert_eq!(err.post_info.actual_weight, Some(EARLY_EXIT_WEIGHT));
+        let err = TemplateModule::change_proposal_crm_masterdata(Origin::signed(1), 1, Vec::new()).unwrap_err();
+        assert_eq!(err.post_info.actual_weight, Some(EARLY_EXIT_WEIGHT));
+        // removals and payouts of a contract not found
+        let err = TemplateModule::force_remove_contract(Origin::root(), 9).unwrap_err();
+        assert_eq!(err.post_info.actual_weight, Some(EARLY_EXIT_WEIGHT));
+        let err = TemplateModule::revoke_contract(Origin::signed(2), 1).unwrap_err();
+        assert_eq!(err.error, Error::<Test>::SignerIsNotOwner.into());
+        assert_eq!(err.post_info.actual_weight, Some(EARLY_EXIT_WEIGHT));
+        let err = TemplateModule::pay_royalties(Origin::signed(2), 9, 100, None).unwrap_err();
+        assert_eq!(err.post_info.actual_weight, Some(EARLY_EXIT_WEIGHT));
+        let err = TemplateModule::claim_royalties(Origin::signed(2), 1, None, None).unwrap_err();
+        assert_eq!(err.error, Error::<Test>::NothingToClaim.into());
+        assert_eq!(err.post_info.actual_weight, Some(EARLY_EXIT_WEIGHT));
+        // the payout done is charged the whole weight
+        let post = TemplateModule::pay_royalties(Origin::signed(2), 1, 100, None).unwrap();
+        assert_eq!(post.actual_weight, None);
+    });
+}
+
+#[test]
+fn contract_of_the_owner_has_the_values_stored() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let contract = TemplateModule::get_crm_contract_of(1, 1).unwrap();
+        let crmdata = crmdata();
+        let field = |key: &str| json_get_value(crmdata.clone(), key.as_bytes().to_vec());
+        assert_eq!(contract.ipfshash, field("ipfshash"));
+        assert_eq!(contract.ipfshashprivate, vec![field("ipfshashprivate")]);
+        assert_eq!(contract.globalquorum.to_string().into_bytes(), field("globalquorum"));
+        assert_eq!(contract.masterquorum.to_string().into_bytes(), field("masterquorum"));
+        assert_eq!(contract.compositionquorum.to_string().into_bytes(), field("compositionquorum"));
+        assert_eq!(contract.othercontractsquorum.to_string().into_bytes(), field("othercontractsquorum"));
+        // the shares are in basis points
+        assert_eq!((contract.mastershare / 100).to_string().into_bytes(), field("mastershare"));
+        assert_eq!((contract.compositionshare / 100).to_string().into_bytes(), field("compositionshare"));
+        assert_eq!((contract.othercontractsshare / 100).to_string().into_bytes(), field("othercontractsshare"));
+        assert_eq!(contract.campaignid, None);
+        // the contract is returned to the owner only
+        assert_eq!(TemplateModule::get_crm_contract_of(2, 1), None);
+        assert_eq!(TemplateModule::get_crm_contract_of(1, 2), None);
+    });
+}
+
+#[test]
+fn single_holder_group_requires_a_full_quorum() {
+    new_test_ext().execute_with(|| {
+        let quorums = |master: u32, composition: u32| {
+            String::from_utf8(crmdata())
+                .unwrap()
+                .replace(r#""masterquorum":51"#, &format!(r#""masterquorum":{}"#, master))
+                .replace(r#""compositionquorum":51"#, &format!(r#""compositionquorum":{}"#, composition))
+                .into_bytes()
+        };
+        // the check is off by default
+        assert_ok!(new_contract(1, 1, crmdata()));
+        set_single_holder_quorum(true);
+        // the master and the composition of the mock have a single holder
+        assert_noop!(new_contract(1, 2, quorums(51, 100)), Error::<Test>::QuorumHolderMismatch);
+        assert_noop!(new_contract(1, 2, quorums(100, 51)), Error::<Test>::QuorumHolderMismatch);
+        assert_ok!(new_contract(1, 2, quorums(100, 100)));
+        // a group with more holders can have any quorum
+        let master = br#"{"master": [{"nickname": "Bob","account": "0x0100000000000000000000000000000000000000000000000000000000000000","percentage":60},{"nickname": "Dave","account": "0x0300000000000000000000000000000000000000000000000000000000000000","percentage":40}]}"#.to_vec();
+        assert_ok!(TemplateModule::new_contract(Origin::signed(1), 3, quorums(51, 100), master, composition(), Vec::new()));
+        // the updates are checked against the holders stored
+        assert_noop!(upsert(1, 2, quorums(100, 51)), Error::<Test>::QuorumHolderMismatch);
+        assert_ok!(upsert(1, 3, quorums(60, 100)));
+    });
+}
+
+#[test]
+fn crm_data_versions_are_written_in_the_offchain_index() {
+    use codec::Encode;
+    let mut ext = new_test_ext();
+    ext.execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(upsert(1, 1, crmdata_with_type("single")));
+        // the contracts stored by hash have no crm data
+        assert_ok!(new_contract_by_hash(1, 2, shares(50, 50, 0)));
+    });
+    ext.persist_offchain_overlay();
+    let db = ext.offchain_db();
+    let value = |crmid, version| db.get(&crate::offchain_key(&1u128, crmid, version));
+    assert_eq!(value(1, 1), Some((1u128, 1u32, crmdata()).encode()));
+    assert_eq!(value(1, 2), Some((1u128, 1u32, crmdata_with_type("single")).encode()));
+    assert_eq!(value(1, 3), None);
+    assert_eq!(value(2, 1), None);
+    // the key is the prefix followed by the owner, the crmid and the version
+    assert_eq!(crate::offchain_key(&1u128, 1, 2), [crate::OFFCHAIN_PREFIX, &(1u128, 1u32, 2u32).encode()[..]].concat());
+}
+
+#[test]
+fn holders_of_the_groups_total_100() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(
+            TemplateModule::set_master_holders(Origin::signed(1), 1, vec![(3, 60), (4, 30)]),
+            Error::<Test>::WrongTotalPercentageHolders
+        );
+        assert_noop!(
+            TemplateModule::set_master_holders(Origin::signed(1), 1, vec![(3, 100), (4, 0)]),
+            Error::<Test>::WrongTotalPercentageHolders
+        );
+        assert_noop!(
+            TemplateModule::set_composition_holders(Origin::signed(1), 1, vec![(3, 50), (3, 50)]),
+            Error::<Test>::DuplicatedHolder
+        );
+        assert_noop!(
+            TemplateModule::set_master_holders(Origin::signed(2), 1, vec![(3, 60), (4, 40)]),
+            Error::<Test>::SignerIsNotOwner
+        );
+        assert_ok!(TemplateModule::set_master_holders(Origin::signed(1), 1, vec![(3, 60), (4, 40)]));
+        assert!(crm_event_emitted(CrmEvent::HoldersUpdated(1, 1, HolderGroup::Master)));
+        assert_eq!(TemplateModule::get_crm_holders(1, HolderGroup::Master), vec![(3, 60), (4, 40)]);
+        assert!(TemplateModule::get_crm_holders(1, HolderGroup::Composition).is_empty());
+        // the holders replace the accounts of the master data, the composition is paid to its account
+        assert_ok!(TemplateModule::pay_royalties(Origin::signed(2), 1, 100, None));
+        assert_eq!(TemplateModule::get_royalty_balance(1, 3), 30);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 4), 20);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 2), 50);
+        assert_eq!(TemplateModule::get_royalty_balance(1, 1), 0);
+        // an empty list removes the holders
+        assert_ok!(TemplateModule::set_master_holders(Origin::signed(1), 1, Vec::new()));
+        assert!(TemplateModule::get_crm_holders(1, HolderGroup::Master).is_empty());
+    });
+}
+
+#[test]
+fn creation_and_last_change_are_recorded() {
+    use frame_support::traits::OnRuntimeUpgrade;
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_eq!(TemplateModule::crm_timestamps(1), Some((3, 3, 1)));
+        System::set_block_number(7);
+        assert_ok!(upsert(1, 1, crmdata_with_type("single")));
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(1, 1, 2, ChangeKind::FullReplace)));
+        assert_eq!(TemplateModule::crm_timestamps(1), Some((3, 7, 2)));
+        assert_eq!(TemplateModule::crm_timestamps(2), None);
+        // the contracts stored before the timestamps get the block of the migration, without version
+        crate::CrmData::<Test>::insert(2, crmdata());
+        crate::CrmStorageVersion::<Test>::put(3);
+        System::set_block_number(9);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::crm_timestamps(2), Some((9, 9, 0)));
+        assert_eq!(TemplateModule::get_crm_created_at(2), None);
+        assert_eq!(TemplateModule::crm_timestamps(1), Some((3, 7, 2)));
+    });
+}
+
+#[test]
+fn changes_are_emitted_with_their_kind() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let patch = |p: &[u8]| TemplateModule::patch_crmdata(Origin::signed(1), 1, p.to_vec());
+        assert_ok!(patch(br#"{"type":"album"}"#));
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(1, 1, 2, ChangeKind::Meta)));
+        let ipfshash = format!(r#"{{"ipfshash":"{}1"}}"#, &IPFSHASH[..45]);
+        assert_ok!(patch(ipfshash.as_bytes()));
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(1, 1, 3, ChangeKind::IpfsHash)));
+        // the shares are reported also when other fields are patched
+        assert_ok!(patch(br#"{"type":"single","mastershare":40,"compositionshare":60}"#));
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(1, 1, 4, ChangeKind::Shares)));
+        assert_ok!(upsert(1, 1, crmdata()));
+        assert!(crm_event_emitted(CrmEvent::CrmDataChanged(1, 1, 5, ChangeKind::FullReplace)));
+        // the editors don't change the version
+        assert_ok!(TemplateModule::add_editor(Origin::signed(1), 1, 2));
+        assert_ok!(TemplateModule::remove_editor(Origin::signed(1), 1, 2));
+        assert_eq!(
+            System::events().iter().filter(|r| r.event == Event::pallet_template(CrmEvent::CrmDataChanged(1, 1, 5, ChangeKind::Editors))).count(),
+            2
+        );
+    });
+}
+
+#[test]
+fn changelog_keeps_the_last_changes_newest_first() {
+    new_test_ext().execute_with(|| {
+        let stored_hash = || sp_io::hashing::blake2_256(&TemplateModule::get_crmdata(1).unwrap());
+        System::set_block_number(1);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let created = stored_hash();
+        assert_eq!(TemplateModule::changelog(1).len(), 1);
+        assert_eq!(TemplateModule::changelog(1)[0].action, ChangeKind::Created);
+        assert_eq!(TemplateModule::changelog(1)[0].datahash, created);
+        let mut hashes = Vec::new();
+        for (block, patch) in [&br#"{"type":"album"}"#[..], &br#"{"type":"single"}"#[..], &br#"{"mastershare":40,"compositionshare":60}"#[..]].iter().enumerate() {
+            System::set_block_number(block as u64 + 2);
+            assert_ok!(TemplateModule::patch_crmdata(Origin::signed(1), 1, patch.to_vec()));
+            hashes.push(stored_hash());
+        }
+        // the creation has been evicted, the newest change is the first
+        let log = TemplateModule::changelog(1);
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.iter().map(|e| e.block).collect::<Vec<_>>(), vec![4, 3, 2]);
+        assert_eq!(log.iter().map(|e| e.action).collect::<Vec<_>>(), vec![ChangeKind::Shares, ChangeKind::Meta, ChangeKind::Meta]);
+        assert_eq!(log.iter().map(|e| e.datahash).collect::<Vec<_>>(), vec![hashes[2], hashes[1], hashes[0]]);
+        assert!(log.iter().all(|e| e.who == 1 && e.datahash != created));
+        // the stored order is the oldest first
+        assert_eq!(TemplateModule::get_changelog(1)[0].block, 2);
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert!(TemplateModule::changelog(1).is_empty());
+    });
+}
+
+#[test]
+fn changelog_records_the_approved_group_data_and_the_holders() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        let proposal = String::from_utf8(master()).unwrap().replacen("{", r#"{"crmid":1,"#, 1).into_bytes();
+        assert_ok!(TemplateModule::change_proposal_crm_masterdata(Origin::signed(1), 1, proposal));
+        assert_eq!(TemplateModule::changelog(1).len(), 1);
+        // the master account (100%) approves the proposal
+        assert_ok!(TemplateModule::vote_proposal_crm_masterdata(Origin::signed(1), 1, true));
+        assert!(crm_event_emitted(CrmEvent::CrmMasterDataChanged(1, 1)));
+        let log = TemplateModule::changelog(1);
+        assert_eq!(log.len(), 2);
+        assert_eq!((log[0].action, log[0].who), (ChangeKind::GroupData, 1));
+        assert_ok!(TemplateModule::set_master_holders(Origin::signed(1), 1, vec![(3, 60), (4, 40)]));
+        let log = TemplateModule::changelog(1);
+        assert_eq!(log.len(), 3);
+        assert_eq!((log[0].action, log[0].who), (ChangeKind::Holders, 1));
+    });
+}
+
+#[test]
+fn required_approvals_are_rounded_up() {
+    assert_eq!(required_approvals(51, 3), 2);
+    assert_eq!(required_approvals(50, 4), 2);
+    assert_eq!(required_approvals(100, 5), 5);
+    assert_eq!(required_approvals(75, 10), 8);
+    assert_eq!(required_approvals(1, 1), 1);
+    assert_eq!(required_approvals(0, 7), 0);
+    assert_eq!(required_approvals(51, 0), 0);
+    // the quorum is capped at 100%, no overflow with the largest group
+    assert_eq!(required_approvals(200, 3), 3);
+    assert_eq!(required_approvals(100, u32::MAX), u32::MAX);
+    assert_eq!(required_approvals(51, u32::MAX), 2_190_433_321);
+}
+
+#[test]
+fn contracts_stored_are_capped() {
+    new_test_ext().execute_with(|| {
+        set_max_total_contracts(3);
+        for crmid in 1..=3 {
+            assert_ok!(new_contract(1, crmid, crmdata()));
+        }
+        assert_noop!(new_contract(2, 4, crmdata()), Error::<Test>::GlobalContractLimitReached.with_weight(EARLY_EXIT_WEIGHT));
+        // a removal frees the capacity
+        assert_ok!(TemplateModule::force_remove_contract(Origin::root(), 1));
+        assert_ok!(new_contract(2, 4, crmdata()));
+        assert_noop!(new_contract(2, 5, crmdata()), Error::<Test>::GlobalContractLimitReached.with_weight(EARLY_EXIT_WEIGHT));
+        // the limit is raised by root only
+        assert_noop!(TemplateModule::set_contract_limit(Origin::signed(1), 5), sp_runtime::traits::BadOrigin);
+        assert_ok!(TemplateModule::set_contract_limit(Origin::root(), 5));
+        assert!(crm_event_emitted(CrmEvent::ContractLimitSet(5)));
+        assert_eq!(TemplateModule::contract_limit(), 5);
+        assert_ok!(new_contract(2, 5, crmdata()));
+        assert_ok!(new_contract(3, 6, crmdata()));
+        assert_noop!(new_contract(3, 7, crmdata()), Error::<Test>::GlobalContractLimitReached.with_weight(EARLY_EXIT_WEIGHT));
+    });
+}
+
+#[test]
+fn unknown_fields_are_rejected_in_strict_schema() {
+    new_test_ext().execute_with(|| {
+        let misspelled = |crmdata: Vec<u8>| {
+            String::from_utf8(crmdata).unwrap().replace(r#""globalquorum""#, r#""crowdfundingshare":0,"globalquorum""#).into_bytes()
+        };
+        // the unknown fields are ignored by default
+        assert_ok!(new_contract(1, 1, misspelled(crmdata())));
+        set_strict_schema(true);
+        assert_noop!(new_contract(1, 2, misspelled(crmdata())), Error::<Test>::UnknownField);
+        assert_ok!(new_contract(1, 2, crmdata()));
+        assert_ok!(new_contract(1, 3, crmdata_with_type("single")));
+        // the changes are checked too
+        assert_noop!(
+            TemplateModule::patch_crmdata(Origin::signed(1), 2, br#"{"note":"x"}"#.to_vec()),
+            Error::<Test>::UnknownField
+        );
+        assert_ok!(TemplateModule::patch_crmdata(Origin::signed(1), 2, br#"{"type":"album"}"#.to_vec()));
+    });
+}
+
+#[test]
+fn creations_of_an_account_have_a_cooldown() {
+    new_test_ext().execute_with(|| {
+        set_creation_cooldown(3);
+        System::set_block_number(1);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        // the creations of the same block count as one
+        assert_ok!(new_contract(1, 2, crmdata()));
+        assert_eq!(TemplateModule::last_creation(1), Some(1));
+        System::set_block_number(2);
+        assert_noop!(new_contract(1, 3, crmdata()), Error::<Test>::CreationRateLimited.with_weight(EARLY_EXIT_WEIGHT));
+        // the cooldown is per account
+        assert_ok!(new_contract(2, 3, crmdata()));
+        System::set_block_number(4);
+        assert_ok!(new_contract(1, 4, crmdata()));
+        System::set_block_number(5);
+        assert_noop!(new_contract(1, 5, crmdata()), Error::<Test>::CreationRateLimited.with_weight(EARLY_EXIT_WEIGHT));
+        // an account exempted by root is not limited
+        assert_noop!(TemplateModule::set_cooldown_exempt(Origin::signed(1), 1, true), sp_runtime::traits::BadOrigin);
+        assert_ok!(TemplateModule::set_cooldown_exempt(Origin::root(), 1, true));
+        assert!(crm_event_emitted(CrmEvent::CooldownExemptSet(1, true)));
+        assert_ok!(new_contract(1, 5, crmdata()));
+        System::set_block_number(6);
+        assert_ok!(new_contract(1, 6, crmdata()));
+        assert_ok!(TemplateModule::set_cooldown_exempt(Origin::root(), 1, false));
+        System::set_block_number(7);
+        assert_noop!(new_contract(1, 7, crmdata()), Error::<Test>::CreationRateLimited.with_weight(EARLY_EXIT_WEIGHT));
+    });
+}
+
+#[test]
+fn co_owned_contract_requires_the_approvals_of_the_threshold() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_noop!(TemplateModule::set_owners(Origin::signed(2), 1, vec![1, 2], 1), Error::<Test>::SignerIsNotOwner);
+        // the owner must be listed once and the threshold between 1 and the owners
+        assert_noop!(TemplateModule::set_owners(Origin::signed(1), 1, vec![2, 3], 1), Error::<Test>::InvalidOwnerSet);
+        assert_noop!(TemplateModule::set_owners(Origin::signed(1), 1, vec![1, 2, 2], 2), Error::<Test>::InvalidOwnerSet);
+        assert_noop!(TemplateModule::set_owners(Origin::signed(1), 1, vec![1, 2, 3], 4), Error::<Test>::InvalidOwnerSet);
+        assert_noop!(TemplateModule::set_owners(Origin::signed(1), 1, vec![1, 2, 3], 0), Error::<Test>::InvalidOwnerSet);
+        assert_ok!(TemplateModule::set_owners(Origin::signed(1), 1, vec![1, 2, 3], 2));
+        assert!(crm_event_emitted(CrmEvent::OwnersUpdated(1, 1)));
+        // the co-owners change the contract as the owner
+        assert_ok!(TemplateModule::add_editor(Origin::signed(2), 1, 4));
+        assert_noop!(TemplateModule::transfer_contract(Origin::signed(1), 1, 4), Error::<Test>::ApprovalRequired);
+        assert_noop!(
+            TemplateModule::revoke_contract(Origin::signed(3), 1),
+            Error::<Test>::ApprovalRequired.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        // below the threshold the action is pending
+        assert_ok!(TemplateModule::approve_owner_action(Origin::signed(2), 1, OwnerAction::Transfer(4)));
+        assert!(crm_event_emitted(CrmEvent::OwnerActionApproved(2, 1, 1)));
+        assert_eq!(TemplateModule::get_crm_owner(1), Some(1));
+        assert_noop!(
+            TemplateModule::approve_owner_action(Origin::signed(2), 1, OwnerAction::Transfer(4)),
+            Error::<Test>::AlreadyApproved
+        );
+        assert_noop!(
+            TemplateModule::approve_owner_action(Origin::signed(4), 1, OwnerAction::Transfer(4)),
+            Error::<Test>::SignerIsNotOwner
+        );
+        // a different action waits for its own approvals, the pending one keeps its approvals
+        assert_ok!(TemplateModule::approve_owner_action(Origin::signed(3), 1, OwnerAction::Revoke));
+        assert_eq!(
+            TemplateModule::get_pending_owner_actions(1),
+            vec![(OwnerAction::Transfer(4), vec![2]), (OwnerAction::Revoke, vec![3])]
+        );
+        assert!(TemplateModule::get_crm_revoked(1).is_none());
+        // the approval reaching the threshold executes the action
+        assert_ok!(TemplateModule::approve_owner_action(Origin::signed(1), 1, OwnerAction::Transfer(4)));
+        assert!(crm_event_emitted(CrmEvent::ContractTransferred(1, 1, 4)));
+        assert!(crm_event_emitted(CrmEvent::OwnerActionExecuted(1)));
+        assert_eq!(TemplateModule::get_crm_owner(1), Some(4));
+        assert!(TemplateModule::get_owner_set(1).is_none());
+        // the transfer drops the actions approved by the previous owners
+        assert!(TemplateModule::get_pending_owner_actions(1).is_empty());
+        assert!(TemplateModule::get_crm_revoked(1).is_none());
+        assert!(!TemplateModule::is_owner(1, &2));
+    });
+}
+
+#[test]
+fn co_owned_contract_approves_the_withholding_account_and_the_licenses() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(TemplateModule::set_owners(Origin::signed(1), 1, vec![1, 2, 3], 2));
+        assert_ok!(TemplateModule::request_license(Origin::signed(4), 1, b"QmTerms".to_vec(), 200));
+        assert_noop!(TemplateModule::set_withholding_account(Origin::signed(1), 1, Some(5)), Error::<Test>::ApprovalRequired);
+        assert_noop!(TemplateModule::grant_license(Origin::signed(1), 0, None), Error::<Test>::ApprovalRequired);
+        // both actions are pending at the same time
+        assert_ok!(TemplateModule::approve_owner_action(Origin::signed(1), 1, OwnerAction::SetWithholdingAccount(Some(5))));
+        assert_ok!(TemplateModule::approve_owner_action(Origin::signed(2), 1, OwnerAction::GrantLicense(0, None)));
+        assert_eq!(TemplateModule::get_pending_owner_actions(1).len(), 2);
+        assert_ok!(TemplateModule::approve_owner_action(Origin::signed(3), 1, OwnerAction::SetWithholdingAccount(Some(5))));
+        assert_eq!(TemplateModule::get_withholding_account(1), Some(5));
+        assert_eq!(TemplateModule::get_pending_owner_actions(1), vec![(OwnerAction::GrantLicense(0, None), vec![2])]);
+        assert_ok!(TemplateModule::approve_owner_action(Origin::signed(1), 1, OwnerAction::GrantLicense(0, None)));
+        assert!(crm_event_emitted(CrmEvent::LicenseGranted(4, 0, 1)));
+        assert!(TemplateModule::get_pending_owner_actions(1).is_empty());
+        // the number of the pending actions is bounded
+        for account in 10..18 {
+            assert_ok!(TemplateModule::approve_owner_action(Origin::signed(1), 1, OwnerAction::SetWithholdingAccount(Some(account))));
+        }
+        assert_noop!(
+            TemplateModule::approve_owner_action(Origin::signed(1), 1, OwnerAction::Revoke),
+            Error::<Test>::TooManyPendingActions
+        );
+    });
+}
+
+#[test]
+fn migration_keeps_the_pending_owner_action() {
+    use frame_support::{storage::StorageMap, traits::OnRuntimeUpgrade};
+    new_test_ext().execute_with(|| {
+        // the single pending action of the earlier versions
+        let key = crate::PendingOwnerActions::<Test>::hashed_key_for(1);
+        frame_support::storage::unhashed::put(&key, &(OwnerAction::<u128, u64>::Revoke, vec![3u128]));
+        crate::CrmStorageVersion::<Test>::put(7);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::crm_storage_version(), crate::STORAGE_VERSION);
+        assert_eq!(TemplateModule::get_pending_owner_actions(1), vec![(OwnerAction::Revoke, vec![3])]);
+    });
+}
+
+// externalities with the contracts of the genesis config
+fn genesis_ext(contracts: Vec<(u128, u32, Vec<u8>)>) -> sp_io::TestExternalities {
+    use frame_support::traits::GenesisBuild;
+    let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    pallet_template::GenesisConfig::<Test> { contracts }.assimilate_storage(&mut t).unwrap();
+    sp_io::TestExternalities::new(t)
+}
+
+#[test]
+fn contracts_of_the_genesis_are_stored() {
+    genesis_ext(vec![(1, 1, crmdata()), (2, 2, crmdata_with_type("album"))]).execute_with(|| {
+        assert_eq!(TemplateModule::get_crmdata(1), Some(crmdata()));
+        assert_eq!(TemplateModule::get_crmdata(2), Some(crmdata_with_type("album")));
+        assert_eq!(TemplateModule::get_crm_owner(1), Some(1));
+        assert_eq!(TemplateModule::get_crm_owner(2), Some(2));
+        assert_eq!(TemplateModule::total_crms(), 2);
+        assert_eq!(TemplateModule::total_contracts(), 2);
+        assert_eq!(TemplateModule::get_contract_count(1), 1);
+        assert_eq!(TemplateModule::crm_version(2), 1);
+        assert_eq!(TemplateModule::crm_timestamps(1), Some((0, 0, 1)));
+        // the contracts of the genesis are changed as the others
+        assert_ok!(upsert(2, 2, crmdata()));
+        assert_eq!(TemplateModule::crm_version(2), 2);
+    });
+}
+
+#[test]
+#[should_panic(expected = "invalid genesis contract 2")]
+fn invalid_contract_of_the_genesis_stops_the_build() {
+    let invalid = String::from_utf8(crmdata()).unwrap().replace(r#""mastershare":50"#, r#""mastershare":60"#).into_bytes();
+    genesis_ext(vec![(1, 1, crmdata()), (1, 2, invalid)]);
+}
+
+#[test]
+fn missing_crmdata_is_an_empty_json() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_eq!(TemplateModule::get_crmdata_or_empty(1, 1), crmdata());
+        // not owned by the account or not on chain
+        assert_eq!(TemplateModule::get_crmdata_or_empty(2, 1), b"{}".to_vec());
+        assert_eq!(TemplateModule::get_crmdata_or_empty(1, 2), b"{}".to_vec());
+    });
+}
+
+#[test]
+fn every_private_hash_is_validated() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(new_contract(1, 1, crmdata_with_private("[]")), Error::<Test>::InvalidIpfsHashPrivate);
+        // one malformed hash among valid ones
+        let hashes = format!(r#"["{}","x","{}1"]"#, IPFSHASHPRIVATE, &IPFSHASHPRIVATE[..45]);
+        assert_noop!(new_contract(1, 1, crmdata_with_private(&hashes)), Error::<Test>::InvalidIpfsHashPrivate);
+        let hashes = format!(r#"["{}","{}-"]"#, IPFSHASHPRIVATE, &IPFSHASHPRIVATE[..45]);
+        assert_noop!(new_contract(1, 1, crmdata_with_private(&hashes)), Error::<Test>::InvalidIpfsHashPrivate);
+        let cap = <Test as crate::Config>::MaxPrivateHashes::get() as usize;
+        assert_noop!(new_contract(1, 1, crmdata_with_private(&private_hashes(cap + 1))), Error::<Test>::TooManyPrivateHashes);
+        assert_ok!(new_contract(1, 1, crmdata_with_private(&private_hashes(cap))));
+        // a single string is an array of one hash
+        assert_ok!(new_contract(1, 2, crmdata()));
+        assert_eq!(TemplateModule::get_crm_contract_of(1, 2).unwrap().ipfshashprivate, vec![IPFSHASHPRIVATE.as_bytes().to_vec()]);
+        // the changes are checked too
+        assert_noop!(upsert(1, 2, crmdata_with_private(r#"["x"]"#)), Error::<Test>::InvalidIpfsHashPrivate);
+    });
+}
+
+#[test]
+fn private_hashes_require_the_public_hash() {
+    new_test_ext().execute_with(|| {
+        let without_public = String::from_utf8(crmdata()).unwrap().replace(&format!(r#""ipfshash":"{}","#, IPFSHASH), "").into_bytes();
+        assert_noop!(new_contract(1, 1, without_public.clone()), Error::<Test>::InvalidIpfsHash);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        // the public hash cannot be removed by a change
+        assert_noop!(upsert(1, 1, without_public), Error::<Test>::InvalidIpfsHash);
+        assert_noop!(
+            TemplateModule::patch_crmdata(Origin::signed(1), 1, br#"{"ipfshash":"Qm"}"#.to_vec()),
+            Error::<Test>::InvalidIpfsHash
+        );
+    });
+}
+
+#[test]
+fn cidv0_is_recognized() {
+    assert!(is_cidv0(IPFSHASH.as_bytes()));
+    assert!(is_cidv0(IPFSHASHPRIVATE.as_bytes()));
+    // '0' is not in the base58btc alphabet
+    assert!(!is_cidv0(format!("{}0", &IPFSHASH[..45]).as_bytes()));
+    assert!(!is_cidv0(IPFSHASH[..45].as_bytes()));
+    assert!(!is_cidv0(format!("{}1", IPFSHASH).as_bytes()));
+    // a hex digest is not a CID
+    assert!(!is_cidv0(b"0E7071C59DF3B9454D1D18A15270AA36D54F89606A576DC621757AFD44AD1D2E"));
+    assert!(!is_cidv0(format!("Zm{}", &IPFSHASH[2..]).as_bytes()));
+}
+
+#[test]
+fn strict_cid_accepts_the_cidv0_only() {
+    new_test_ext().execute_with(|| {
+        let with_public = |hash: &str| String::from_utf8(crmdata()).unwrap().replace(IPFSHASH, hash).into_bytes();
+        let invalid_char = format!("{}0", &IPFSHASH[..45]);
+        // the alphanumeric hashes are accepted by default
+        assert_ok!(new_contract(1, 1, with_public(&invalid_char)));
+        set_strict_cid(true);
+        assert_noop!(new_contract(1, 2, with_public(&invalid_char)), Error::<Test>::InvalidIpfsHash);
+        assert_noop!(new_contract(1, 2, with_public(&format!("{}1", IPFSHASH))), Error::<Test>::InvalidIpfsHash);
+        let hashes = format!(r#"["{}","{}0"]"#, IPFSHASHPRIVATE, &IPFSHASHPRIVATE[..45]);
+        assert_noop!(new_contract(1, 2, crmdata_with_private(&hashes)), Error::<Test>::InvalidIpfsHashPrivate);
+        assert_ok!(new_contract(1, 2, crmdata()));
+    });
+}
+
+#[test]
+fn amount_is_split_by_the_shares() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TemplateModule::split_amount(1, 1, 100), None);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_eq!(TemplateModule::split_amount(2, 1, 100), None);
+        assert_eq!(
+            TemplateModule::split_amount(1, 1, 100),
+            Some(vec![(ShareGroup::Master, 50), (ShareGroup::Composition, 50)])
+        );
+        // the remainder goes to the master
+        let parts = TemplateModule::split_amount(1, 1, 101).unwrap();
+        assert_eq!(parts, vec![(ShareGroup::Master, 51), (ShareGroup::Composition, 50)]);
+        assert_eq!(parts.iter().map(|(_, part)| part).sum::<u64>(), 101);
+        // every group with a share is listed
+        assert_ok!(new_contract_by_hash(1, 2, shares(40, 35, 25)));
+        let parts = TemplateModule::split_amount(1, 2, 999).unwrap();
+        assert_eq!(parts, vec![(ShareGroup::Master, 401), (ShareGroup::Composition, 349), (ShareGroup::OtherContracts, 249)]);
+        assert_eq!(parts.iter().map(|(_, part)| part).sum::<u64>(), 999);
+    });
+}
+
+// CIDv1 in base32 of the content of IPFSHASH and IPFSHASHPRIVATE
+const IPFSHASH_V1: &str = "bafybeiduiecxoeiqs3gyc6r7v3lymmhserldnpw62qjnhmqsulqjxjmtzi";
+const IPFSHASHPRIVATE_V1: &str = "bafybeicg2rebjoofv4kbyovkw7af3rpiitvnl6i7ckcywaq6xjcxnc2mby";
+
+#[test]
+fn cidv1_has_the_multihash_of_the_cidv0() {
+    assert_eq!(cid_multihash(IPFSHASH_V1.as_bytes()), cid_multihash(IPFSHASH.as_bytes()));
+    assert_eq!(cid_multihash(IPFSHASHPRIVATE_V1.as_bytes()), cid_multihash(IPFSHASHPRIVATE.as_bytes()));
+    assert_eq!(cid_multihash(IPFSHASH.as_bytes()).unwrap()[..2], [0x12, 0x20]);
+    // '1' is not in the base32 alphabet, the upper case is another multibase
+    assert_eq!(cid_multihash(format!("{}1", &IPFSHASH_V1[..58]).as_bytes()), None);
+    assert_eq!(cid_multihash(IPFSHASH_V1.to_uppercase().as_bytes()), None);
+    // truncated digest
+    assert_eq!(cid_multihash(IPFSHASH_V1[..52].as_bytes()), None);
+    assert_eq!(cid_multihash(b"0E7071C59DF3B9454D1D18A15270AA36D54F89606A576DC621757AFD44AD1D2E"), None);
+}
+
+#[test]
+fn cidv0_and_cidv1_of_the_same_content_collide() {
+    new_test_ext().execute_with(|| {
+        set_strict_cid(true);
+        let hashes = format!(r#"["{}","{}"]"#, IPFSHASHPRIVATE, IPFSHASHPRIVATE_V1);
+        assert_noop!(new_contract(1, 1, crmdata_with_private(&hashes)), Error::<Test>::DuplicatePrivateHash);
+        let hashes = format!(r#"["{}","{}"]"#, IPFSHASHPRIVATE, IPFSHASH_V1);
+        assert_noop!(new_contract(1, 1, crmdata_with_private(&hashes)), Error::<Test>::DuplicatePublicPrivateHash);
+        let malformed = format!(r#""{}1""#, &IPFSHASHPRIVATE_V1[..58]);
+        assert_noop!(new_contract(1, 1, crmdata_with_private(&malformed)), Error::<Test>::InvalidIpfsHashPrivate);
+        // the CIDv1 is accepted anywhere a hash is expected
+        let v1 = String::from_utf8(crmdata_with_private(&format!(r#""{}""#, IPFSHASHPRIVATE_V1))).unwrap().replace(IPFSHASH, IPFSHASH_V1);
+        assert_ok!(new_contract(1, 1, v1.into_bytes()));
+    });
+}
+
+#[test]
+fn contract_events_are_indexed_by_crmid() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, crmdata()));
+        assert_ok!(upsert(1, 1, crmdata_with_type("single")));
+        let topic = TemplateModule::crm_topic(1);
+        assert_ne!(topic, TemplateModule::crm_topic(2));
+        let indexed: Vec<_> = System::events()
+            .into_iter()
+            .filter(|r| r.topics.contains(&topic))
+            .map(|r| r.event)
+            .collect();
+        assert_eq!(indexed.len(), 2);
+        assert_eq!(indexed[0], Event::pallet_template(CrmEvent::CrmAdded(1, 1)));
+        assert_eq!(indexed[1], Event::pallet_template(CrmEvent::CrmDataChanged(1, 1, 2, ChangeKind::FullReplace)));
+        // the events of the contract are found by topic in the system storage
+        assert_eq!(System::event_topics(topic).len(), 2);
+        assert_eq!(System::event_topics(TemplateModule::crm_topic(2)).len(), 1);
+    });
+}
+
+// crm data with a public and a private hash of the given lengths
+fn crmdata_with_hash_lengths(public: usize, private: usize) -> Vec<u8> {
+    let data = crmdata_with_private(&format!(r#""{}""#, "c".repeat(private)));
+    String::from_utf8(data).unwrap().replace(IPFSHASH, &"b".repeat(public)).into_bytes()
+}
+
+#[test]
+fn ipfs_hashes_are_bounded() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(new_contract(1, 1, crmdata_with_hash_lengths(45, 46)), Error::<Test>::InvalidIpfsHash);
+        assert_noop!(new_contract(1, 1, crmdata_with_hash_lengths(129, 46)), Error::<Test>::InvalidIpfsHash);
+        assert_noop!(new_contract(1, 1, crmdata_with_hash_lengths(46, 45)), Error::<Test>::InvalidIpfsHashPrivate);
+        assert_noop!(new_contract(1, 1, crmdata_with_hash_lengths(46, 129)), Error::<Test>::InvalidIpfsHashPrivate);
+        assert_ok!(new_contract(1, 1, crmdata_with_hash_lengths(46, 128)));
+        assert_ok!(new_contract(1, 2, crmdata_with_hash_lengths(128, 46)));
+        assert_noop!(
+            TemplateModule::add_private_hash(Origin::signed(1), 1, "d".repeat(129).into_bytes(), None),
+            Error::<Test>::InvalidIpfsHashPrivate
+        );
+        assert_noop!(
+            TemplateModule::new_contract_by_hash(Origin::signed(1), 3, "e".repeat(45).into_bytes(), [1; 32], shares(40, 35, 25)),
+            Error::<Test>::InvalidIpfsHash
+        );
+    });
+}
+
+#[test]
+fn short_hashes_stored_before_the_bounds_are_kept() {
+    use frame_support::traits::OnRuntimeUpgrade;
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, crmdata()));
+        // a contract stored by an earlier runtime with short hashes
+        let legacy = |data: Vec<u8>| String::from_utf8(data).unwrap().replace(IPFSHASH, "QmShort").replace(IPFSHASHPRIVATE, "QmPrivate");
+        crate::CrmData::<Test>::insert(1, legacy(crmdata()).into_bytes());
+        crate::CrmStorageVersion::<Test>::put(4);
+        TemplateModule::on_runtime_upgrade();
+        assert_eq!(TemplateModule::crm_storage_version(), crate::STORAGE_VERSION);
+        assert!(TemplateModule::has_legacy_ipfshash(1));
+        assert!(!TemplateModule::has_legacy_ipfshash(2));
+        // the stored hashes are kept on an update, a new short hash is refused
+        assert_ok!(upsert(1, 1, legacy(crmdata_with_type("single")).into_bytes()));
+        let changed = legacy(crmdata()).replace("QmShort", "QmOther");
+        assert_noop!(upsert(1, 1, changed.into_bytes()), Error::<Test>::InvalidIpfsHash);
+        assert_noop!(
+            TemplateModule::add_private_hash(Origin::signed(1), 1, b"QmPrivate2".to_vec(), None),
+            Error::<Test>::InvalidIpfsHashPrivate
+        );
+        // the other contracts are not grandfathered
+        assert_noop!(upsert(1, 2, legacy(crmdata()).into_bytes()), Error::<Test>::InvalidIpfsHash);
+    });
+}
+
+#[test]
+fn crm_digest_follows_the_content() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(TemplateModule::crm_digest(1, 1), None);
+        assert_ok!(new_contract(1, 1, crmdata()));
+        assert_ok!(new_contract(1, 2, crmdata()));
+        let digest = TemplateModule::crm_digest(1, 1);
+        assert_eq!(digest, Some(sp_io::hashing::blake2_256(&crmdata())));
+        // identical content, identical digest
+        assert_eq!(TemplateModule::crm_digest(1, 2), digest);
+        assert_eq!(TemplateModule::crm_digest(2, 1), None);
+        assert_ok!(upsert(1, 1, crmdata_with_type("single")));
+        assert_ne!(TemplateModule::crm_digest(1, 1), digest);
+        assert_eq!(TemplateModule::crm_digest(1, 1), Some(sp_io::hashing::blake2_256(&crmdata_with_type("single"))));
+    });
+}
+
+// crmid beyond the range of u32, for the runtime with 64 bit crmids
+const WIDE_CRMID: u64 = 5_000_000_000;
+
+fn wide_contract(who: u128, crmid: u64, crmdata: Vec<u8>) -> frame_support::dispatch::DispatchResultWithPostInfo {
+    wide::TemplateModule::new_contract(wide::Origin::signed(who), crmid, crmdata, master(), composition(), Vec::new())
+}
+
+fn wide_event_emitted(e: CrmEvent<wide::Test>) -> bool {
+    wide::System::events()
+        .iter()
+        .any(|r| r.event == wide::Event::pallet_template(e.clone()))
+}
+
+#[test]
+fn crmid_type_is_configurable() {
+    wide::new_test_ext().execute_with(|| {
+        // the maximum of the crmid type is reserved, u32::MAX is a crmid as any other
+        assert_eq!(wide::TemplateModule::reserved_crm_id(), u64::MAX);
+        assert_noop!(
+            wide_contract(1, u64::MAX, crmdata()),
+            Error::<wide::Test>::ReservedCrmId.with_weight(EARLY_EXIT_WEIGHT)
+        );
+        assert_ok!(wide_contract(1, WIDE_CRMID, crmdata()));
+        assert_ok!(wide_contract(1, u32::MAX as u64, crmdata()));
+        assert!(wide_event_emitted(CrmEvent::CrmAdded(1, WIDE_CRMID)));
+        assert_eq!(wide::TemplateModule::get_crm_ids(1), vec![u32::MAX as u64, WIDE_CRMID]);
+        assert_eq!(wide::TemplateModule::contract_by_ipfshash(IPFSHASH.as_bytes().to_vec()), Some((1, WIDE_CRMID)));
+    });
+}
+
+#[test]
+fn wide_crmids_are_parsed_from_the_json() {
+    wide::new_test_ext().execute_with(|| {
+        assert_ok!(wide_contract(1, WIDE_CRMID, crmdata()));
+        assert_ok!(wide_contract(1, WIDE_CRMID + 1, crmdata_with_parent(WIDE_CRMID, 10)));
+        assert_eq!(wide::TemplateModule::get_derivative_of(WIDE_CRMID + 1), Some((WIDE_CRMID, Percent::from_percent(10))));
+        let proposal = String::from_utf8(crmdata_with_ipfshash(7))
+            .unwrap()
+            .replacen("{", &format!(r#"{{"crmid":{},"#, WIDE_CRMID), 1);
+        assert_ok!(wide::TemplateModule::change_proposal_crmdata(wide::Origin::signed(1), 1, proposal.into_bytes()));
+        assert!(wide_event_emitted(CrmEvent::CrmDataNewChangeProposal(1, WIDE_CRMID, 1)));
+        assert_ok!(wide::TemplateModule::vote_proposal_crmdata(wide::Origin::signed(1), 1, true));
+        assert_ok!(wide::TemplateModule::vote_proposal_crmdata(wide::Origin::signed(2), 1, true));
+        assert!(wide_event_emitted(CrmEvent::CrmDataChanged(2, WIDE_CRMID, 2, ChangeKind::FullReplace)));
+    });
+}
+
+#[test]
+fn crmids_out_of_the_range_of_the_crmid_type_are_invalid() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(new_contract(1, 1, crmdata()));
+        // 2^32 + 1 is not truncated to the contract 1
+        assert_noop!(
+            new_contract(1, 2, crmdata_with_parent(u32::MAX as u64 + 2, 10)),
+            Error::<Test>::ParentContractNotFound
+        );
+        let proposal = String::from_utf8(crmdata_with_ipfshash(7))
+            .unwrap()
+            .replacen("{", &format!(r#"{{"crmid":{},"#, u32::MAX as u64 + 2), 1);
+        assert_noop!(
+            TemplateModule::change_proposal_crmdata(Origin::signed(1), 1, proposal.into_bytes()),
+            Error::<Test>::InvalidContractId
+        );
+    });
+}
@@ -0,0 +1,169 @@
+//! PLACEHOLDER weights of the crm pallet: the values below are hand-written estimates, they have NOT been generated by
+//! the benchmarks in `benchmarking.rs`. The layout is the one of the benchmark CLI, the file must be replaced by its
+//! output on the reference hardware before a release:
+//!
+//! ./target/release/polkamusic-node benchmark --chain dev --execution wasm --wasm-execution compiled
+//!     --pallet pallet_crm --extrinsic '*' --steps 50 --repeat 20 --output ./pallets/crm/src/weights.rs
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight, Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed by the crm pallet.
+pub trait WeightInfo {
+    fn new_contract(l: u32) -> Weight;
+    fn patch_crmdata(l: u32) -> Weight;
+    fn change_proposal_crmdata(l: u32) -> Weight;
+    fn vote_proposal_crmdata(l: u32) -> Weight;
+    fn change_proposal_crm_masterdata() -> Weight;
+    fn vote_proposal_crm_masterdata() -> Weight;
+    fn change_proposal_crm_compositiondata() -> Weight;
+    fn vote_proposal_crm_compositiondata() -> Weight;
+    fn change_proposal_crm_othercontractsdata() -> Weight;
+    fn vote_proposal_crm_othercontractsdata() -> Weight;
+    fn add_private_hash(l: u32) -> Weight;
+    fn set_crowdfounders(l: u32) -> Weight;
+}
+
+/// Weights of the crm pallet for the node, using the database weights of the runtime (placeholders, see above).
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn new_contract(l: u32) -> Weight {
+        (95_000_000 as Weight)
+            .saturating_add((160_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(T::DbWeight::get().reads(10 as Weight))
+            .saturating_add(T::DbWeight::get().writes(16 as Weight))
+    }
+    fn patch_crmdata(l: u32) -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add((190_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(T::DbWeight::get().reads(9 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn change_proposal_crmdata(l: u32) -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add((180_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(T::DbWeight::get().reads(8 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn vote_proposal_crmdata(l: u32) -> Weight {
+        (140_000_000 as Weight)
+            .saturating_add((230_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(T::DbWeight::get().reads(14 as Weight))
+            .saturating_add(T::DbWeight::get().writes(10 as Weight))
+    }
+    fn change_proposal_crm_masterdata() -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn vote_proposal_crm_masterdata() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn change_proposal_crm_compositiondata() -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(4 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn vote_proposal_crm_compositiondata() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(5 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn change_proposal_crm_othercontractsdata() -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(14 as Weight))
+            .saturating_add(T::DbWeight::get().writes(2 as Weight))
+    }
+    fn vote_proposal_crm_othercontractsdata() -> Weight {
+        (95_000_000 as Weight)
+            .saturating_add(T::DbWeight::get().reads(14 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn add_private_hash(l: u32) -> Weight {
+        (65_000_000 as Weight)
+            .saturating_add((170_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(T::DbWeight::get().reads(8 as Weight))
+            .saturating_add(T::DbWeight::get().writes(6 as Weight))
+    }
+    fn set_crowdfounders(l: u32) -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add((150_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(T::DbWeight::get().reads(9 as Weight))
+            .saturating_add(T::DbWeight::get().writes(8 as Weight))
+    }
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+    fn new_contract(l: u32) -> Weight {
+        (95_000_000 as Weight)
+            .saturating_add((160_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(RocksDbWeight::get().reads(10 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(16 as Weight))
+    }
+    fn patch_crmdata(l: u32) -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add((190_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(RocksDbWeight::get().reads(9 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(6 as Weight))
+    }
+    fn change_proposal_crmdata(l: u32) -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add((180_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(RocksDbWeight::get().reads(8 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn vote_proposal_crmdata(l: u32) -> Weight {
+        (140_000_000 as Weight)
+            .saturating_add((230_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(RocksDbWeight::get().reads(14 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(10 as Weight))
+    }
+    fn change_proposal_crm_masterdata() -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn vote_proposal_crm_masterdata() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(5 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(6 as Weight))
+    }
+    fn change_proposal_crm_compositiondata() -> Weight {
+        (55_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(4 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn vote_proposal_crm_compositiondata() -> Weight {
+        (60_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(5 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(6 as Weight))
+    }
+    fn change_proposal_crm_othercontractsdata() -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(14 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(2 as Weight))
+    }
+    fn vote_proposal_crm_othercontractsdata() -> Weight {
+        (95_000_000 as Weight)
+            .saturating_add(RocksDbWeight::get().reads(14 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(6 as Weight))
+    }
+    fn add_private_hash(l: u32) -> Weight {
+        (65_000_000 as Weight)
+            .saturating_add((170_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(RocksDbWeight::get().reads(8 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(6 as Weight))
+    }
+    fn set_crowdfounders(l: u32) -> Weight {
+        (70_000_000 as Weight)
+            .saturating_add((150_000 as Weight).saturating_mul(l as Weight))
+            .saturating_add(RocksDbWeight::get().reads(9 as Weight))
+            .saturating_add(RocksDbWeight::get().writes(8 as Weight))
+    }
+}
@@ -9,7 +9,7 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 use sp_std::prelude::*;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
-	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature,
+	ApplyExtrinsicResult, generic, create_runtime_str, impl_opaque_keys, MultiSignature, ModuleId,
 	transaction_validity::{TransactionValidity, TransactionSource},
 };
 use sp_runtime::traits::{
@@ -64,6 +64,9 @@ pub type Index = u32;
 /// A hash of some data used by the chain.
 pub type Hash = sp_core::H256;
 
+/// Identifier of a contract of the crm pallet.
+pub type CrmId = u32;
+
 /// Digest item type.
 pub type DigestItem = generic::DigestItem<Hash>;
 
@@ -252,14 +255,98 @@ impl pallet_transaction_payment::Config for Runtime {
 	type WeightToFee = IdentityFee<Balance>;
 	type FeeMultiplierUpdate = ();
 }
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * BlockWeights::get().max_block;
+	pub const MaxScheduledPerBlock: u32 = 50;
+}
+// Scheduler of the dispatches at a given block, used to close the crowdfunding campaigns
+impl pallet_scheduler::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	type ScheduleOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxScheduledPerBlock = MaxScheduledPerBlock;
+	type WeightInfo = ();
+}
 // Super user module (SUDO)
 impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 }
+parameter_types! {
+	pub const CrmModuleId: ModuleId = ModuleId(*b"py/crmfd");
+	pub const MaxCrmDataLength: u32 = 1024;
+	pub const MinCrmDataLength: u32 = 32;
+	pub const MaxPrivateHashes: u32 = 10;
+	pub const MinIpfsHashLength: u32 = 46;
+	pub const MaxIpfsHashLength: u32 = 128;
+	pub const MaxContractsPerAccount: u32 = 1000;
+	pub const MaxContractsPerBlock: u32 = 100;
+	pub const MaxTotalContracts: u32 = 1_000_000;
+	pub const CreationCooldown: BlockNumber = 10;
+	pub const ReservedCrmIdMax: CrmId = 1000;
+	pub const MaxPoolPayouts: u32 = 100;
+	pub const MaxTerritories: u32 = 250;
+	pub const SoftDelete: bool = false;
+	pub const UpdateLockPeriod: BlockNumber = 0;
+	pub const StrictJson: bool = false;
+	pub const UniqueIpfsHash: bool = false;
+	pub const SingleHolderQuorum: bool = false;
+	pub const StrictSchema: bool = false;
+	pub const StrictCid: bool = false;
+	pub const MaxJsonDepth: u32 = 8;
+	pub const MaxListReturn: u32 = 1000;
+	pub const MaxChangelogEntries: u32 = 20;
+	pub const DepositBase: Balance = 100_000;
+	pub const DepositPerByte: Balance = 1_000;
+	pub const CreationFee: Balance = 10_000;
+}
 // Contract Right Management Contract
 impl pallet_crm::Config for Runtime {
 	type Event = Event;
+	type Currency = Balances;
+	type ModuleId = CrmModuleId;
+	type MaxCrmDataLength = MaxCrmDataLength;
+	type MinCrmDataLength = MinCrmDataLength;
+	type MaxPrivateHashes = MaxPrivateHashes;
+	type MinIpfsHashLength = MinIpfsHashLength;
+	type MaxIpfsHashLength = MaxIpfsHashLength;
+	type MaxContractsPerAccount = MaxContractsPerAccount;
+	type MaxContractsPerBlock = MaxContractsPerBlock;
+	type MaxTotalContracts = MaxTotalContracts;
+	type CreationCooldown = CreationCooldown;
+	type CrmId = CrmId;
+	type ReservedCrmIdMax = ReservedCrmIdMax;
+	type MaxPoolPayouts = MaxPoolPayouts;
+	type MaxTerritories = MaxTerritories;
+	type SoftDelete = SoftDelete;
+	type UpdateLockPeriod = UpdateLockPeriod;
+	type StrictJson = StrictJson;
+	type UniqueIpfsHash = UniqueIpfsHash;
+	type SingleHolderQuorum = SingleHolderQuorum;
+	type StrictSchema = StrictSchema;
+	type StrictCid = StrictCid;
+	type MaxJsonDepth = MaxJsonDepth;
+	type MaxListReturn = MaxListReturn;
+	type MaxChangelogEntries = MaxChangelogEntries;
+	type DepositBase = DepositBase;
+	type DepositPerByte = DepositPerByte;
+	type CreationFee = CreationFee;
+	// there is no treasury in this runtime, the creation fees are burnt
+	type CreationFeeHandler = ();
+	type SchedulerCall = Call;
+	type PalletsOrigin = OriginCaller;
+	type Scheduler = Scheduler;
+	// there is no treasury in this runtime, the dust is burnt
+	type DustHandler = ();
+	type Signature = Signature;
+	type Public = <Signature as Verify>::Signer;
+	type ReporterAdminOrigin = frame_system::EnsureRoot<AccountId>;
+	// there is no assets pallet in this runtime, the royalties are paid in the native currency only
+	type Assets = ();
+	type WeightInfo = pallet_crm::weights::SubstrateWeight<Runtime>;
 }
 
 
@@ -278,7 +365,8 @@ construct_runtime!(
 		TransactionPayment: pallet_transaction_payment::{Module, Storage},
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
 		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
-		Crm: pallet_crm::{Module, Call, Storage, Event<T>},
+		Scheduler: pallet_scheduler::{Module, Call, Storage, Event<T>},
+		Crm: pallet_crm::{Module, Call, Storage, Event<T>, Config<T>},
 	}
 );
 
@@ -313,6 +401,8 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllModules,
+	// the crm storage is migrated by the pallet, these are the try-runtime checks around the upgrade
+	pallet_crm::migrations::CrmMigrationChecks<Runtime>,
 >;
 
 impl_runtime_apis! {
@@ -447,6 +537,24 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_crm_rpc_runtime_api::CrmApi<Block, AccountId, CrmId> for Runtime {
+		fn get_crm_contract(account: AccountId, crmid: CrmId) -> Option<pallet_crm::CrmContract> {
+			Crm::get_crm_contract_of(account, crmid)
+		}
+		fn crm_digest(account: AccountId, crmid: CrmId) -> Option<[u8; 32]> {
+			Crm::crm_digest(account, crmid)
+		}
+	}
+
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade() -> Result<(Weight, Weight), sp_runtime::RuntimeString> {
+			frame_support::debug::RuntimeLogger::init();
+			let weight = Executive::try_runtime_upgrade()?;
+			Ok((weight, BlockWeights::get().max_block))
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -481,6 +589,7 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
+			add_benchmark!(params, batches, pallet_crm, Crm);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)